@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use bevy_chess::net::server::{Server, ServerConfig};
+use bevy_chess::net::DEFAULT_PORT;
+use bevy_chess::rules::board::Variant;
+
+// how long to wait between polls when there's nothing to do
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}: {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+fn main() {
+    let _ = log::set_logger(&LOGGER).map(|()| log::set_max_level(LevelFilter::Info));
+
+    // everything can optionally be picked with the arguments, in this order, e.g.
+    // `bevy_chess_server 0.0.0.0:7878 games capablanca 10+5`
+    let mut args = std::env::args().skip(1);
+    let address = args
+        .next()
+        .unwrap_or_else(|| format!("127.0.0.1:{}", DEFAULT_PORT));
+    let games_dir = PathBuf::from(args.next().unwrap_or_else(|| String::from("games")));
+    let variant = match args.next() {
+        Some(arg) => arg.parse().unwrap_or_else(|err| {
+            eprintln!("{}, falling back to standard chess", err);
+            Variant::Standard
+        }),
+        None => Variant::Standard,
+    };
+    let time_control = args.next().and_then(|arg| {
+        arg.parse()
+            .map_err(|err| eprintln!("{}, playing without a clock", err))
+            .ok()
+    });
+
+    let mut server = match Server::bind(
+        &address,
+        ServerConfig {
+            variant,
+            time_control,
+            games_dir: Some(games_dir),
+            ..ServerConfig::default()
+        },
+    ) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    log::info!("waiting for players on ws://{}/", address);
+
+    loop {
+        server.poll();
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::theme::DEFAULT_THEME_PATH;
+
+// the board is drawn with this many pixels per square, piece textures are scaled to fit
+pub const TEXTURE_TILE_SIZE: f32 = 96.;
+
+// how the board is laid out and where its assets are loaded from
+#[derive(Resource, Debug, Clone)]
+pub struct ChessConfig {
+    // the centre of the board, in world coordinates
+    pub board_position: Vec2,
+    // the size of a square in world units, everything drawn around the board is scaled with it
+    pub tile_size: f32,
+    // how long it takes a piece to move to its new tile, in seconds, 0 to move instantly
+    pub animation_duration: f32,
+    pub assets: ChessAssetPaths,
+}
+
+impl Default for ChessConfig {
+    fn default() -> Self {
+        Self {
+            board_position: Vec2::ZERO,
+            tile_size: TEXTURE_TILE_SIZE,
+            animation_duration: 0.2,
+            assets: ChessAssetPaths::default(),
+        }
+    }
+}
+
+impl ChessConfig {
+    // a transform that puts the tilemap's centre on the board position
+    pub fn tilemap_transform(
+        &self,
+        map_size: &TilemapSize,
+        grid_size: &TilemapGridSize,
+        map_type: &TilemapType,
+        z: f32,
+    ) -> Transform {
+        let scale = self.tile_size / TEXTURE_TILE_SIZE;
+        let centered = get_tilemap_center_transform(map_size, grid_size, map_type, z);
+        Transform {
+            translation: (centered.translation.truncate() * scale + self.board_position).extend(z),
+            scale: Vec3::new(scale, scale, 1.),
+            ..default()
+        }
+    }
+}
+
+// paths relative to the asset folder
+#[derive(Debug, Clone)]
+pub struct ChessAssetPaths {
+    pub move_indicator: String,
+    pub take_indicator: String,
+    pub checked_tile: String,
+    pub hover_ring: String,
+    // themes that can be picked from in the settings, the textures, colours and fonts of the
+    // board come from the selected one
+    pub themes: Vec<String>,
+}
+
+impl Default for ChessAssetPaths {
+    fn default() -> Self {
+        Self {
+            move_indicator: String::from("move_indicator.png"),
+            take_indicator: String::from("take_indicator.png"),
+            checked_tile: String::from("checked_tile.png"),
+            hover_ring: String::from("hover_ring.png"),
+            themes: vec![
+                String::from(DEFAULT_THEME_PATH),
+                String::from("themes/terminal.theme.ron"),
+            ],
+        }
+    }
+}
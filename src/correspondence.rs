@@ -0,0 +1,443 @@
+// correspondence chess, played by sending a game file back and forth, one move at a time. the file
+// holds the moves, the game written as PGN for other chess software, and what's needed to check
+// that the moves weren't changed on the way:
+//
+// - every move is hashed together with the hash of the move before it, so that a move can't be
+//   changed, dropped or swapped without every hash after it changing too
+// - every move comes with the next key of the side that made it. a side's keys are a chain of
+//   hashes of a secret only that player knows, given out backwards: every key hashes to the one
+//   given before it, and the first one to the public key the side joined the game with. only
+//   the player holding the secret can give the next one, so no one else can move for them
+//
+// the player receiving the file also checks it against their own copy from after their last move,
+// which catches files older than that copy and changes to moves that were already made
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::rules::board::{Board, Variant};
+use crate::rules::pgn::{pgn_date, write_pgn, GameResult};
+use crate::rules::pieces::ChessPieceColour;
+use crate::rules::MoveFromTo;
+
+// bumped whenever a change to game files can't be read by older versions of the game
+pub const CORRESPONDENCE_VERSION: u32 = 1;
+
+// the most moves a side can make in one game, every move uses up one of its keys
+pub const KEY_CHAIN_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Player {
+    pub name: String,
+    // what the first key of the player hashes to
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedMove {
+    // in long algebraic notation, e.g. `e2e4`
+    pub uci: String,
+    // the next key of the side that made the move
+    pub key: String,
+    // of the move, the key, the player and the hash of the move before it
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorrespondenceGame {
+    pub version: u32,
+    // picked at random when the game is started, so that every game gets different keys
+    pub id: String,
+    pub variant: String,
+    // the day the game was started, written like in PGN, e.g. `2023.01.31`
+    pub date: String,
+    // a side joins the game with its first move
+    pub white: Option<Player>,
+    pub black: Option<Player>,
+    pub moves: Vec<SignedMove>,
+    // only written from the moves, never read
+    pub pgn: String,
+}
+
+impl CorrespondenceGame {
+    pub fn new(variant: Variant, timestamp: u64) -> Self {
+        let mut game = Self {
+            version: CORRESPONDENCE_VERSION,
+            id: random_hex(16),
+            variant: variant.to_string(),
+            date: pgn_date(timestamp),
+            white: None,
+            black: None,
+            moves: Vec::new(),
+            pgn: String::new(),
+        };
+        game.pgn = game
+            .write_pgn(&Board::from_variant(variant))
+            .expect("a game without moves can always be written");
+        game
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        Self::parse(&contents).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let game: CorrespondenceGame = ron::from_str(contents).map_err(|err| err.to_string())?;
+        if game.version > CORRESPONDENCE_VERSION {
+            return Err(format!(
+                "game file version {} is newer than this game's {}",
+                game.version, CORRESPONDENCE_VERSION
+            ));
+        }
+        Ok(game)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("couldn't create {}: {}", dir.display(), err))?;
+        }
+        fs::write(path, contents)
+            .map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+    }
+
+    // white moves first
+    pub fn to_move(&self) -> ChessPieceColour {
+        match self.moves.len() % 2 {
+            0 => ChessPieceColour::White,
+            _ => ChessPieceColour::Black,
+        }
+    }
+
+    pub fn player(&self, colour: ChessPieceColour) -> Option<&Player> {
+        match colour {
+            ChessPieceColour::White => self.white.as_ref(),
+            ChessPieceColour::Black => self.black.as_ref(),
+        }
+    }
+
+    fn player_mut(&mut self, colour: ChessPieceColour) -> &mut Option<Player> {
+        match colour {
+            ChessPieceColour::White => &mut self.white,
+            ChessPieceColour::Black => &mut self.black,
+        }
+    }
+
+    // the side played by whoever holds the secret, if they've joined the game
+    pub fn side_of(&self, secret: &str) -> Option<ChessPieceColour> {
+        [ChessPieceColour::White, ChessPieceColour::Black]
+            .into_iter()
+            .find(|colour| {
+                self.player(*colour)
+                    .is_some_and(|player| player.public_key == self.key(secret, *colour, 0))
+            })
+    }
+
+    // the key for the side's move with the given number, counting from 1. 0 is the public key
+    fn key(&self, secret: &str, colour: ChessPieceColour, number: usize) -> String {
+        let mut key = digest(&[secret, &self.id, &format!("{:?}", colour)]);
+        for _ in number..KEY_CHAIN_LENGTH {
+            key = digest(&[&key]);
+        }
+        key
+    }
+
+    // what the first move is chained to
+    fn first_hash(&self) -> String {
+        digest(&[&self.id, &self.variant, &self.date])
+    }
+
+    // replays the moves, checking them against the rules, the keys of the players and the hashes.
+    // returns the position after the last move
+    pub fn verify(&self) -> Result<Board, String> {
+        let mut board = Board::from_variant(self.variant.parse()?);
+        let mut previous_hash = self.first_hash();
+        let mut white_key = self.white.as_ref().map(|player| player.public_key.clone());
+        let mut black_key = self.black.as_ref().map(|player| player.public_key.clone());
+
+        for (ply, signed) in self.moves.iter().enumerate() {
+            let colour = match ply % 2 {
+                0 => ChessPieceColour::White,
+                _ => ChessPieceColour::Black,
+            };
+            let player = self
+                .player(colour)
+                .ok_or(format!("{:?} hasn't joined the game", colour))?;
+            let previous_key = match colour {
+                ChessPieceColour::White => &mut white_key,
+                ChessPieceColour::Black => &mut black_key,
+            };
+            if previous_key.as_deref() != Some(digest(&[&signed.key]).as_str()) {
+                return Err(format!("{} wasn't made by {}", describe(ply), player.name));
+            }
+            *previous_key = Some(signed.key.clone());
+
+            let hash = digest(&[
+                &previous_hash,
+                &signed.uci,
+                &signed.key,
+                &player.name,
+                &player.public_key,
+            ]);
+            if hash != signed.hash {
+                return Err(format!("{} was changed", describe(ply)));
+            }
+            previous_hash = hash;
+
+            let _move = board
+                .parse_move(&signed.uci, colour)
+                .map_err(|err| format!("{}: {}", describe(ply), err))?;
+            board.play(&_move);
+        }
+
+        if self.pgn != self.write_pgn(&board)? {
+            return Err(String::from("the PGN doesn't match the moves"));
+        }
+        Ok(board)
+    }
+
+    // checks a file received from the other player against the copy kept after the last move of
+    // the given side. it has to be the same game with at most one move made since
+    pub fn check_reply(&self, copy: &Self, colour: ChessPieceColour) -> Result<(), String> {
+        if (&self.id, &self.variant, &self.date) != (&copy.id, &copy.variant, &copy.date) {
+            return Err(String::from("this is a different game"));
+        }
+        if self.moves.len() < copy.moves.len() {
+            return Err(String::from("the file is older than your copy of the game"));
+        }
+        if self.moves.len() > copy.moves.len() + 1 {
+            return Err(String::from("more than one move was made since yours"));
+        }
+        if let Some(ply) = (0..copy.moves.len()).find(|ply| self.moves[*ply] != copy.moves[*ply]) {
+            return Err(format!("{} was changed", describe(ply)));
+        }
+        for side in [ChessPieceColour::White, ChessPieceColour::Black] {
+            if copy.player(side).is_some() && self.player(side) != copy.player(side) {
+                return Err(format!("{:?} was replaced by someone else", side));
+            }
+        }
+        if self.moves.len() > copy.moves.len() && copy.to_move() == colour {
+            return Err(String::from("someone else made your move"));
+        }
+        Ok(())
+    }
+
+    // makes the next move for whoever holds the secret, who joins the game under the given name
+    // if it's the first move of their side
+    pub fn play(&mut self, uci: &str, name: &str, secret: &str) -> Result<(), String> {
+        let mut board = self.verify()?;
+        let colour = self.to_move();
+        let public_key = self.key(secret, colour, 0);
+        match self.player(colour) {
+            Some(player) if player.public_key != public_key => {
+                return Err(format!("it's {}'s move", player.name))
+            }
+            None if self.side_of(secret) == Some(!colour) => {
+                return Err(String::from("it's the other player's move"))
+            }
+            _ => {}
+        }
+        let number = self.moves.len() / 2 + 1;
+        if number > KEY_CHAIN_LENGTH {
+            return Err(String::from("the game is too long to make any more moves"));
+        }
+        let _move: MoveFromTo = board.parse_move(uci, colour)?;
+
+        let player = self
+            .player_mut(colour)
+            .get_or_insert_with(|| Player {
+                name: name.to_string(),
+                public_key,
+            })
+            .clone();
+        let key = self.key(secret, colour, number);
+        let previous_hash = self
+            .moves
+            .last()
+            .map_or_else(|| self.first_hash(), |signed| signed.hash.clone());
+        let hash = digest(&[
+            &previous_hash,
+            &_move.to_string(),
+            &key,
+            &player.name,
+            &player.public_key,
+        ]);
+        self.moves.push(SignedMove {
+            uci: _move.to_string(),
+            key,
+            hash,
+        });
+
+        board.play(&_move);
+        self.pgn = self.write_pgn(&board)?;
+        Ok(())
+    }
+
+    // the game up to the given position, which is the one after the last move
+    fn write_pgn(&self, end: &Board) -> Result<String, String> {
+        let name = |colour| {
+            self.player(colour)
+                .map_or(String::from("?"), |player: &Player| player.name.clone())
+        };
+        let moves = self
+            .moves
+            .iter()
+            .map(|signed| signed.uci.parse())
+            .collect::<Result<Vec<MoveFromTo>, String>>()?;
+        write_pgn(
+            &[
+                ("Event", String::from("Correspondence game")),
+                ("Site", String::from("?")),
+                ("Date", self.date.clone()),
+                ("Round", String::from("-")),
+                ("White", name(ChessPieceColour::White)),
+                ("Black", name(ChessPieceColour::Black)),
+            ],
+            &Board::from_variant(self.variant.parse()?),
+            &moves,
+            GameResult::from_position(end, self.to_move()),
+        )
+    }
+}
+
+// e.g. `White's move 3`
+fn describe(ply: usize) -> String {
+    let colour = match ply % 2 {
+        0 => ChessPieceColour::White,
+        _ => ChessPieceColour::Black,
+    };
+    format!("{:?}'s move {}", colour, ply / 2 + 1)
+}
+
+// from the system's random number generator, so that neither game ids nor secrets can be guessed
+pub fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes).expect("the system has a random number generator");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// the parts are length prefixed, so that moving text from one part to the next changes the hash
+fn digest(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::correspondence::{digest, CorrespondenceGame};
+    use crate::rules::board::{Board, Variant};
+    use crate::rules::pieces::ChessPieceColour;
+
+    const ALICE: &str = "alice's secret";
+    const BOB: &str = "bob's secret";
+
+    // the file as it's sent, read back in
+    fn send(game: &CorrespondenceGame) -> CorrespondenceGame {
+        CorrespondenceGame::parse(&ron::to_string(game).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn games_are_played_by_exchanging_files() {
+        let mut game = CorrespondenceGame::new(Variant::Standard, 1704067199);
+        game.play("e2e4", "Alice", ALICE).unwrap();
+        let alice_copy = game.clone();
+
+        let mut game = send(&game);
+        game.verify().unwrap();
+        assert_eq!(None, game.side_of(BOB));
+        game.play("e7e5", "Bob", BOB).unwrap();
+        let bob_copy = game.clone();
+
+        let mut game = send(&game);
+        game.verify().unwrap();
+        game.check_reply(&alice_copy, ChessPieceColour::White)
+            .unwrap();
+        assert_eq!(Some(ChessPieceColour::White), game.side_of(ALICE));
+        assert_eq!(Some(ChessPieceColour::Black), game.side_of(BOB));
+        assert!(game.pgn.contains("[Date \"2023.12.31\"]"));
+        assert!(game.pgn.contains("[White \"Alice\"]\n[Black \"Bob\"]"));
+        assert!(game.pgn.ends_with("1. e4 e5 *\n"));
+
+        // only the player whose move it is can make it, and only legal moves
+        assert!(game.clone().play("d7d5", "Bob", BOB).is_err());
+        assert!(game.clone().play("e1e3", "Alice", ALICE).is_err());
+        game.play("g1f3", "Alice", ALICE).unwrap();
+        send(&game)
+            .check_reply(&bob_copy, ChessPieceColour::Black)
+            .unwrap();
+    }
+
+    #[test]
+    fn tampering_is_caught() {
+        let mut game = CorrespondenceGame::new(Variant::Standard, 0);
+        game.play("e2e4", "Alice", ALICE).unwrap();
+        game.play("e7e5", "Bob", BOB).unwrap();
+        let bob_copy = game.clone();
+        game.play("g1f3", "Alice", ALICE).unwrap();
+
+        // a changed move
+        let mut changed = game.clone();
+        changed.moves[1].uci = String::from("d7d5");
+        assert!(changed.verify().is_err());
+
+        // even with the hashes after it worked out again, bob still has his move
+        let mut previous_hash = changed.first_hash();
+        for (ply, signed) in changed.moves.iter_mut().enumerate() {
+            let player = match ply % 2 {
+                0 => game.white.as_ref().unwrap(),
+                _ => game.black.as_ref().unwrap(),
+            };
+            signed.hash = digest(&[
+                &previous_hash,
+                &signed.uci,
+                &signed.key,
+                &player.name,
+                &player.public_key,
+            ]);
+            previous_hash = signed.hash.clone();
+        }
+        assert!(changed.verify().is_err_and(|err| err.contains("PGN")));
+        let mut end = Board::default();
+        for signed in &changed.moves {
+            end.play(&signed.uci.parse().unwrap());
+        }
+        changed.pgn = changed.write_pgn(&end).unwrap();
+        changed.verify().unwrap();
+        assert!(changed
+            .check_reply(&bob_copy, ChessPieceColour::Black)
+            .is_err());
+
+        // a key that doesn't follow from the one before it
+        let mut forged = bob_copy.clone();
+        forged.moves[1].key = forged.moves[0].key.clone();
+        assert!(forged.verify().is_err());
+
+        // files that are out of date or skip ahead
+        let mut older = game.clone();
+        older.moves.truncate(1);
+        assert!(older
+            .check_reply(&bob_copy, ChessPieceColour::Black)
+            .is_err());
+        let mut ahead = game.clone();
+        ahead.play("b8c6", "Bob", BOB).unwrap();
+        assert!(ahead
+            .check_reply(&bob_copy, ChessPieceColour::Black)
+            .is_err());
+        assert!(game
+            .check_reply(&bob_copy, ChessPieceColour::White)
+            .is_err());
+    }
+}
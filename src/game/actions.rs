@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::game::outcome::{GameOutcome, Outcome, OutcomeReason};
+use crate::game::MoveEvent;
+use crate::rules::pieces::ChessPieceColour;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameAction {
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+}
+
+pub struct GameActionEvent {
+    pub colour: ChessPieceColour,
+    pub action: GameAction,
+}
+
+// the side that offered a draw, if there's an offer waiting for an answer
+#[derive(Resource, Default)]
+pub struct DrawOffer(pub Option<ChessPieceColour>);
+
+pub fn handle_game_actions(
+    mut action_ev: EventReader<GameActionEvent>,
+    mut draw_offer: ResMut<DrawOffer>,
+    mut outcome: ResMut<GameOutcome>,
+) {
+    for ev in action_ev.iter() {
+        if outcome.0.is_some() {
+            break;
+        }
+
+        match ev.action {
+            GameAction::Resign => {
+                outcome.0 = Some(Outcome::win(!ev.colour, OutcomeReason::Resignation));
+                draw_offer.0 = None;
+            }
+            GameAction::OfferDraw => {
+                if draw_offer.0.is_none() {
+                    draw_offer.0 = Some(ev.colour);
+                }
+            }
+            GameAction::AcceptDraw => {
+                if draw_offer.0 == Some(!ev.colour) {
+                    outcome.0 = Some(Outcome::draw(OutcomeReason::DrawAgreement));
+                    draw_offer.0 = None;
+                }
+            }
+            GameAction::DeclineDraw => {
+                if draw_offer.0 == Some(!ev.colour) {
+                    draw_offer.0 = None;
+                }
+            }
+        }
+    }
+}
+
+// making a move instead of answering a draw offer declines it
+pub fn expire_draw_offer(mut move_ev: EventReader<MoveEvent>, mut draw_offer: ResMut<DrawOffer>) {
+    for ev in move_ev.iter() {
+        if draw_offer.0 == Some(!ev.colour) {
+            draw_offer.0 = None;
+        }
+    }
+}
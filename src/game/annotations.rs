@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::game::board::BoardHistory;
+use crate::game::history::ViewedPly;
+use crate::game::{tile_under_cursor, BoardTilemapQuery};
+use crate::rules::annotations::{AnnotationColour, Annotations};
+use crate::rules::{BoardPos, MoveFromTo};
+use crate::utils::cursor::CursorPos;
+
+// what's been drawn on each position of the game, indexed like the positions in the history
+// with the current position last
+#[derive(Resource, Default)]
+pub struct BoardAnnotations(pub Vec<Annotations>);
+
+impl BoardAnnotations {
+    pub fn get(&self, ply: usize) -> Option<&Annotations> {
+        self.0.get(ply)
+    }
+
+    pub fn get_mut(&mut self, ply: usize) -> &mut Annotations {
+        if self.0.len() <= ply {
+            self.0.resize_with(ply + 1, Annotations::default);
+        }
+        &mut self.0[ply]
+    }
+}
+
+// the tile a right click started on
+#[derive(Resource, Default)]
+pub struct AnnotationDrag(Option<TilePos>);
+
+// the ply of the position on the board, which is an earlier one while looking through the history
+pub fn viewed_ply_index(viewed_ply: &ViewedPly, history: &BoardHistory) -> usize {
+    viewed_ply.0.unwrap_or(history.0.len())
+}
+
+// the modifier keys pick the colour, like in most analysis boards
+fn annotation_colour(keys: &Input<KeyCode>) -> AnnotationColour {
+    let pressed = |left, right| keys.pressed(left) || keys.pressed(right);
+    if pressed(KeyCode::LShift, KeyCode::RShift) {
+        AnnotationColour::Red
+    } else if pressed(KeyCode::LControl, KeyCode::RControl) {
+        AnnotationColour::Yellow
+    } else if pressed(KeyCode::LAlt, KeyCode::RAlt) {
+        AnnotationColour::Blue
+    } else {
+        AnnotationColour::Green
+    }
+}
+
+// right clicking a tile marks it, dragging from one tile to another with the right mouse button
+// draws an arrow between them
+#[allow(clippy::too_many_arguments)]
+pub fn annotate(
+    mouse_input: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    cursor_pos: Res<CursorPos>,
+    history: Res<BoardHistory>,
+    viewed_ply: Res<ViewedPly>,
+    tilemap_q: BoardTilemapQuery,
+    mut drag: ResMut<AnnotationDrag>,
+    mut annotations: ResMut<BoardAnnotations>,
+) {
+    if mouse_input.just_pressed(MouseButton::Right) {
+        drag.0 = tile_under_cursor(&cursor_pos, &tilemap_q);
+    }
+    if !mouse_input.just_released(MouseButton::Right) {
+        return;
+    }
+    let (from, to) = match (drag.0.take(), tile_under_cursor(&cursor_pos, &tilemap_q)) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return,
+    };
+
+    let colour = annotation_colour(&keys);
+    let annotations = annotations.get_mut(viewed_ply_index(&viewed_ply, &history));
+    match from == to {
+        true => annotations.toggle_square(
+            BoardPos {
+                x: from.x,
+                y: from.y,
+            },
+            colour,
+        ),
+        false => annotations.toggle_arrow(MoveFromTo::new(from.x, from.y, to.x, to.y), colour),
+    }
+}
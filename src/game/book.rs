@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+use crate::rules::polyglot::OpeningBook;
+use crate::settings::Settings;
+
+// the opening book from the settings, the computer plays from it while the position is in it
+#[derive(Resource, Default)]
+pub struct Book {
+    // what the book was loaded from, it's only loaded again once the setting changes
+    path: Option<String>,
+    pub book: Option<OpeningBook>,
+}
+
+pub fn load_opening_book(settings: Res<Settings>, mut book: ResMut<Book>) {
+    if !settings.is_changed() || book.path == settings.opening_book {
+        return;
+    }
+    book.path = settings.opening_book.clone();
+    book.book = settings
+        .opening_book
+        .as_ref()
+        .and_then(|path| match OpeningBook::load(path) {
+            Ok(loaded) => {
+                info!("loaded {} book moves from {}", loaded.len(), path);
+                Some(loaded)
+            }
+            Err(err) => {
+                warn!("{}, playing without an opening book", err);
+                None
+            }
+        });
+}
@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+use crate::game::board::ChessBoard;
+use crate::game::outcome::{GameOutcome, Outcome, OutcomeReason};
+use crate::game::IsBlackTurn;
+use crate::rules::clock::ChessClock;
+use crate::rules::pieces::ChessPieceColour;
+
+// the clocks of the game being played
+#[derive(Resource, Debug, Clone, Default, Deref, DerefMut)]
+pub struct GameClock(pub ChessClock);
+
+pub fn tick_clock(
+    time: Res<Time>,
+    is_black_turn: Res<IsBlackTurn>,
+    board: Res<ChessBoard>,
+    mut clock: ResMut<GameClock>,
+    mut outcome: ResMut<GameOutcome>,
+) {
+    if outcome.0.is_some() || clock.time_control.is_none() {
+        return;
+    }
+
+    let colour = match is_black_turn.0 {
+        true => ChessPieceColour::Black,
+        false => ChessPieceColour::White,
+    };
+    clock.tick(time.delta(), colour);
+
+    if clock.is_flagged(colour) {
+        outcome.0 = Some(match board.has_mating_material(!colour) {
+            true => Outcome::win(!colour, OutcomeReason::Timeout),
+            false => Outcome::draw(OutcomeReason::TimeoutVsInsufficientMaterial),
+        });
+        info!("{:?} ran out of time: {:?}", colour, outcome.0);
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::game::actions::{DrawOffer, GameAction, GameActionEvent};
+use crate::game::board::ChessBoard;
+use crate::game::book::Book;
+use crate::game::outcome::GameOutcome;
+use crate::game::state::{GameSetup, Opponent};
+use crate::game::{IsBlackTurn, MoveRequestEvent};
+
+// plays a move from the opening book, or a random legal move once the game has left the book,
+// whenever it's the computer's turn
+pub fn computer_move(
+    setup: Res<GameSetup>,
+    is_black_turn: Res<IsBlackTurn>,
+    board: Res<ChessBoard>,
+    book: Option<Res<Book>>,
+    outcome: Res<GameOutcome>,
+    mut move_request_ev: EventWriter<MoveRequestEvent>,
+) {
+    if outcome.0.is_some() || !setup.is_computer_turn(&is_black_turn) {
+        return;
+    }
+    // only think once per position, not every frame
+    if !board.is_changed() && !is_black_turn.is_changed() {
+        return;
+    }
+
+    // there's no book without the book plugin
+    let book_move = book
+        .as_ref()
+        .and_then(|book| book.book.as_ref())
+        .and_then(|book| book.weighted_move(&board, |total| fastrand::u32(..total)));
+    if let Some(_move) = book_move {
+        let (from, to) = (_move.from(), _move.to());
+        move_request_ev.send(MoveRequestEvent {
+            from: TilePos::new(from.x, from.y),
+            to: TilePos::new(to.x, to.y),
+        });
+        return;
+    }
+
+    let moves: Vec<(TilePos, TilePos)> = board
+        .iter()
+        .flatten()
+        .filter(|piece| piece.colour == board.to_move)
+        .flat_map(|piece| {
+            let from = TilePos::new(piece.x, piece.y);
+            board
+                .get_legal_moves(piece.x, piece.y)
+                .into_iter()
+                .flatten()
+                .map(move |_move| (from, TilePos::from(&_move)))
+        })
+        .collect();
+
+    if moves.is_empty() {
+        return;
+    }
+    let (from, to) = moves[fastrand::usize(..moves.len())];
+    move_request_ev.send(MoveRequestEvent { from, to });
+}
+
+// the computer doesn't know how to judge a position, so it turns down draw offers straight away
+// rather than leaving them until its next move
+pub fn answer_draw_offers(
+    setup: Res<GameSetup>,
+    draw_offer: Res<DrawOffer>,
+    mut action_ev: EventWriter<GameActionEvent>,
+) {
+    if let Opponent::Computer(colour) = setup.opponent {
+        if draw_offer.is_changed() && draw_offer.0 == Some(!colour) {
+            action_ev.send(GameActionEvent {
+                colour,
+                action: GameAction::DeclineDraw,
+            });
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::correspondence::{random_hex, CorrespondenceGame};
+use crate::game::board::ChessBoard;
+use crate::game::save::{PendingGame, SavedGame, SavedOpponent, SAVE_VERSION};
+use crate::game::state::{AppState, GameSetup, Opponent};
+use crate::game::MoveEvent;
+use crate::rules::clock::ChessClock;
+use crate::rules::pieces::ChessPieceColour;
+use crate::settings::Settings;
+
+// where the secret moves are signed with and the copies of games are kept, no correspondence
+// games can be played without a folder
+#[derive(Resource, Debug, Clone, Default)]
+pub struct CorrespondenceDir(pub Option<PathBuf>);
+
+impl CorrespondenceDir {
+    // `bevy_chess/correspondence` in the user's data folder, if the platform has one
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("bevy_chess").join("correspondence"))
+    }
+
+    fn dir(&self) -> Result<&Path, String> {
+        self.0.as_deref().ok_or(String::from(
+            "there's no folder to keep correspondence games in",
+        ))
+    }
+
+    // made up the first time it's needed. losing it means not being able to move in any of the
+    // games started with it, and anyone who can read it can move for this side, so only the
+    // user can read it
+    fn secret(&self) -> Result<String, String> {
+        let path = self.dir()?.join("secret");
+        if path.exists() {
+            return fs::read_to_string(&path)
+                .map(|secret| secret.trim().to_string())
+                .map_err(|err| format!("couldn't read {}: {}", path.display(), err));
+        }
+        let secret = random_hex(32);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        fs::create_dir_all(self.dir()?)
+            .and_then(|_| options.open(&path))
+            .and_then(|mut file| file.write_all(secret.as_bytes()))
+            .map_err(|err| format!("couldn't write {}: {}", path.display(), err))?;
+        Ok(secret)
+    }
+
+    // the game as it was after this side's last move, replies are checked against it
+    fn copy(&self, id: &str) -> Result<PathBuf, String> {
+        Ok(self.dir()?.join("copies").join(format!("{}.ron", id)))
+    }
+
+    // where a new game is written, to be sent to the other player
+    fn new_game(&self, id: &str) -> Result<PathBuf, String> {
+        Ok(self.dir()?.join(format!("{}.ron", id)))
+    }
+}
+
+pub struct OpenCorrespondence {
+    pub game: CorrespondenceGame,
+    // every move is written back to the file the game came from
+    pub path: PathBuf,
+    pub colour: ChessPieceColour,
+}
+
+// the correspondence game being played, if there is one
+#[derive(Resource, Default)]
+pub struct Correspondence(pub Option<OpenCorrespondence>);
+
+// starts a game with this side as white, the caller switches to `AppState::Setup`
+pub fn new_correspondence_game(
+    dir: &CorrespondenceDir,
+    setup: &mut GameSetup,
+    correspondence: &mut Correspondence,
+) -> Result<(), String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let game = CorrespondenceGame::new(setup.variant, timestamp);
+    let path = dir.new_game(&game.id)?;
+    setup.time_control = None;
+    setup.opponent = Opponent::Remote(ChessPieceColour::Black);
+    correspondence.0 = Some(OpenCorrespondence {
+        game,
+        path,
+        colour: ChessPieceColour::White,
+    });
+    Ok(())
+}
+
+// checks a game file received from the other player and sets it up to be replied to, the caller
+// switches to `AppState::Setup`. a game between other players can't be replied to, so it's only
+// watched
+pub fn open_correspondence_game(
+    path: &Path,
+    dir: &CorrespondenceDir,
+    setup: &mut GameSetup,
+    pending: &mut PendingGame,
+    correspondence: &mut Correspondence,
+) -> Result<(), String> {
+    let game = CorrespondenceGame::load(path)?;
+    game.verify()?;
+    let secret = dir.secret()?;
+    // whoever opens the file first joins the side that hasn't moved yet
+    let colour = game.side_of(&secret).or_else(|| {
+        let to_move = game.to_move();
+        game.player(to_move).is_none().then_some(to_move)
+    });
+
+    if let Some(colour) = colour {
+        let copy = dir.copy(&game.id)?;
+        if copy.exists() {
+            game.check_reply(&CorrespondenceGame::load(&copy)?, colour)?;
+        }
+    }
+
+    setup.variant = game.variant.parse()?;
+    setup.time_control = None;
+    setup.opponent = match colour {
+        Some(colour) => Opponent::Remote(!colour),
+        None => Opponent::Spectator,
+    };
+    pending.0 = Some(SavedGame {
+        version: SAVE_VERSION,
+        variant: game.variant.clone(),
+        opponent: SavedOpponent::Human,
+        moves: game.moves.iter().map(|signed| signed.uci.clone()).collect(),
+        clock: ChessClock::new(None),
+        annotations: Vec::new(),
+    });
+    match colour {
+        Some(colour) => {
+            info!("opened {}, playing {:?}", path.display(), colour);
+            correspondence.0 = Some(OpenCorrespondence {
+                game,
+                path: path.to_path_buf(),
+                colour,
+            });
+        }
+        None => {
+            info!("{} is a game between other players", path.display());
+            correspondence.0 = None;
+        }
+    }
+    Ok(())
+}
+
+// game files dropped onto the window in the main menu are opened
+pub fn open_dropped_games(
+    mut drop_ev: EventReader<FileDragAndDrop>,
+    dir: Res<CorrespondenceDir>,
+    mut setup: ResMut<GameSetup>,
+    mut pending: ResMut<PendingGame>,
+    mut correspondence: ResMut<Correspondence>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for ev in drop_ev.iter() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = ev {
+            match open_correspondence_game(
+                path_buf,
+                &dir,
+                &mut setup,
+                &mut pending,
+                &mut correspondence,
+            ) {
+                Ok(()) => {
+                    let _ = state.set(AppState::Setup);
+                }
+                Err(err) => warn!("{}", err),
+            }
+        }
+    }
+}
+
+// signs the moves made on this side of the board into the game file, and keeps a copy of it to
+// check the reply against
+pub fn sign_local_moves(
+    mut move_ev: EventReader<MoveEvent>,
+    board: Res<ChessBoard>,
+    settings: Res<Settings>,
+    dir: Res<CorrespondenceDir>,
+    mut correspondence: ResMut<Correspondence>,
+) {
+    let open = match &mut correspondence.0 {
+        Some(open) => open,
+        None => return,
+    };
+    for ev in move_ev.iter() {
+        if ev.colour != open.colour {
+            continue;
+        }
+        let uci = match board.last_move() {
+            Some(uci) => uci.to_string(),
+            None => continue,
+        };
+        let result = dir.secret().and_then(|secret| {
+            open.game.play(&uci, &settings.player_name, &secret)?;
+            open.game.save(&open.path)?;
+            open.game.save(dir.copy(&open.game.id)?)
+        });
+        match result {
+            Ok(()) => info!(
+                "your move is in {}, send it to the other player",
+                open.path.display()
+            ),
+            Err(err) => warn!("couldn't sign {}: {}", uci, err),
+        }
+    }
+}
+
+pub fn close_correspondence_game(mut correspondence: ResMut<Correspondence>) {
+    correspondence.0 = None;
+}
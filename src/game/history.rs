@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::state::{AppState, GameSetup, Opponent};
+use crate::game::CheckEvent;
+use crate::rules::board::Board;
+use crate::rules::pieces::ChessPieceKind;
+
+// the ply being looked at while browsing through the history, None for the current position
+#[derive(Resource, Default)]
+pub struct ViewedPly(pub Option<usize>);
+
+impl ViewedPly {
+    pub fn board<'a>(&self, board: &'a Board, history: &'a BoardHistory) -> &'a Board {
+        self.0.and_then(|ply| history.0.get(ply)).unwrap_or(board)
+    }
+}
+
+// during a game the arrow keys move the keyboard cursor, so only spectators can look back through
+// the game before it's over
+pub fn navigate_history(
+    keys: Res<Input<KeyCode>>,
+    state: Res<State<AppState>>,
+    setup: Res<GameSetup>,
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    mut viewed_ply: ResMut<ViewedPly>,
+    mut check_ev: EventWriter<CheckEvent>,
+) {
+    if state.current() != &AppState::Review && setup.opponent != Opponent::Spectator {
+        return;
+    }
+
+    let last_ply = history.0.len();
+    let current_ply = viewed_ply.0.unwrap_or(last_ply);
+    let ply = if keys.just_pressed(KeyCode::Left) {
+        current_ply.saturating_sub(1)
+    } else if keys.just_pressed(KeyCode::Right) {
+        (current_ply + 1).min(last_ply)
+    } else if keys.just_pressed(KeyCode::Home) {
+        0
+    } else if keys.just_pressed(KeyCode::End) {
+        last_ply
+    } else {
+        return;
+    };
+    if ply == current_ply {
+        return;
+    }
+
+    viewed_ply.0 = match ply == last_ply {
+        true => None,
+        false => Some(ply),
+    };
+
+    check_ev.send(check_event(viewed_ply.board(&board, &history)));
+}
+
+pub fn stop_viewing_history(
+    board: Res<ChessBoard>,
+    mut viewed_ply: ResMut<ViewedPly>,
+    mut check_ev: EventWriter<CheckEvent>,
+) {
+    viewed_ply.0 = None;
+    check_ev.send(check_event(&board));
+}
+
+// marks the king in check on the given board, if there is one
+pub(crate) fn check_event(board: &Board) -> CheckEvent {
+    CheckEvent(board.check.and_then(|colour| {
+        board
+            .find_piece(ChessPieceKind::King, colour)
+            .map(|king| TilePos::new(king.x, king.y))
+    }))
+}
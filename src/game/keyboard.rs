@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::game::board::ChessBoard;
+use crate::game::outcome::GameOutcome;
+use crate::game::state::{GameSetup, Opponent};
+use crate::game::{BoardClickEvent, IsBlackTurn, MoveRequestEvent};
+use crate::rules::pieces::ChessPieceColour;
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::move_indicators::{MoveIndicator, SelectedTile};
+use crate::tilemap::orientation::BoardOrientation;
+
+// the tile picked with the arrow keys, shown with the hover ring
+#[derive(Resource, Default)]
+pub struct KeyboardCursor(pub Option<TilePos>);
+
+// a move being typed in, opened with tab
+#[derive(Resource, Default)]
+pub struct MoveTextInput {
+    pub active: bool,
+    pub text: String,
+    // why the last move that was entered couldn't be made
+    pub error: Option<String>,
+}
+
+// a new game can have a different board size
+pub fn reset_keyboard_input(mut cursor: ResMut<KeyboardCursor>, mut input: ResMut<MoveTextInput>) {
+    *cursor = KeyboardCursor::default();
+    *input = MoveTextInput::default();
+}
+
+fn can_move(outcome: &GameOutcome, setup: &GameSetup, is_black_turn: &IsBlackTurn) -> bool {
+    outcome.0.is_none() && !setup.is_opponent_turn(is_black_turn)
+}
+
+fn side_to_move(is_black_turn: &IsBlackTurn) -> ChessPieceColour {
+    match is_black_turn.0 {
+        true => ChessPieceColour::Black,
+        false => ChessPieceColour::White,
+    }
+}
+
+// the arrow keys move the cursor around, enter does what clicking the tile under it would do and
+// escape drops the selection
+#[allow(clippy::too_many_arguments)]
+pub fn keyboard_move(
+    mut keys: ResMut<Input<KeyCode>>,
+    board: Res<ChessBoard>,
+    is_black_turn: Res<IsBlackTurn>,
+    setup: Res<GameSetup>,
+    outcome: Res<GameOutcome>,
+    orientation: Res<BoardOrientation>,
+    mut cursor: ResMut<KeyboardCursor>,
+    tilemap_q: Query<&TileStorage, With<BoardTilemap>>,
+    tiles_w_indicators_q: Query<(), With<MoveIndicator>>,
+    tile_selected_q: Query<&TilePos, With<SelectedTile>>,
+    mut click_ev: EventWriter<BoardClickEvent>,
+    mut move_request_ev: EventWriter<MoveRequestEvent>,
+) {
+    // spectators look back through the game with the arrow keys instead, see `navigate_history`
+    if setup.opponent == Opponent::Spectator {
+        return;
+    }
+    let selected_tile = tile_selected_q.get_single().ok().copied();
+
+    // escape only pauses the game if there's nothing to deselect
+    if keys.just_pressed(KeyCode::Escape) && selected_tile.is_some() {
+        click_ev.send(BoardClickEvent::default());
+        keys.reset(KeyCode::Escape);
+        return;
+    }
+
+    let offset: Option<(i32, i32)> = [
+        (KeyCode::Left, (-1, 0)),
+        (KeyCode::Right, (1, 0)),
+        (KeyCode::Up, (0, 1)),
+        (KeyCode::Down, (0, -1)),
+    ]
+    .into_iter()
+    .find(|(key, _)| keys.just_pressed(*key))
+    .map(|(_, offset)| offset);
+    if let Some((x_offset, y_offset)) = offset {
+        // up is always up on the screen, even with the board turned around
+        let (x_offset, y_offset) = match orientation.is_flipped() {
+            true => (-x_offset, -y_offset),
+            false => (x_offset, y_offset),
+        };
+        cursor.0 = Some(match cursor.0 {
+            Some(tile_pos) => TilePos {
+                x: (tile_pos.x as i32 + x_offset).clamp(0, board.width() as i32 - 1) as u32,
+                y: (tile_pos.y as i32 + y_offset).clamp(0, board.height() as i32 - 1) as u32,
+            },
+            // starts off on the selected piece, or in the middle of the side at the bottom
+            None => selected_tile.unwrap_or(TilePos {
+                x: board.width() / 2,
+                y: match orientation.is_flipped() {
+                    true => board.height() - 1,
+                    false => 0,
+                },
+            }),
+        });
+    }
+
+    if !keys.just_pressed(KeyCode::Return) || !can_move(&outcome, &setup, &is_black_turn) {
+        return;
+    }
+    let tile_pos = match cursor.0 {
+        Some(tile_pos) => tile_pos,
+        None => return,
+    };
+    let has_indicator = tilemap_q
+        .get_single()
+        .ok()
+        .and_then(|tile_storage| tile_storage.get(&tile_pos))
+        .is_some_and(|tile_entity| tiles_w_indicators_q.get(tile_entity).is_ok());
+
+    match (selected_tile, board.get(tile_pos.x, tile_pos.y)) {
+        (Some(from), _) if has_indicator => {
+            move_request_ev.send(MoveRequestEvent { from, to: tile_pos });
+            click_ev.send(BoardClickEvent::default());
+        }
+        (_, Some(piece)) if piece.colour == side_to_move(&is_black_turn) => {
+            click_ev.send(BoardClickEvent {
+                tile: tile_pos,
+                moves: board.get_legal_moves(piece.x, piece.y),
+            });
+        }
+        _ => click_ev.send(BoardClickEvent::default()),
+    }
+}
+
+// tab opens the move text box, enter makes the move typed into it and escape closes it.
+// while it's open, key presses are only used for typing
+#[allow(clippy::too_many_arguments)]
+pub fn type_move(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut chars: EventReader<ReceivedCharacter>,
+    board: Res<ChessBoard>,
+    is_black_turn: Res<IsBlackTurn>,
+    setup: Res<GameSetup>,
+    outcome: Res<GameOutcome>,
+    mut input: ResMut<MoveTextInput>,
+    mut click_ev: EventWriter<BoardClickEvent>,
+    mut move_request_ev: EventWriter<MoveRequestEvent>,
+) {
+    if !input.active {
+        chars.clear();
+        if keys.just_pressed(KeyCode::Tab) {
+            input.active = true;
+            input.error = None;
+        }
+        return;
+    }
+
+    for ev in chars.iter() {
+        if ev.char.is_ascii_alphanumeric() || "-=+#".contains(ev.char) {
+            input.text.push(ev.char);
+            input.error = None;
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        input.text.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        *input = MoveTextInput::default();
+    } else if keys.just_pressed(KeyCode::Return) && !input.text.is_empty() {
+        let parsed = match can_move(&outcome, &setup, &is_black_turn) {
+            true => board.parse_move(&input.text, side_to_move(&is_black_turn)),
+            false => Err(String::from("it's not your turn")),
+        };
+        match parsed {
+            Ok(_move) => {
+                let (from, to) = (_move.from(), _move.to());
+                move_request_ev.send(MoveRequestEvent {
+                    from: TilePos::new(from.x, from.y),
+                    to: TilePos::new(to.x, to.y),
+                });
+                click_ev.send(BoardClickEvent::default());
+                *input = MoveTextInput::default();
+            }
+            Err(err) => input.error = Some(err),
+        }
+    }
+
+    keys.clear();
+}
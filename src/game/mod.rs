@@ -4,18 +4,42 @@ use bevy::math::Vec4Swizzles;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
-use crate::game::board::{Board, BoardHistory};
-use crate::game::pieces::{ChessPieceColour, ChessPieceKind};
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::clock::GameClock;
+use crate::game::outcome::GameOutcome;
+use crate::game::state::GameSetup;
+use crate::rules::board::Board;
+use crate::rules::pieces::ChessPieceColour;
+use crate::rules::{Move, MoveFromTo};
 use crate::tilemap::board::BoardTilemap;
 use crate::tilemap::move_indicators::{MoveIndicator, SelectedTile};
 use crate::utils::cursor::CursorPos;
 
+pub mod actions;
+pub mod annotations;
 pub mod board;
-pub mod pieces;
+pub mod book;
+pub mod clock;
+pub mod computer;
+pub mod correspondence;
+pub mod history;
+pub mod keyboard;
+pub mod network;
+pub mod outcome;
+pub mod save;
+pub mod state;
 
+// a copy of the board's side to move for the systems that only need that, always set from the
+// board after it changes
 #[derive(Resource, Default)]
 pub struct IsBlackTurn(pub bool);
 
+impl From<&Board> for IsBlackTurn {
+    fn from(board: &Board) -> Self {
+        Self(board.to_move == ChessPieceColour::Black)
+    }
+}
+
 #[derive(Default)]
 pub struct BoardClickEvent {
     pub tile: TilePos,
@@ -25,46 +49,93 @@ pub struct BoardClickEvent {
 #[derive(Default)]
 pub struct CheckEvent(pub Option<TilePos>);
 
+// sent after a move has been made on the board
+pub struct MoveEvent {
+    // the side that made the move
+    pub colour: ChessPieceColour,
+}
+
+// asks for a piece to be moved, the move is only made if it's legal
+pub struct MoveRequestEvent {
+    pub from: TilePos,
+    pub to: TilePos,
+}
+
+// the piece being dragged, it's dropped when the mouse button is released
+#[derive(Resource, Default)]
+pub struct DragState(Option<Drag>);
+
+struct Drag {
+    from: TilePos,
+    // releasing on the tile the drag started on deselects it again if it was already selected,
+    // so that click-to-select still works
+    was_selected: bool,
+}
+
+pub enum DragEvent {
+    Lift(TilePos),
+    // also sent when a drag is cancelled
+    Drop,
+}
+
+type BoardTilemapQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static TilemapSize,
+        &'static TilemapGridSize,
+        &'static TilemapType,
+        &'static Transform,
+        &'static TileStorage,
+    ),
+    With<BoardTilemap>,
+>;
+
+fn tile_under_cursor(cursor_pos: &CursorPos, tilemap_q: &BoardTilemapQuery) -> Option<TilePos> {
+    let (map_size, grid_size, map_type, map_transform, _) = tilemap_q.single();
+    let cursor_pos: Vec3 = cursor_pos.0;
+    let cursor_in_map_pos: Vec2 = {
+        let cursor_pos = Vec4::from((cursor_pos, 1.0));
+        let cursor_in_map_pos = map_transform.compute_matrix().inverse() * cursor_pos;
+        cursor_in_map_pos.xy()
+    };
+
+    TilePos::from_world_pos(&cursor_in_map_pos, map_size, grid_size, map_type)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn mouse_click(
     mouse_input: Res<Input<MouseButton>>,
     cursor_pos: Res<CursorPos>,
-    mut is_black_turn: ResMut<IsBlackTurn>,
-    mut board: ResMut<Board>,
-    mut history: ResMut<BoardHistory>,
-    tilemap_q: Query<
-        (
-            &TilemapSize,
-            &TilemapGridSize,
-            &TilemapType,
-            &Transform,
-            &TileStorage,
-        ),
-        With<BoardTilemap>,
-    >,
+    is_black_turn: Res<IsBlackTurn>,
+    board: Res<ChessBoard>,
+    setup: Res<GameSetup>,
+    outcome: Res<GameOutcome>,
+    mut drag_state: ResMut<DragState>,
+    tilemap_q: BoardTilemapQuery,
     tiles_w_indicators_q: Query<(), With<MoveIndicator>>,
     tile_selected_q: Query<&TilePos, With<SelectedTile>>,
     mut click_ev: EventWriter<BoardClickEvent>,
-    mut check_ev: EventWriter<CheckEvent>,
+    mut drag_ev: EventWriter<DragEvent>,
+    mut move_request_ev: EventWriter<MoveRequestEvent>,
 ) {
-    if mouse_input.just_pressed(MouseButton::Left) {
-        let (map_size, grid_size, map_type, map_transform, tile_storage) = tilemap_q.single();
-        let cursor_pos: Vec3 = cursor_pos.0;
-        let cursor_in_map_pos: Vec2 = {
-            let cursor_pos = Vec4::from((cursor_pos, 1.0));
-            let cursor_in_map_pos = map_transform.compute_matrix().inverse() * cursor_pos;
-            cursor_in_map_pos.xy()
-        };
+    if mouse_input.just_pressed(MouseButton::Left)
+        && outcome.0.is_none()
+        && !setup.is_opponent_turn(&is_black_turn)
+    {
+        let tile_storage = tilemap_q.single().4;
 
-        if let Some(tile_pos) =
-            TilePos::from_world_pos(&cursor_in_map_pos, map_size, grid_size, map_type)
-        {
+        if let Some(tile_pos) = tile_under_cursor(&cursor_pos, &tilemap_q) {
             if let Some(tile_entity) = tile_storage.get(&tile_pos) {
                 if tiles_w_indicators_q.get(tile_entity).is_err() {
-                    // deselect selected tile if clicked again
+                    // pick the selected piece up again, it's deselected if it's dropped right away
                     if let Ok(selected_tile) = tile_selected_q.get_single() {
                         if selected_tile == &tile_pos {
-                            click_ev.send(BoardClickEvent::default());
+                            drag_state.0 = Some(Drag {
+                                from: tile_pos,
+                                was_selected: true,
+                            });
+                            drag_ev.send(DragEvent::Lift(tile_pos));
                             return;
                         }
                     }
@@ -87,224 +158,124 @@ pub fn mouse_click(
                             }
                         }
 
-                        let mut moves = board.get_moves(piece.x, piece.y);
-
-                        if let Some(check) = board.check {
-                            if piece.colour == check && piece.kind != ChessPieceKind::King {
-                                if let Some(moves) = &mut moves {
-                                    if !moves.is_empty() {
-                                        let attackers = board.get_king_attackers(check);
-                                        // if the king managed to get in check, we're assuming he exists
-                                        let king =
-                                            board.find_piece(ChessPieceKind::King, check).unwrap();
-                                        if attackers.len() > 1 {
-                                            moves.clear();
-                                        } else {
-                                            for attacker in attackers {
-                                                if let ChessPieceKind::Rook
-                                                | ChessPieceKind::Bishop
-                                                | ChessPieceKind::Queen = attacker.kind
-                                                {
-                                                    let threats = board
-                                                        .get_threatened_tiles(
-                                                            attacker.x, attacker.y,
-                                                        )
-                                                        .unwrap()
-                                                        .between(
-                                                            BoardPos {
-                                                                x: king.x,
-                                                                y: king.y,
-                                                            },
-                                                            BoardPos {
-                                                                x: attacker.x,
-                                                                y: attacker.y,
-                                                            },
-                                                        );
-                                                    moves.retain(|_move| {
-                                                        threats.iter().any(|threat| _move == threat)
-                                                            || _move
-                                                                == &BoardPos {
-                                                                    x: attacker.x,
-                                                                    y: attacker.y,
-                                                                }
-                                                    });
-                                                } else {
-                                                    moves.retain(|_move| {
-                                                        _move
-                                                            == &BoardPos {
-                                                                x: attacker.x,
-                                                                y: attacker.y,
-                                                            }
-                                                    });
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-
-                        // is this piece pinned?
-                        if let Some(moves) = &mut moves {
-                            if !moves.is_empty() {
-                                if let Some(king) =
-                                    board.find_piece(ChessPieceKind::King, piece.colour)
-                                {
-                                    for potential_attacker in
-                                        board.iter().flatten().filter(|piece| {
-                                            if piece.colour != king.colour {
-                                                if let ChessPieceKind::Rook
-                                                | ChessPieceKind::Bishop
-                                                | ChessPieceKind::Queen = piece.kind
-                                                {
-                                                    return true;
-                                                }
-                                            }
-                                            false
-                                        })
-                                    {
-                                        if let Some(threats) = board.get_threatened_tiles(
-                                            potential_attacker.x,
-                                            potential_attacker.y,
-                                        ) {
-                                            if threats
-                                                .between(
-                                                    BoardPos {
-                                                        x: king.x,
-                                                        y: king.y,
-                                                    },
-                                                    BoardPos {
-                                                        x: potential_attacker.x,
-                                                        y: potential_attacker.y,
-                                                    },
-                                                )
-                                                .contains(&BoardPos {
-                                                    x: piece.x,
-                                                    y: piece.y,
-                                                })
-                                            {
-                                                moves.clear();
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                        let moves = board.get_legal_moves(piece.x, piece.y);
 
                         click_ev.send(BoardClickEvent {
                             tile: tile_pos,
                             moves,
                         });
+                        drag_state.0 = Some(Drag {
+                            from: tile_pos,
+                            was_selected: false,
+                        });
+                        drag_ev.send(DragEvent::Lift(tile_pos));
                     } else {
                         click_ev.send(BoardClickEvent::default());
                     }
                 } else if let Ok(selected_tile) = tile_selected_q.get_single() {
-                    history.0.push(board.clone());
-                    board._move(selected_tile.x, selected_tile.y, tile_pos.x, tile_pos.y);
-                    is_black_turn.0 = !is_black_turn.0;
+                    move_request_ev.send(MoveRequestEvent {
+                        from: *selected_tile,
+                        to: tile_pos,
+                    });
                     click_ev.send(BoardClickEvent::default());
-
-                    // did that move cause a check?
-                    let mut check: Option<ChessPieceColour> = None;
-                    if let Some(king) = board.find_piece(
-                        ChessPieceKind::King,
-                        !board.get(tile_pos.x, tile_pos.y).unwrap().colour,
-                    ) {
-                        if !board.get_king_attackers(king.colour).is_empty() {
-                            check_ev.send(CheckEvent(Some(TilePos::new(king.x, king.y))));
-                            check = Some(king.colour);
-                        } else {
-                            check_ev.send(CheckEvent::default());
-                        }
-                    }
-                    board.check = check;
                 }
             }
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct Move {
-    x: u32,
-    y: u32,
-    pub takes: bool,
-}
-
-impl From<&Move> for TilePos {
-    fn from(_move: &Move) -> Self {
-        Self {
-            x: _move.x,
-            y: _move.y,
-        }
-    }
-}
-
-impl PartialEq<BoardPos> for Move {
-    fn eq(&self, other: &BoardPos) -> bool {
-        self.x == other.x && self.y == other.y
+#[allow(clippy::too_many_arguments)]
+pub fn mouse_release(
+    mouse_input: Res<Input<MouseButton>>,
+    cursor_pos: Res<CursorPos>,
+    mut drag_state: ResMut<DragState>,
+    tilemap_q: BoardTilemapQuery,
+    tiles_w_indicators_q: Query<(), With<MoveIndicator>>,
+    mut click_ev: EventWriter<BoardClickEvent>,
+    mut drag_ev: EventWriter<DragEvent>,
+    mut move_request_ev: EventWriter<MoveRequestEvent>,
+) {
+    if !mouse_input.just_released(MouseButton::Left) {
+        return;
     }
-}
+    let drag = match drag_state.0.take() {
+        Some(drag) => drag,
+        None => return,
+    };
+    drag_ev.send(DragEvent::Drop);
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub struct BoardPos {
-    x: u32,
-    y: u32,
-}
+    let tile_storage = tilemap_q.single().4;
+    let drop_tile = tile_under_cursor(&cursor_pos, &tilemap_q);
+    let is_legal = drop_tile
+        .and_then(|tile_pos| tile_storage.get(&tile_pos))
+        .is_some_and(|tile_entity| tiles_w_indicators_q.get(tile_entity).is_ok());
 
-impl PartialEq<Move> for BoardPos {
-    fn eq(&self, other: &Move) -> bool {
-        self.x == other.x && self.y == other.y
+    match drop_tile {
+        // dropped back where it was picked up, which is just a click
+        Some(tile_pos) if tile_pos == drag.from => {
+            if drag.was_selected {
+                click_ev.send(BoardClickEvent::default());
+            }
+        }
+        Some(tile_pos) if is_legal => {
+            move_request_ev.send(MoveRequestEvent {
+                from: drag.from,
+                to: tile_pos,
+            });
+            click_ev.send(BoardClickEvent::default());
+        }
+        // the piece snaps back to where it came from
+        _ => click_ev.send(BoardClickEvent::default()),
     }
 }
 
-trait Between {
-    fn between(&self, pos1: BoardPos, pos2: BoardPos) -> Self;
+// puts a dragged piece back when the game is paused or over
+pub fn cancel_drag(mut drag_state: ResMut<DragState>, mut drag_ev: EventWriter<DragEvent>) {
+    if drag_state.0.take().is_some() {
+        drag_ev.send(DragEvent::Drop);
+    }
 }
 
-impl Between for HashSet<BoardPos> {
-    // returns the set of tiles that are in a straight line between pos1 and pos2
-    fn between(&self, pos1: BoardPos, pos2: BoardPos) -> Self {
-        let mut between = HashSet::new();
-
-        let x_diff = pos1.x as i32 - pos2.x as i32;
-        let y_diff = pos1.y as i32 - pos2.y as i32;
-        if x_diff.abs() != y_diff.abs() && x_diff != 0 && y_diff != 0 {
-            return between;
+#[allow(clippy::too_many_arguments)]
+pub fn make_move(
+    mut move_request_ev: EventReader<MoveRequestEvent>,
+    mut is_black_turn: ResMut<IsBlackTurn>,
+    mut board: ResMut<ChessBoard>,
+    mut history: ResMut<BoardHistory>,
+    mut clock: ResMut<GameClock>,
+    outcome: Res<GameOutcome>,
+    mut check_ev: EventWriter<CheckEvent>,
+    mut move_ev: EventWriter<MoveEvent>,
+) {
+    for ev in move_request_ev.iter() {
+        let colour = board.to_move;
+        let is_legal = outcome.0.is_none()
+            && board
+                .get(ev.from.x, ev.from.y)
+                .is_some_and(|piece| piece.colour == colour)
+            && board
+                .get_legal_moves(ev.from.x, ev.from.y)
+                .is_some_and(|moves| moves.iter().any(|_move| TilePos::from(_move) == ev.to));
+        if !is_legal {
+            warn!("ignoring illegal move request {:?} -> {:?}", ev.from, ev.to);
+            continue;
         }
-        let x_offset = x_diff.clamp(-1, 1);
-        let y_offset = y_diff.clamp(-1, 1);
-        let mut next_pos = BoardPos {
-            x: (pos1.x as i32 - x_offset) as u32,
-            y: (pos1.y as i32 - y_offset) as u32,
-        };
-        while self.contains(&next_pos) {
-            between.insert(next_pos);
 
-            next_pos = BoardPos {
-                x: (next_pos.x as i32 - x_offset) as u32,
-                y: (next_pos.y as i32 - y_offset) as u32,
-            };
-        }
+        history.0.push(board.0.clone());
+        board.play(&MoveFromTo::new(ev.from.x, ev.from.y, ev.to.x, ev.to.y));
+        clock.press(colour);
+        *is_black_turn = IsBlackTurn::from(&board.0);
 
-        between
+        check_ev.send(history::check_event(&board));
+        move_ev.send(MoveEvent { colour });
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct MoveFromTo {
-    from: BoardPos,
-    to: BoardPos,
-}
-
-impl MoveFromTo {
-    pub fn new(from_x: u32, from_y: u32, to_x: u32, to_y: u32) -> Self {
+impl From<&Move> for TilePos {
+    fn from(_move: &Move) -> Self {
         Self {
-            from: BoardPos {
-                x: from_x,
-                y: from_y,
-            },
-            to: BoardPos { x: to_x, y: to_y },
+            x: _move.x,
+            y: _move.y,
         }
     }
 }
@@ -0,0 +1,726 @@
+use std::io::ErrorKind;
+use std::iter;
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::game::actions::{GameAction, GameActionEvent};
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::clock::GameClock;
+use crate::game::history::check_event;
+use crate::game::outcome::{GameOutcome, Outcome, OutcomeReason};
+use crate::game::state::{AppState, GameSetup, Opponent};
+use crate::game::{CheckEvent, IsBlackTurn, MoveEvent, MoveRequestEvent};
+use crate::net::connection::Connection;
+use crate::net::protocol::{Message, MoveTimes, PROTOCOL_VERSION};
+use crate::net::session::Session;
+use crate::net::{DEFAULT_PORT, RECONNECT_TIMEOUT};
+use crate::rules::clock::ChessClock;
+use crate::rules::pieces::ChessPieceColour;
+use crate::rules::serialisation::GameRecord;
+
+// the host plays white, the player that joins plays black
+pub const HOST_COLOUR: ChessPieceColour = ChessPieceColour::White;
+
+// seconds between pings, so that a dropped connection is noticed
+const PING_INTERVAL: f32 = 5.;
+
+// seconds between attempts to get back into the game after losing the connection
+const RECONNECT_INTERVAL: f32 = 2.;
+
+pub enum NetworkRole {
+    // waits for a player to join, and checks every move against its own copy of the game
+    Host {
+        listener: TcpListener,
+        // created once the board has been set up
+        session: Option<Box<Session>>,
+        peer: Option<Connection>,
+        // whether the other player has been sent the game, after that only they can come back,
+        // with their token
+        joined: bool,
+    },
+    // plays or watches whatever the host or server sends
+    Client {
+        // None while trying to get back into the game
+        connection: Option<Connection>,
+        address: String,
+        // sent with the game to rejoin it with, spectators don't get one
+        token: Option<String>,
+        // the attempt to get back into the game, which connects on another thread
+        rejoining: Option<JoinHandle<Result<Connection, String>>>,
+    },
+}
+
+// the game being played over the network, if there is one
+#[derive(Resource, Default)]
+pub struct NetworkGame(pub Option<NetworkRole>);
+
+impl NetworkGame {
+    fn session(&mut self) -> Option<&mut Session> {
+        match &mut self.0 {
+            Some(NetworkRole::Host { session, .. }) => session.as_deref_mut(),
+            _ => None,
+        }
+    }
+
+    // the host waits for the other player to come back, the client tries to rejoin if it has
+    // a token and gives up on the game otherwise
+    fn disconnect(&mut self) {
+        match &mut self.0 {
+            Some(NetworkRole::Host { peer, .. }) => *peer = None,
+            Some(NetworkRole::Client {
+                connection,
+                token: Some(_),
+                ..
+            }) => *connection = None,
+            _ => self.0 = None,
+        }
+    }
+
+    // sends a message straight to the other player
+    fn send(&mut self, message: &Message) {
+        let result = match &mut self.0 {
+            Some(NetworkRole::Host {
+                peer: Some(peer), ..
+            }) => peer.send(message),
+            Some(NetworkRole::Client {
+                connection: Some(connection),
+                ..
+            }) => connection.send(message),
+            _ => return,
+        };
+        if let Err(err) = result {
+            warn!("{}", err);
+            self.disconnect();
+        }
+    }
+
+    // a move or action made on this side of the board, the host checks it first
+    fn send_local(&mut self, colour: ChessPieceColour, message: Message) {
+        let message = match self.session() {
+            Some(session) => match session.handle(colour, message) {
+                Ok(Some(relayed)) => relayed,
+                Ok(None) => return,
+                Err(err) => {
+                    warn!("not sending {:?} move or action: {}", colour, err);
+                    return;
+                }
+            },
+            None => message,
+        };
+        self.send(&message);
+    }
+
+    // what the other player sent since the last frame, the host lets a new player join if
+    // there's no one connected
+    fn receive(&mut self) -> Vec<Message> {
+        let result = match &mut self.0 {
+            Some(NetworkRole::Host { listener, peer, .. }) => {
+                if peer.is_none() {
+                    match listener.accept() {
+                        Ok((stream, address)) => match Connection::new(stream) {
+                            Ok(connection) => {
+                                info!("{} joined", address);
+                                *peer = Some(connection);
+                            }
+                            Err(err) => warn!("{}", err),
+                        },
+                        Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+                        Err(err) => warn!("couldn't accept a player: {}", err),
+                    }
+                }
+                match peer {
+                    Some(peer) => peer.receive(),
+                    None => return Vec::new(),
+                }
+            }
+            Some(NetworkRole::Client {
+                connection: Some(connection),
+                ..
+            }) => connection.receive(),
+            _ => return Vec::new(),
+        };
+        result.unwrap_or_else(|err| {
+            warn!("{}", err);
+            self.disconnect();
+            Vec::new()
+        })
+    }
+}
+
+// a game received from the host before the board was set up, it's replayed once it is
+#[derive(Resource, Default)]
+pub struct PendingSync(pub Option<(GameRecord, MoveTimes)>);
+
+// starts listening on the port of the address, the caller switches to `AppState::Setup`
+pub fn host_game(
+    address: &str,
+    setup: &mut GameSetup,
+    network: &mut NetworkGame,
+) -> Result<(), String> {
+    let port = address
+        .rsplit(':')
+        .next()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|err| format!("couldn't host on port {}: {}", port, err))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| err.to_string())?;
+
+    info!("hosting a game on port {}", port);
+    setup.opponent = Opponent::Remote(!HOST_COLOUR);
+    network.0 = Some(NetworkRole::Host {
+        listener,
+        session: None,
+        peer: None,
+        joined: false,
+    });
+    Ok(())
+}
+
+// connects to a host, the game starts once it has sent the game
+pub fn join_game(address: &str, network: &mut NetworkGame) -> Result<(), String> {
+    let mut connection = Connection::connect(address)?;
+    connection.send(&Message::Hello {
+        version: PROTOCOL_VERSION,
+    })?;
+    info!("joined {}, waiting for the game", address);
+    network.0 = Some(NetworkRole::Client {
+        connection: Some(connection),
+        address: address.to_string(),
+        token: None,
+        rejoining: None,
+    });
+    Ok(())
+}
+
+// connects to a server to watch the game with the given id, or the one that started there last.
+// it's shown once the server has sent it
+pub fn watch_game(
+    address: &str,
+    game: Option<u64>,
+    network: &mut NetworkGame,
+) -> Result<(), String> {
+    let mut connection = Connection::connect(address)?;
+    connection.send(&Message::Watch {
+        version: PROTOCOL_VERSION,
+        game,
+    })?;
+    info!("watching on {}, waiting for the game", address);
+    network.0 = Some(NetworkRole::Client {
+        connection: Some(connection),
+        address: address.to_string(),
+        token: None,
+        rejoining: None,
+    });
+    Ok(())
+}
+
+fn rejoin(address: &str, token: &str) -> Result<Connection, String> {
+    let mut connection = Connection::connect(address)?;
+    connection.send(&Message::Rejoin {
+        version: PROTOCOL_VERSION,
+        token: token.to_string(),
+    })?;
+    Ok(connection)
+}
+
+pub fn close_network_game(mut network: ResMut<NetworkGame>, mut pending: ResMut<PendingSync>) {
+    network.0 = None;
+    pending.0 = None;
+}
+
+// replaces the game with one sent by the host. the clocks are set up from how long the moves took,
+// unless the times are missing
+fn apply_sync(
+    game: &GameRecord,
+    times: &MoveTimes,
+    board: &mut ChessBoard,
+    history: &mut BoardHistory,
+    is_black_turn: &mut IsBlackTurn,
+    clock: &mut ChessClock,
+) -> Result<(), String> {
+    let mut positions = game.replay()?;
+    match positions.pop() {
+        Some(current) => {
+            history.0 = iter::once(game.start.clone()).chain(positions).collect();
+            board.0 = current;
+        }
+        None => {
+            history.0 = Vec::new();
+            board.0 = game.start.clone();
+        }
+    }
+    *is_black_turn = IsBlackTurn::from(&board.0);
+
+    if times.moves.len() == game.moves.len() {
+        let move_times: Vec<Duration> = times
+            .moves
+            .iter()
+            .map(|time| Duration::from_millis(*time))
+            .collect();
+        *clock = ChessClock::replay(
+            clock.time_control,
+            &move_times,
+            Duration::from_millis(times.thinking),
+        );
+    }
+    Ok(())
+}
+
+// a move or action the other player made, which is made on this side of the board like any other.
+// a flag from the host runs out the clock here too, which then ends the game
+fn apply_remote(
+    message: &Message,
+    colour: ChessPieceColour,
+    clock: &mut ChessClock,
+    outcome: &mut GameOutcome,
+    move_request_ev: &mut EventWriter<MoveRequestEvent>,
+    action_ev: &mut EventWriter<GameActionEvent>,
+) {
+    let action = match message {
+        Message::Move { uci } => {
+            move_request_ev.send(MoveRequestEvent {
+                from: TilePos::new(uci.from().x, uci.from().y),
+                to: TilePos::new(uci.to().x, uci.to().y),
+            });
+            return;
+        }
+        Message::Flag { colour } => {
+            clock.flag(*colour);
+            return;
+        }
+        Message::Abandoned { colour } => {
+            if outcome.0.is_none() {
+                outcome.0 = Some(Outcome::win(!*colour, OutcomeReason::Abandonment));
+            }
+            return;
+        }
+        Message::Resign => GameAction::Resign,
+        Message::OfferDraw => GameAction::OfferDraw,
+        Message::AcceptDraw => GameAction::AcceptDraw,
+        Message::DeclineDraw => GameAction::DeclineDraw,
+        _ => return,
+    };
+    action_ev.send(GameActionEvent { colour, action });
+}
+
+// the host's copy of the game starts from the board that was just set up
+pub fn start_session(
+    setup: Res<GameSetup>,
+    board: Res<ChessBoard>,
+    mut network: ResMut<NetworkGame>,
+) {
+    if let Some(NetworkRole::Host { session, .. }) = &mut network.0 {
+        *session = Some(Box::new(Session::new(board.0.clone(), setup.time_control)));
+    }
+}
+
+pub fn restore_sync(
+    mut pending: ResMut<PendingSync>,
+    mut board: ResMut<ChessBoard>,
+    mut history: ResMut<BoardHistory>,
+    mut is_black_turn: ResMut<IsBlackTurn>,
+    mut clock: ResMut<GameClock>,
+    mut check_ev: EventWriter<CheckEvent>,
+) {
+    if let Some((game, times)) = pending.0.take() {
+        match apply_sync(
+            &game,
+            &times,
+            &mut board,
+            &mut history,
+            &mut is_black_turn,
+            &mut clock,
+        ) {
+            Ok(()) => check_ev.send(check_event(&board)),
+            Err(err) => warn!("couldn't set up the host's game: {}", err),
+        }
+    }
+}
+
+// sets up the game sent by the host, which is set up further in `restore_sync`
+fn start_synced_game(
+    game: GameRecord,
+    times: MoveTimes,
+    opponent: Opponent,
+    time_control: Option<String>,
+    setup: &mut GameSetup,
+    pending: &mut PendingSync,
+    state: &mut State<AppState>,
+) {
+    setup.variant = game.start.variant;
+    setup.opponent = opponent;
+    setup.time_control = time_control.and_then(|time_control| {
+        time_control
+            .parse()
+            .map_err(|err| warn!("{}, playing without a clock", err))
+            .ok()
+    });
+    pending.0 = Some((game, times));
+    let _ = state.set(AppState::Setup);
+}
+
+// the player that joined or the spectator waits in the main menu until the host sends the game
+pub fn wait_for_game(
+    mut network: ResMut<NetworkGame>,
+    mut setup: ResMut<GameSetup>,
+    mut pending: ResMut<PendingSync>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if !matches!(network.0, Some(NetworkRole::Client { .. })) {
+        return;
+    }
+
+    for message in network.receive() {
+        match message {
+            Message::Sync {
+                game,
+                colour,
+                time_control,
+                times,
+                token,
+            } => {
+                if let Some(NetworkRole::Client { token: kept, .. }) = &mut network.0 {
+                    *kept = token;
+                }
+                start_synced_game(
+                    game,
+                    times,
+                    Opponent::Remote(!colour),
+                    time_control,
+                    &mut setup,
+                    &mut pending,
+                    &mut state,
+                );
+                return;
+            }
+            Message::Watching {
+                id,
+                game,
+                time_control,
+                times,
+            } => {
+                info!("watching game {}", id);
+                start_synced_game(
+                    game,
+                    times,
+                    Opponent::Spectator,
+                    time_control,
+                    &mut setup,
+                    &mut pending,
+                    &mut state,
+                );
+                return;
+            }
+            Message::Hello { version } if version != PROTOCOL_VERSION => {
+                warn!(
+                    "the host uses protocol version {}, this game uses {}",
+                    version, PROTOCOL_VERSION
+                );
+                network.disconnect();
+                return;
+            }
+            Message::Ping { id } => network.send(&Message::Pong { id }),
+            Message::Error { reason } => {
+                warn!("the host refused to start the game: {}", reason);
+                network.disconnect();
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn receive_messages(
+    mut network: ResMut<NetworkGame>,
+    setup: Res<GameSetup>,
+    mut board: ResMut<ChessBoard>,
+    mut history: ResMut<BoardHistory>,
+    mut is_black_turn: ResMut<IsBlackTurn>,
+    mut clock: ResMut<GameClock>,
+    mut outcome: ResMut<GameOutcome>,
+    mut move_request_ev: EventWriter<MoveRequestEvent>,
+    mut action_ev: EventWriter<GameActionEvent>,
+    mut check_ev: EventWriter<CheckEvent>,
+) {
+    // spectators get everything with the side that played it, see `Message::Played`
+    let remote = match setup.opponent {
+        Opponent::Remote(colour) => colour,
+        Opponent::Spectator => {
+            for message in network.receive() {
+                match message {
+                    Message::Played { colour, message } => apply_remote(
+                        &message,
+                        colour,
+                        &mut clock,
+                        &mut outcome,
+                        &mut move_request_ev,
+                        &mut action_ev,
+                    ),
+                    Message::Ping { id } => network.send(&Message::Pong { id }),
+                    Message::Error { reason } => warn!("the server couldn't follow: {}", reason),
+                    _ => {}
+                }
+            }
+            return;
+        }
+        _ => return,
+    };
+
+    // the host's clock has the final say, the other player is told as soon as a flag falls
+    if let Some(flag) = network.session().and_then(|session| session.check_clock()) {
+        network.send(&flag);
+        apply_remote(
+            &flag,
+            remote,
+            &mut clock,
+            &mut outcome,
+            &mut move_request_ev,
+            &mut action_ev,
+        );
+    }
+
+    for message in network.receive() {
+        match message {
+            Message::Hello { version } if version != PROTOCOL_VERSION => {
+                warn!(
+                    "the other player uses protocol version {}, this game uses {}",
+                    version, PROTOCOL_VERSION
+                );
+                network.send(&Message::Error {
+                    reason: format!("protocol version {} is needed", PROTOCOL_VERSION),
+                });
+                network.disconnect();
+                return;
+            }
+            // the host answers with the game so far, once. after that the seat is kept for the
+            // player that joined
+            Message::Hello { .. } => {
+                let sync = network.session().map(|session| session.sync(remote));
+                match (&mut network.0, sync) {
+                    (Some(NetworkRole::Host { joined: true, .. }), _) => {
+                        network.send(&Message::Error {
+                            reason: String::from("the game has already started"),
+                        });
+                        network.disconnect();
+                        return;
+                    }
+                    (Some(NetworkRole::Host { joined, .. }), Some(sync)) => {
+                        *joined = true;
+                        network.send(&Message::Hello {
+                            version: PROTOCOL_VERSION,
+                        });
+                        network.send(&sync);
+                    }
+                    _ => {}
+                }
+            }
+            Message::Rejoin { token, .. } if network.session().is_some() => {
+                // a player that comes back too late is told how the game ended
+                let sync = network
+                    .session()
+                    .filter(|session| session.player_with_token(&token) == Some(remote))
+                    .map(|session| (session.sync(remote), session.ending()));
+                match sync {
+                    Some((sync, ending)) => {
+                        info!("the other player rejoined");
+                        network.send(&sync);
+                        if let Some(ending) = ending {
+                            network.send(&ending);
+                        }
+                    }
+                    None => {
+                        network.send(&Message::Error {
+                            reason: String::from("there's no game to rejoin"),
+                        });
+                        network.disconnect();
+                        return;
+                    }
+                }
+            }
+            Message::Ping { id } => network.send(&Message::Pong { id }),
+            Message::Pong { .. } => {}
+            Message::Error { reason } => warn!("the other player couldn't follow: {}", reason),
+            // only the client takes the game from the other side
+            Message::Sync {
+                game, times, token, ..
+            } if network.session().is_none() => {
+                if let Some(NetworkRole::Client { token: kept, .. }) = &mut network.0 {
+                    *kept = token.or(kept.take());
+                }
+                match apply_sync(
+                    &game,
+                    &times,
+                    &mut board,
+                    &mut history,
+                    &mut is_black_turn,
+                    &mut clock,
+                ) {
+                    Ok(()) => check_ev.send(check_event(&board)),
+                    Err(err) => warn!("couldn't follow the host's game: {}", err),
+                }
+            }
+            message => {
+                let message = match network.session() {
+                    Some(session) => match session.handle(remote, message) {
+                        // a move made too late gets the flag, which goes back to the player
+                        Ok(Some(relayed @ Message::Flag { .. })) => {
+                            network.send(&relayed);
+                            relayed
+                        }
+                        Ok(Some(relayed)) => relayed,
+                        Ok(None) => continue,
+                        // the other player gets the game again in case it got out of step
+                        Err(reason) => {
+                            let sync = session.sync(remote);
+                            network.send(&Message::Error { reason });
+                            network.send(&sync);
+                            continue;
+                        }
+                    },
+                    None => message,
+                };
+                apply_remote(
+                    &message,
+                    remote,
+                    &mut clock,
+                    &mut outcome,
+                    &mut move_request_ev,
+                    &mut action_ev,
+                );
+            }
+        }
+    }
+}
+
+pub fn send_local_moves(
+    mut move_ev: EventReader<MoveEvent>,
+    setup: Res<GameSetup>,
+    board: Res<ChessBoard>,
+    mut network: ResMut<NetworkGame>,
+) {
+    for ev in move_ev.iter() {
+        if !setup.is_network_game() || setup.is_remote(ev.colour) {
+            continue;
+        }
+        if let Some(uci) = board.last_move() {
+            network.send_local(ev.colour, Message::Move { uci: uci.clone() });
+        }
+    }
+}
+
+pub fn send_local_actions(
+    mut action_ev: EventReader<GameActionEvent>,
+    setup: Res<GameSetup>,
+    mut network: ResMut<NetworkGame>,
+) {
+    for ev in action_ev.iter() {
+        if !setup.is_network_game() || setup.is_remote(ev.colour) {
+            continue;
+        }
+        let message = match ev.action {
+            GameAction::Resign => Message::Resign,
+            GameAction::OfferDraw => Message::OfferDraw,
+            GameAction::AcceptDraw => Message::AcceptDraw,
+            GameAction::DeclineDraw => Message::DeclineDraw,
+        };
+        network.send_local(ev.colour, message);
+    }
+}
+
+// gets back into the game after losing the connection. the side that's away for longer than
+// `RECONNECT_TIMEOUT` forfeits, that's the other player for the host and this side for the player
+// that joined
+pub fn reconnect(
+    time: Res<Time>,
+    setup: Res<GameSetup>,
+    mut network: ResMut<NetworkGame>,
+    mut outcome: ResMut<GameOutcome>,
+    mut away_for: Local<f32>,
+    mut since_attempt: Local<f32>,
+) {
+    let remote = match setup.opponent {
+        Opponent::Remote(colour) => colour,
+        _ => return,
+    };
+    let away = match &network.0 {
+        Some(NetworkRole::Host {
+            peer: None,
+            joined: true,
+            ..
+        }) => remote,
+        Some(NetworkRole::Client {
+            connection: None, ..
+        }) => !remote,
+        _ => {
+            *away_for = 0.;
+            return;
+        }
+    };
+    if outcome.0.is_some() {
+        return;
+    }
+
+    *away_for += time.delta_seconds();
+    if *away_for >= RECONNECT_TIMEOUT.as_secs_f32() {
+        info!("{:?} didn't come back in time", away);
+        // the host ends its copy of the game too, the other player gets the forfeit if it still
+        // comes back
+        if let Some(abandoned) = network.session().and_then(|session| session.abandon(away)) {
+            network.send(&abandoned);
+        }
+        outcome.0 = Some(Outcome::win(!away, OutcomeReason::Abandonment));
+        if away != remote {
+            network.0 = None;
+        }
+        return;
+    }
+
+    *since_attempt += time.delta_seconds();
+    if let Some(NetworkRole::Client {
+        connection,
+        address,
+        token: Some(token),
+        rejoining,
+    }) = &mut network.0
+    {
+        match rejoining.take() {
+            Some(attempt) if !attempt.is_finished() => *rejoining = Some(attempt),
+            Some(attempt) => match attempt.join() {
+                Ok(Ok(rejoined)) => {
+                    info!("rejoined the game on {}", address);
+                    *connection = Some(rejoined);
+                }
+                Ok(Err(err)) => info!("couldn't rejoin yet: {}", err),
+                Err(_) => warn!("couldn't rejoin, the attempt panicked"),
+            },
+            None if *since_attempt >= RECONNECT_INTERVAL => {
+                *since_attempt = 0.;
+                let (address, token) = (address.clone(), token.clone());
+                *rejoining = Some(thread::spawn(move || rejoin(&address, &token)));
+            }
+            None => {}
+        }
+    }
+}
+
+pub fn ping(
+    time: Res<Time>,
+    mut since_ping: Local<f32>,
+    mut ping_id: Local<u64>,
+    mut network: ResMut<NetworkGame>,
+) {
+    *since_ping += time.delta_seconds();
+    if *since_ping >= PING_INTERVAL {
+        *since_ping = 0.;
+        *ping_id += 1;
+        network.send(&Message::Ping { id: *ping_id });
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::game::board::ChessBoard;
+use crate::game::MoveEvent;
+use crate::rules::pieces::ChessPieceColour;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutcomeReason {
+    Checkmate,
+    Stalemate,
+    Resignation,
+    DrawAgreement,
+    Timeout,
+    // the side that ran out of time can't be checkmated anymore
+    TimeoutVsInsufficientMaterial,
+    // the other player lost the connection and didn't come back in time
+    Abandonment,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    // None for a draw
+    pub winner: Option<ChessPieceColour>,
+    pub reason: OutcomeReason,
+}
+
+impl Outcome {
+    pub fn win(winner: ChessPieceColour, reason: OutcomeReason) -> Self {
+        Self {
+            winner: Some(winner),
+            reason,
+        }
+    }
+
+    pub fn draw(reason: OutcomeReason) -> Self {
+        Self {
+            winner: None,
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.reason {
+            OutcomeReason::Checkmate => "checkmate",
+            OutcomeReason::Stalemate => "stalemate",
+            OutcomeReason::Resignation => "resignation",
+            OutcomeReason::DrawAgreement => "agreement",
+            OutcomeReason::Timeout => "timeout",
+            OutcomeReason::TimeoutVsInsufficientMaterial => "timeout vs insufficient material",
+            OutcomeReason::Abandonment => "abandonment",
+        };
+        match self.winner {
+            Some(ChessPieceColour::White) => write!(f, "White wins by {}", reason),
+            Some(ChessPieceColour::Black) => write!(f, "Black wins by {}", reason),
+            None => write!(f, "Draw by {}", reason),
+        }
+    }
+}
+
+// set once the game is over, no moves can be made after that
+#[derive(Resource, Default)]
+pub struct GameOutcome(pub Option<Outcome>);
+
+// ends the game if the side to move after a move has no legal moves left
+pub fn detect_game_end(
+    mut move_ev: EventReader<MoveEvent>,
+    board: Res<ChessBoard>,
+    mut outcome: ResMut<GameOutcome>,
+) {
+    for ev in move_ev.iter() {
+        if outcome.0.is_some() || board.has_legal_moves(!ev.colour) {
+            continue;
+        }
+        outcome.0 = Some(match board.check {
+            Some(_) => Outcome::win(ev.colour, OutcomeReason::Checkmate),
+            None => Outcome::draw(OutcomeReason::Stalemate),
+        });
+        info!("game over: {:?}", outcome.0);
+    }
+}
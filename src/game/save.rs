@@ -0,0 +1,374 @@
+use std::fs;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::annotations::BoardAnnotations;
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::clock::GameClock;
+use crate::game::history::check_event;
+use crate::game::outcome::GameOutcome;
+use crate::game::state::{GameSetup, Opponent};
+use crate::game::{CheckEvent, IsBlackTurn, MoveEvent};
+use crate::rules::annotations::Annotations;
+use crate::rules::board::Board;
+use crate::rules::clock::ChessClock;
+use crate::rules::pieces::ChessPieceColour;
+use crate::rules::MoveFromTo;
+
+// bumped whenever a change to saved games can't be read by older versions of the game
+pub const SAVE_VERSION: u32 = 1;
+
+// the number of manual save slots, next to the autosave
+pub const SAVE_SLOTS: u32 = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SavedOpponent {
+    Human,
+    ComputerAsWhite,
+    ComputerAsBlack,
+}
+
+impl From<Opponent> for SavedOpponent {
+    fn from(opponent: Opponent) -> Self {
+        match opponent {
+            // network games can't be continued with the same player, they're saved to be
+            // finished on one screen
+            Opponent::Human | Opponent::Remote(_) | Opponent::Spectator => SavedOpponent::Human,
+            Opponent::Computer(ChessPieceColour::White) => SavedOpponent::ComputerAsWhite,
+            Opponent::Computer(ChessPieceColour::Black) => SavedOpponent::ComputerAsBlack,
+        }
+    }
+}
+
+impl From<SavedOpponent> for Opponent {
+    fn from(opponent: SavedOpponent) -> Self {
+        match opponent {
+            SavedOpponent::Human => Opponent::Human,
+            SavedOpponent::ComputerAsWhite => Opponent::Computer(ChessPieceColour::White),
+            SavedOpponent::ComputerAsBlack => Opponent::Computer(ChessPieceColour::Black),
+        }
+    }
+}
+
+// a game in progress, stored as the moves that led to the position so that the history can be
+// rebuilt when it's loaded. custom pieces aren't saved, they come from the piece set the game is
+// loaded with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub variant: String,
+    pub opponent: SavedOpponent,
+    // in long algebraic notation, e.g. `e2e4`
+    pub moves: Vec<String>,
+    pub clock: ChessClock,
+    // what was drawn on each position, written like in PGN comments
+    #[serde(default)]
+    pub annotations: Vec<String>,
+}
+
+impl SavedGame {
+    pub fn new(
+        setup: &GameSetup,
+        board: &Board,
+        history: &BoardHistory,
+        clock: &ChessClock,
+        annotations: &BoardAnnotations,
+    ) -> Self {
+        // every position after the first one knows the move that led to it
+        let moves = history
+            .0
+            .iter()
+            .skip(1)
+            .chain(iter::once(board))
+            .filter_map(|board| board.last_move())
+            .map(|_move| _move.to_string())
+            .collect();
+
+        Self {
+            version: SAVE_VERSION,
+            variant: setup.variant.to_string(),
+            opponent: setup.opponent.into(),
+            moves,
+            clock: clock.clone(),
+            annotations: annotations
+                .0
+                .iter()
+                .map(|annotations| annotations.to_pgn_comment())
+                .collect(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        Self::parse(&contents).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let game: SavedGame = ron::from_str(contents).map_err(|err| err.to_string())?;
+        if game.version > SAVE_VERSION {
+            return Err(format!(
+                "save version {} is newer than this game's {}",
+                game.version, SAVE_VERSION
+            ));
+        }
+        Ok(game)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("couldn't create {}: {}", dir.display(), err))?;
+        }
+        fs::write(path, contents)
+            .map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+    }
+
+    // the setup to start the game with before replaying its moves, keeping the current piece set
+    pub fn setup(&self, current: &GameSetup) -> Result<GameSetup, String> {
+        Ok(GameSetup {
+            variant: self.variant.parse()?,
+            time_control: self.clock.time_control,
+            opponent: self.opponent.into(),
+            piece_set: current.piece_set.clone(),
+        })
+    }
+
+    // plays the moves on the starting position, returns the positions before each move and the
+    // one after the last move
+    pub fn replay(&self, start: Board) -> Result<(Vec<Board>, Board), String> {
+        let mut history = Vec::new();
+        let mut board = start;
+        for _move in &self.moves {
+            let parsed: MoveFromTo = board.parse_move(_move, board.to_move)?;
+            history.push(board.clone());
+            board.play(&parsed);
+        }
+        Ok((history, board))
+    }
+
+    pub fn ply_count(&self) -> usize {
+        self.moves.len()
+    }
+}
+
+// where games are saved, nothing is saved without a folder
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SaveDir(pub Option<PathBuf>);
+
+impl SaveDir {
+    // `bevy_chess/saves` in the user's data folder, if the platform has one
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("bevy_chess").join("saves"))
+    }
+
+    pub fn autosave(&self) -> Option<PathBuf> {
+        self.0.as_ref().map(|dir| dir.join("autosave.ron"))
+    }
+
+    // slots are numbered from 1
+    pub fn slot(&self, slot: u32) -> Option<PathBuf> {
+        self.0
+            .as_ref()
+            .map(|dir| dir.join(format!("slot{}.ron", slot)))
+    }
+}
+
+// a game picked to be continued, it's replayed once the new game has been set up
+#[derive(Resource, Default)]
+pub struct PendingGame(pub Option<SavedGame>);
+
+// loads a saved game and starts setting it up, the caller switches to `AppState::Setup`
+pub fn resume_game(
+    path: &Path,
+    setup: &mut GameSetup,
+    pending: &mut PendingGame,
+) -> Result<(), String> {
+    let game = SavedGame::load(path)?;
+    *setup = game.setup(setup)?;
+    pending.0 = Some(game);
+    Ok(())
+}
+
+pub fn restore_game(
+    mut pending: ResMut<PendingGame>,
+    mut board: ResMut<ChessBoard>,
+    mut history: ResMut<BoardHistory>,
+    mut annotations: ResMut<BoardAnnotations>,
+    mut is_black_turn: ResMut<IsBlackTurn>,
+    mut clock: ResMut<GameClock>,
+    mut check_ev: EventWriter<CheckEvent>,
+) {
+    let game = match pending.0.take() {
+        Some(game) => game,
+        None => return,
+    };
+
+    let (replayed_history, replayed_board) = match game.replay(board.0.clone()) {
+        Ok(replayed) => replayed,
+        Err(err) => {
+            warn!("couldn't restore the saved game: {}", err);
+            return;
+        }
+    };
+    annotations.0 = game
+        .annotations
+        .iter()
+        .map(|comment| Annotations::from_pgn_comment(comment).unwrap_or_default())
+        .collect();
+    clock.0 = game.clock.clone();
+    history.0 = replayed_history;
+    board.0 = replayed_board;
+    *is_black_turn = IsBlackTurn::from(&board.0);
+
+    check_ev.send(check_event(&board));
+    info!("restored a game after {} moves", game.ply_count());
+}
+
+// saves the game after every move, and forgets it once the game is over
+#[allow(clippy::too_many_arguments)]
+pub fn autosave(
+    mut move_ev: EventReader<MoveEvent>,
+    save_dir: Res<SaveDir>,
+    setup: Res<GameSetup>,
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    clock: Res<GameClock>,
+    annotations: Res<BoardAnnotations>,
+    outcome: Res<GameOutcome>,
+) {
+    // network games aren't autosaved, there would be no one to continue them with
+    if move_ev.iter().count() == 0 || setup.is_network_game() {
+        return;
+    }
+    let path = match save_dir.autosave() {
+        Some(path) => path,
+        None => return,
+    };
+
+    if outcome.0.is_some() {
+        remove_autosave(&path);
+        return;
+    }
+    let game = SavedGame::new(&setup, &board, &history, &clock, &annotations);
+    if let Err(err) = game.save(&path) {
+        warn!("{}", err);
+    }
+}
+
+// also keeps the clocks up to date when the game is paused, e.g. right before quitting
+pub fn autosave_on_pause(
+    save_dir: Res<SaveDir>,
+    setup: Res<GameSetup>,
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    clock: Res<GameClock>,
+    annotations: Res<BoardAnnotations>,
+) {
+    if setup.is_network_game() {
+        return;
+    }
+    if let Some(path) = save_dir.autosave() {
+        let game = SavedGame::new(&setup, &board, &history, &clock, &annotations);
+        if let Err(err) = game.save(&path) {
+            warn!("{}", err);
+        }
+    }
+}
+
+pub fn forget_finished_game(save_dir: Res<SaveDir>) {
+    if let Some(path) = save_dir.autosave() {
+        remove_autosave(&path);
+    }
+}
+
+fn remove_autosave(path: &Path) {
+    if path.exists() {
+        if let Err(err) = fs::remove_file(path) {
+            warn!("couldn't remove {}: {}", path.display(), err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::annotations::BoardAnnotations;
+    use crate::game::board::BoardHistory;
+    use crate::game::save::SavedGame;
+    use crate::game::state::{GameSetup, Opponent};
+    use crate::rules::board::{Board, Variant};
+    use crate::rules::clock::ChessClock;
+    use crate::rules::pieces::ChessPieceColour;
+
+    #[test]
+    fn saved_games_replay() {
+        let setup = GameSetup {
+            variant: Variant::LosAlamos,
+            opponent: Opponent::Computer(ChessPieceColour::Black),
+            ..GameSetup::default()
+        };
+        let mut board = Board::from_variant(setup.variant);
+        let mut history = BoardHistory::default();
+        let mut colour = ChessPieceColour::White;
+        for _move in ["c2c3", "b5b4", "c1c2"] {
+            let parsed = board.parse_move(_move, colour).unwrap();
+            history.0.push(board.clone());
+            board._move(
+                parsed.from().x,
+                parsed.from().y,
+                parsed.to().x,
+                parsed.to().y,
+            );
+            colour = !colour;
+        }
+        let clock = ChessClock::new(Some("5+3".parse().unwrap()));
+
+        let game = SavedGame::new(
+            &setup,
+            &board,
+            &history,
+            &clock,
+            &BoardAnnotations::default(),
+        );
+        assert_eq!(vec!["c2c3", "b5b4", "c1c2"], game.moves);
+
+        let contents =
+            ron::ser::to_string_pretty(&game, ron::ser::PrettyConfig::default()).unwrap();
+        let loaded = SavedGame::parse(&contents).unwrap();
+        assert_eq!(game, loaded);
+
+        let loaded_setup = loaded.setup(&setup).unwrap();
+        assert_eq!(Variant::LosAlamos, loaded_setup.variant);
+        assert_eq!(setup.opponent, loaded_setup.opponent);
+        assert_eq!(clock.time_control, loaded_setup.time_control);
+
+        let (replayed_history, replayed) = loaded
+            .replay(Board::from_variant(loaded_setup.variant))
+            .unwrap();
+        assert_eq!(3, replayed_history.len());
+        assert!(replayed.get(2, 1).is_some());
+        assert!(replayed.get(2, 0).is_none());
+    }
+
+    #[test]
+    fn illegal_saved_moves_are_rejected() {
+        let game = SavedGame {
+            moves: vec![String::from("e2e4"), String::from("e2e4")],
+            ..SavedGame::new(
+                &GameSetup::default(),
+                &Board::default(),
+                &BoardHistory::default(),
+                &ChessClock::default(),
+                &BoardAnnotations::default(),
+            )
+        };
+        assert!(game.replay(Board::default()).is_err());
+    }
+}
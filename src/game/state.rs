@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+
+use crate::game::actions::DrawOffer;
+use crate::game::annotations::BoardAnnotations;
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::clock::GameClock;
+use crate::game::history::ViewedPly;
+use crate::game::outcome::GameOutcome;
+use crate::game::IsBlackTurn;
+use crate::rules::board::{Board, Variant};
+use crate::rules::clock::{ChessClock, TimeControl};
+use crate::rules::custom_pieces::PieceSet;
+use crate::rules::pieces::ChessPieceColour;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppState {
+    MainMenu,
+    // a new game is being set up, goes straight to Playing once the board is ready
+    Setup,
+    Playing,
+    // pushed on top of Playing
+    Paused,
+    GameOver,
+    // browsing through the moves of a finished game
+    Review,
+    // pushed on top of the main menu or the pause screen
+    Settings,
+    // the save slots, pushed on top of the main menu to load or the pause screen to save
+    Saves,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Opponent {
+    #[default]
+    Human,
+    // the computer plays the given side
+    Computer(ChessPieceColour),
+    // the given side is played by someone else, over the network or by exchanging game files,
+    // see `game::network` and `game::correspondence`
+    Remote(ChessPieceColour),
+    // both sides are played by others over the network, this side only watches
+    Spectator,
+}
+
+// what the next game is going to look like, picked in the main menu
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GameSetup {
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    pub opponent: Opponent,
+    pub piece_set: PieceSet,
+}
+
+impl GameSetup {
+    pub fn is_computer_turn(&self, is_black_turn: &IsBlackTurn) -> bool {
+        match self.opponent {
+            Opponent::Computer(colour) => (colour == ChessPieceColour::Black) == is_black_turn.0,
+            Opponent::Human | Opponent::Remote(_) | Opponent::Spectator => false,
+        }
+    }
+
+    // whether the pieces can't be moved from this side of the board, because the computer or a
+    // player over the network is to move. spectators never get to move
+    pub fn is_opponent_turn(&self, is_black_turn: &IsBlackTurn) -> bool {
+        match self.opponent {
+            Opponent::Human => false,
+            Opponent::Spectator => true,
+            Opponent::Computer(colour) | Opponent::Remote(colour) => {
+                (colour == ChessPieceColour::Black) == is_black_turn.0
+            }
+        }
+    }
+
+    pub fn is_computer(&self, colour: ChessPieceColour) -> bool {
+        self.opponent == Opponent::Computer(colour)
+    }
+
+    pub fn is_remote(&self, colour: ChessPieceColour) -> bool {
+        self.opponent == Opponent::Remote(colour) || self.opponent == Opponent::Spectator
+    }
+
+    pub fn is_network_game(&self) -> bool {
+        matches!(self.opponent, Opponent::Remote(_) | Opponent::Spectator)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn reset_game(
+    setup: Res<GameSetup>,
+    mut board: ResMut<ChessBoard>,
+    mut history: ResMut<BoardHistory>,
+    mut annotations: ResMut<BoardAnnotations>,
+    mut viewed_ply: ResMut<ViewedPly>,
+    mut is_black_turn: ResMut<IsBlackTurn>,
+    mut clock: ResMut<GameClock>,
+    mut outcome: ResMut<GameOutcome>,
+    mut draw_offer: ResMut<DrawOffer>,
+) {
+    board.0 = Board::from_variant(setup.variant)
+        .with_piece_set(setup.piece_set.clone())
+        .unwrap_or_else(|err| {
+            warn!("{}, playing without custom pieces", err);
+            Board::from_variant(setup.variant)
+        });
+    *history = BoardHistory::default();
+    *annotations = BoardAnnotations::default();
+    *viewed_ply = ViewedPly::default();
+    *is_black_turn = IsBlackTurn::from(&board.0);
+    clock.0 = ChessClock::new(setup.time_control);
+    *outcome = GameOutcome::default();
+    *draw_offer = DrawOffer::default();
+}
+
+pub fn finish_setup(mut state: ResMut<State<AppState>>) {
+    let _ = state.set(AppState::Playing);
+}
+
+pub fn enter_game_over(outcome: Res<GameOutcome>, mut state: ResMut<State<AppState>>) {
+    if outcome.0.is_some() {
+        let _ = state.set(AppState::GameOver);
+    }
+}
+
+pub fn toggle_pause(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        let _ = match state.current() {
+            AppState::Playing => state.push(AppState::Paused),
+            AppState::Paused | AppState::Settings | AppState::Saves => state.pop(),
+            _ => return,
+        };
+        // otherwise the state we just switched to would see the same key press
+        keys.reset(KeyCode::Escape);
+    }
+}
@@ -0,0 +1,31 @@
+pub mod rules;
+
+#[cfg(feature = "correspondence")]
+pub mod correspondence;
+#[cfg(feature = "net")]
+pub mod net;
+
+#[cfg(feature = "bevy")]
+pub mod config;
+#[cfg(feature = "bevy")]
+pub mod game;
+#[cfg(feature = "bevy")]
+pub mod plugin;
+#[cfg(feature = "bevy")]
+pub mod settings;
+#[cfg(feature = "bevy")]
+pub mod theme;
+#[cfg(feature = "bevy")]
+pub mod tilemap;
+#[cfg(feature = "bevy")]
+pub mod ui;
+#[cfg(feature = "bevy")]
+pub mod utils;
+
+#[cfg(feature = "bevy")]
+pub use config::{ChessAssetPaths, ChessConfig};
+#[cfg(feature = "bevy")]
+pub use plugin::{
+    ChessBoardRenderPlugin, ChessBookPlugin, ChessCorrespondencePlugin, ChessInputPlugin,
+    ChessNetworkPlugin, ChessPersistencePlugin, ChessPlugin, ChessRulesPlugin,
+};
@@ -1,23 +1,15 @@
 use bevy::prelude::*;
-use bevy_ecs_tilemap::prelude::*;
 
-use crate::game::board::{Board, BoardHistory};
-use crate::game::{mouse_click, BoardClickEvent, CheckEvent, IsBlackTurn};
-use crate::tilemap::board::create_board_tilemap;
-use crate::tilemap::checked_tile::{spawn_checked_tile, CheckedTileHandle};
-use crate::tilemap::hover::{show_hover_ring, spawn_hover_ring};
-use crate::tilemap::move_indicators::{
-    spawn_move_indicators, MoveIndicatorHandle, TakeIndicatorHandle,
-};
-use crate::tilemap::pieces::{draw_piece_tilemap, ChessPieceHandle};
-use crate::tilemap::ranks_and_files::create_labels;
-use crate::tilemap::turn_indicator::{draw_turn_indicators, spawn_turn_indicators};
-use crate::utils::cursor::{update_cursor_pos, CursorPos};
-use crate::utils::on_window_resize;
-
-mod game;
-mod tilemap;
-mod utils;
+use bevy_chess::game::correspondence::CorrespondenceDir;
+use bevy_chess::game::save::SaveDir;
+use bevy_chess::game::state::{GameSetup, Opponent};
+use bevy_chess::rules::board::Variant;
+use bevy_chess::rules::clock::TimeControl;
+use bevy_chess::rules::custom_pieces::{PieceSet, PIECE_SET_PATH};
+use bevy_chess::settings::{Settings, SettingsFile};
+use bevy_chess::theme::Theme;
+use bevy_chess::utils::on_window_resize;
+use bevy_chess::ChessPlugin;
 
 fn spawn_camera(mut commands: Commands) {
     commands.spawn(Camera2dBundle {
@@ -30,43 +22,72 @@ fn spawn_camera(mut commands: Commands) {
 }
 
 fn main() {
+    // the variant and time control can optionally be picked with the first two arguments,
+    // e.g. `bevy_chess capablanca 10+5`. both can still be changed in the main menu
+    let variant = match std::env::args().nth(1) {
+        Some(arg) => arg.parse().unwrap_or_else(|err| {
+            eprintln!("{}, falling back to standard chess", err);
+            Variant::Standard
+        }),
+        None => Variant::Standard,
+    };
+    let time_control = std::env::args()
+        .nth(2)
+        .and_then(|arg| match arg.parse::<TimeControl>() {
+            Ok(time_control) => Some(time_control),
+            Err(err) => {
+                eprintln!("{}, playing without a clock", err);
+                None
+            }
+        });
+
+    let piece_set = PieceSet::load(PIECE_SET_PATH, Theme::default().pieces.sprite_count())
+        .unwrap_or_else(|err| {
+            eprintln!("{}, playing without custom pieces", err);
+            PieceSet::default()
+        });
+
+    // a missing or unreadable settings file is replaced with the defaults on the next save
+    let settings_path = Settings::default_path();
+    let settings = match &settings_path {
+        Some(path) if path.exists() => Settings::load(path).unwrap_or_else(|err| {
+            eprintln!("{}, using the default settings", err);
+            Settings::default()
+        }),
+        _ => Settings::default(),
+    };
+
     App::new()
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     window: WindowDescriptor {
                         title: String::from("Chess"),
-                        width: 1600.,
-                        height: 900.,
+                        width: settings.window_width,
+                        height: settings.window_height,
                         ..default()
                     },
                     ..default()
                 })
-                .set(ImagePlugin::default_nearest()),
+                .set(ImagePlugin::default_nearest())
+                // picks up changes to the theme files while the game is running
+                .set(AssetPlugin {
+                    watch_for_changes: true,
+                    ..default()
+                }),
         )
-        .init_resource::<CursorPos>()
-        .init_resource::<Board>()
-        .init_resource::<BoardHistory>()
-        .init_resource::<IsBlackTurn>()
-        .init_resource::<MoveIndicatorHandle>()
-        .init_resource::<TakeIndicatorHandle>()
-        .init_resource::<ChessPieceHandle>()
-        .init_resource::<CheckedTileHandle>()
-        .add_event::<BoardClickEvent>()
-        .add_event::<CheckEvent>()
-        .add_plugin(TilemapPlugin)
+        .insert_resource(settings)
+        .insert_resource(SettingsFile(settings_path))
+        .insert_resource(SaveDir(SaveDir::default_path()))
+        .insert_resource(CorrespondenceDir(CorrespondenceDir::default_path()))
+        .insert_resource(GameSetup {
+            variant,
+            time_control,
+            opponent: Opponent::Human,
+            piece_set,
+        })
+        .add_plugin(ChessPlugin::default())
         .add_startup_system(spawn_camera)
-        .add_startup_system(create_board_tilemap)
-        .add_startup_system_to_stage(StartupStage::PostStartup, create_labels)
-        .add_startup_system_to_stage(StartupStage::PostStartup, spawn_hover_ring)
-        .add_startup_system_to_stage(StartupStage::PostStartup, spawn_turn_indicators)
-        .add_system(draw_piece_tilemap)
-        .add_system(draw_turn_indicators)
-        .add_system(update_cursor_pos)
-        .add_system(spawn_move_indicators)
-        .add_system(spawn_checked_tile)
-        .add_system(show_hover_ring.after(update_cursor_pos))
-        .add_system(mouse_click.after(update_cursor_pos))
         .add_system(on_window_resize)
         .run();
 }
@@ -0,0 +1,380 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use tungstenite::WebSocket;
+
+use crate::net::protocol::Message;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// a peer that sends a longer line or doesn't read this much of what's sent to it is dropped
+const MAX_BUFFERED: usize = 1 << 16;
+// frames waiting to be written to a WebSocket, a peer that falls further behind is dropped
+const MAX_QUEUED_FRAMES: usize = 256;
+
+enum Transport {
+    // one message per line
+    Tcp {
+        stream: TcpStream,
+        // what's been read but isn't a whole line yet
+        buffer: Vec<u8>,
+        // what's been sent but couldn't be written yet
+        outgoing: Vec<u8>,
+    },
+    // one message per text frame
+    WebSocket(Box<WebSocket<TcpStream>>),
+}
+
+// a connection that messages can be polled from without blocking, e.g. once per frame. it's
+// either a plain TCP connection or a WebSocket, the messages are the same either way
+pub struct Connection {
+    transport: Transport,
+    // kept so that it can still be named once the connection is gone
+    peer: String,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, String> {
+        let peer = peer_name(&stream);
+        stream
+            .set_nonblocking(true)
+            .and_then(|_| stream.set_nodelay(true))
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            transport: Transport::Tcp {
+                stream,
+                buffer: Vec::new(),
+                outgoing: Vec::new(),
+            },
+            peer,
+        })
+    }
+
+    // for a WebSocket that's done with its handshake
+    pub fn from_websocket(mut socket: WebSocket<TcpStream>) -> Result<Self, String> {
+        let peer = peer_name(socket.get_ref());
+        socket.set_config(|config| config.max_send_queue = Some(MAX_QUEUED_FRAMES));
+        socket
+            .get_ref()
+            .set_nonblocking(true)
+            .and_then(|_| socket.get_ref().set_nodelay(true))
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            transport: Transport::WebSocket(Box::new(socket)),
+            peer,
+        })
+    }
+
+    // `host:port` for a TCP connection, `ws://host:port/path` for a WebSocket
+    pub fn connect(address: &str) -> Result<Self, String> {
+        if address.starts_with("wss://") {
+            return Err(String::from("secure WebSockets aren't supported"));
+        }
+        let host = match address.strip_prefix("ws://") {
+            Some(rest) => rest.split('/').next().unwrap_or(rest),
+            None => address,
+        };
+        let socket_address = host
+            .to_socket_addrs()
+            .map_err(|err| format!("invalid address {}: {}", address, err))?
+            .next()
+            .ok_or(format!("invalid address: {}", address))?;
+        let stream = TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)
+            .map_err(|err| format!("couldn't connect to {}: {}", address, err))?;
+
+        if host == address {
+            return Self::new(stream);
+        }
+        // the handshake is done before the stream is made non-blocking
+        stream
+            .set_read_timeout(Some(CONNECT_TIMEOUT))
+            .map_err(|err| err.to_string())?;
+        let (socket, _) = tungstenite::client(address, stream)
+            .map_err(|err| format!("couldn't connect to {}: {}", address, err))?;
+        socket
+            .get_ref()
+            .set_read_timeout(None)
+            .map_err(|err| err.to_string())?;
+        Self::from_websocket(socket)
+    }
+
+    pub fn peer(&self) -> &str {
+        &self.peer
+    }
+
+    // messages are queued and written out as far as the socket takes them without blocking, the
+    // rest is written on the next call to `send` or `receive`
+    pub fn send(&mut self, message: &Message) -> Result<(), String> {
+        let line = message.encode();
+        let result = match &mut self.transport {
+            Transport::Tcp { outgoing, .. } => {
+                outgoing.extend_from_slice(line.as_bytes());
+                Ok(())
+            }
+            Transport::WebSocket(socket) => {
+                match socket.write_message(tungstenite::Message::Text(line.trim_end().to_string()))
+                {
+                    // the frame stays queued
+                    Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {
+                        Ok(())
+                    }
+                    Err(tungstenite::Error::SendQueueFull(_)) => {
+                        Err(String::from("too many messages waiting to be sent"))
+                    }
+                    result => result.map_err(|err| err.to_string()),
+                }
+            }
+        };
+        result
+            .and_then(|_| self.flush())
+            .map_err(|err| format!("couldn't send to {}: {}", self.peer, err))
+    }
+
+    // writes what the socket takes of the queued messages
+    fn flush(&mut self) -> Result<(), String> {
+        match &mut self.transport {
+            Transport::Tcp {
+                stream, outgoing, ..
+            } => {
+                while !outgoing.is_empty() {
+                    match stream.write(outgoing) {
+                        Ok(0) => return Err(String::from("the connection is closed")),
+                        Ok(written) => {
+                            outgoing.drain(..written);
+                        }
+                        Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                        Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                        Err(err) => return Err(err.to_string()),
+                    }
+                }
+                match outgoing.len() > MAX_BUFFERED {
+                    true => Err(String::from("too much data waiting to be sent")),
+                    false => Ok(()),
+                }
+            }
+            Transport::WebSocket(socket) => match socket.write_pending() {
+                Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => Ok(()),
+                result => result.map_err(|err| err.to_string()),
+            },
+        }
+    }
+
+    // the messages that have arrived since the last call, fails once the connection is closed.
+    // messages that arrived right before it was closed are still returned first. the other side
+    // is told about the ones that couldn't be read, and the rest are still returned
+    pub fn receive(&mut self) -> Result<Vec<Message>, String> {
+        self.flush()
+            .map_err(|err| format!("couldn't send to {}: {}", self.peer, err))?;
+        let mut messages = Vec::new();
+        let mut invalid = Vec::new();
+        let mut closed = false;
+        match &mut self.transport {
+            Transport::Tcp { stream, buffer, .. } => {
+                let mut chunk = [0; 4096];
+                loop {
+                    match stream.read(&mut chunk) {
+                        Ok(0) => {
+                            closed = true;
+                            break;
+                        }
+                        Ok(read) => {
+                            buffer.extend_from_slice(&chunk[..read]);
+                            // the rest is read once these lines are handled
+                            if buffer.len() > MAX_BUFFERED {
+                                break;
+                            }
+                        }
+                        Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                        Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                        Err(err) => {
+                            return Err(format!("lost connection to {}: {}", self.peer, err))
+                        }
+                    }
+                }
+
+                while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=end).collect();
+                    let line = String::from_utf8_lossy(&line);
+                    if !line.trim().is_empty() {
+                        match Message::decode(&line) {
+                            Ok(message) => messages.push(message),
+                            Err(reason) => invalid.push(reason),
+                        }
+                    }
+                }
+                if buffer.len() > MAX_BUFFERED {
+                    return Err(format!("{} sent a line that's too long", self.peer));
+                }
+            }
+            Transport::WebSocket(socket) => loop {
+                match socket.read_message() {
+                    Ok(tungstenite::Message::Text(text)) => match Message::decode(&text) {
+                        Ok(message) => messages.push(message),
+                        Err(reason) => invalid.push(reason),
+                    },
+                    Ok(tungstenite::Message::Close(_))
+                    | Err(
+                        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed,
+                    ) => {
+                        closed = true;
+                        break;
+                    }
+                    // pings are answered by tungstenite itself
+                    Ok(_) => {}
+                    Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {
+                        break
+                    }
+                    Err(err) => return Err(format!("lost connection to {}: {}", self.peer, err)),
+                }
+            },
+        }
+        for reason in invalid {
+            if !closed {
+                self.send(&Message::Error { reason })?;
+            }
+        }
+        match closed && messages.is_empty() {
+            true => Err(format!("{} closed the connection", self.peer)),
+            false => Ok(messages),
+        }
+    }
+
+    // lets the other side know the connection was closed on purpose. whatever is still queued is
+    // written as far as the socket takes it without blocking
+    pub fn close(mut self) {
+        if let Transport::WebSocket(socket) = &mut self.transport {
+            let _ = socket.close(None);
+        }
+        let _ = self.flush();
+        if let Transport::Tcp { stream, .. } = &self.transport {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn peer_name(stream: &TcpStream) -> String {
+    stream
+        .peer_addr()
+        .map_or(String::from("unknown"), |address| address.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::net::connection::Connection;
+    use crate::net::protocol::Message;
+
+    fn receive(connection: &mut Connection, count: usize) -> Vec<Message> {
+        let mut received = Vec::new();
+        for _ in 0..100 {
+            received.extend(connection.receive().unwrap());
+            if received.len() >= count {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        received
+    }
+
+    fn wait_until_closed(connection: &mut Connection) -> bool {
+        for _ in 0..100 {
+            if connection.receive().is_err() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn messages_arrive_over_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut client = Connection::connect(&address).unwrap();
+        let mut server = Connection::new(listener.accept().unwrap().0).unwrap();
+
+        client.send(&Message::Ping { id: 1 }).unwrap();
+        client.send(&Message::Resign).unwrap();
+        assert!(matches!(
+            receive(&mut server, 2)[..],
+            [Message::Ping { id: 1 }, Message::Resign]
+        ));
+
+        drop(client);
+        assert!(wait_until_closed(&mut server));
+    }
+
+    #[test]
+    fn invalid_messages_are_answered_with_an_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut stream = TcpStream::connect(&address).unwrap();
+        let mut server = Connection::new(listener.accept().unwrap().0).unwrap();
+
+        stream
+            .write_all(b"{\"type\":\"resign\"}\nnot a message\n{\"type\":\"offer_draw\"}\n")
+            .unwrap();
+        assert!(matches!(
+            receive(&mut server, 2)[..],
+            [Message::Resign, Message::OfferDraw]
+        ));
+        let mut reply = [0; 256];
+        let read = stream.read(&mut reply).unwrap();
+        assert!(matches!(
+            Message::decode(&String::from_utf8_lossy(&reply[..read])),
+            Ok(Message::Error { .. })
+        ));
+    }
+
+    #[test]
+    fn peers_that_stall_are_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut client = Connection::connect(&address).unwrap();
+        let _stalled = listener.accept().unwrap();
+
+        // the other side never reads, so sending has to fail instead of blocking
+        let reason = "x".repeat(1024);
+        let sent = (0..100_000).find(|_| {
+            client
+                .send(&Message::Error {
+                    reason: reason.clone(),
+                })
+                .is_err()
+        });
+        assert!(sent.is_some());
+
+        // a line without an end
+        let mut stream = TcpStream::connect(&address).unwrap();
+        let mut server = Connection::new(listener.accept().unwrap().0).unwrap();
+        stream.write_all(&[b'x'; 1 << 17]).unwrap();
+        assert!(wait_until_closed(&mut server));
+    }
+
+    #[test]
+    fn messages_arrive_over_websockets() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("ws://{}/", listener.local_addr().unwrap());
+        let accepted = thread::spawn(move || {
+            let socket = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+            Connection::from_websocket(socket).unwrap()
+        });
+        let mut client = Connection::connect(&address).unwrap();
+        let mut server = accepted.join().unwrap();
+
+        client.send(&Message::OfferDraw).unwrap();
+        server.send(&Message::DeclineDraw).unwrap();
+        assert!(matches!(receive(&mut server, 1)[..], [Message::OfferDraw]));
+        assert!(matches!(
+            receive(&mut client, 1)[..],
+            [Message::DeclineDraw]
+        ));
+
+        client.close();
+        assert!(wait_until_closed(&mut server));
+    }
+}
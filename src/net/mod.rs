@@ -0,0 +1,16 @@
+// playing over the network, without anything bevy related so that servers can be built on it.
+// either one of the players or a `server` runs a `Session` that has the final say on every move,
+// the messages between them are described in `protocol`
+
+use std::time::Duration;
+
+pub mod connection;
+pub mod protocol;
+pub mod server;
+pub mod session;
+
+// the port games are hosted on unless another one is picked
+pub const DEFAULT_PORT: u16 = 7878;
+
+// how long a player that lost the connection has to rejoin before forfeiting the game
+pub const RECONNECT_TIMEOUT: Duration = Duration::from_secs(60);
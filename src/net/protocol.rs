@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rules::pieces::ChessPieceColour;
+use crate::rules::serialisation::GameRecord;
+use crate::rules::MoveFromTo;
+
+// bumped whenever a change to the messages can't be understood by older versions of the game
+pub const PROTOCOL_VERSION: u32 = 2;
+
+// how long the moves of a game took, so that the clocks can be set up again after joining in the
+// middle of it, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveTimes {
+    pub moves: Vec<u64>,
+    // the time spent so far on the move that's being thought about
+    pub thinking: u64,
+}
+
+// messages are sent as one line of JSON each, tagged with their type, e.g.
+// `{"type":"move","uci":"e2e4"}`. positions and moves are written as described in
+// `rules::serialisation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    // the first message both ways, connections with a different version are closed
+    Hello {
+        version: u32,
+    },
+    // the whole game so far, sent after the hello and whenever the other side got out of step
+    Sync {
+        game: GameRecord,
+        // the side the receiver plays
+        colour: ChessPieceColour,
+        // written like on the command line, e.g. `5+3`
+        time_control: Option<String>,
+        #[serde(default)]
+        times: MoveTimes,
+        // lets the receiver back into the game with a `Rejoin` after losing the connection
+        #[serde(default)]
+        token: Option<String>,
+    },
+    // sent instead of the hello to get back into a game, with the token from its sync. it's
+    // answered with the game again
+    Rejoin {
+        version: u32,
+        token: String,
+    },
+    Move {
+        uci: MoveFromTo,
+    },
+    Resign,
+    // the side to move ran out of time. the host sends it once its clock runs out, or to both
+    // players instead of a move made after that. a player can also send it about the other side,
+    // which the host only passes on if its own clock agrees
+    Flag {
+        colour: ChessPieceColour,
+    },
+    // the side lost the connection and didn't rejoin in time, so it forfeits. only the host sends
+    // it
+    Abandoned {
+        colour: ChessPieceColour,
+    },
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    // answered with a pong with the same id, to keep the connection alive
+    Ping {
+        id: u64,
+    },
+    Pong {
+        id: u64,
+    },
+    // the last message couldn't be handled
+    Error {
+        reason: String,
+    },
+    // sent instead of the hello to watch a game on a server rather than play in one. a game can be
+    // picked by its id, otherwise the one that started last is watched
+    Watch {
+        version: u32,
+        game: Option<u64>,
+    },
+    // the answer to a watch, the whole game so far. what the players do next follows as `Played`
+    Watching {
+        id: u64,
+        game: GameRecord,
+        time_control: Option<String>,
+        #[serde(default)]
+        times: MoveTimes,
+    },
+    // a move or action of one of the players, passed on to the spectators
+    Played {
+        colour: ChessPieceColour,
+        message: Box<Message>,
+    },
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("messages can always be serialised");
+        line.push('\n');
+        line
+    }
+
+    pub fn decode(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line.trim()).map_err(|err| format!("invalid message: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::net::protocol::{Message, MoveTimes, PROTOCOL_VERSION};
+    use crate::rules::board::Board;
+    use crate::rules::pieces::ChessPieceColour;
+    use crate::rules::serialisation::GameRecord;
+    use crate::rules::MoveFromTo;
+
+    #[test]
+    fn messages_round_trip() {
+        assert_eq!(
+            "{\"type\":\"move\",\"uci\":\"e2e4\"}\n",
+            Message::Move {
+                uci: MoveFromTo::new(4, 1, 4, 3)
+            }
+            .encode()
+        );
+        assert_eq!("{\"type\":\"resign\"}\n", Message::Resign.encode());
+        assert!(matches!(
+            Message::decode(
+                &Message::Hello {
+                    version: PROTOCOL_VERSION
+                }
+                .encode()
+            ),
+            Ok(Message::Hello {
+                version: PROTOCOL_VERSION
+            })
+        ));
+
+        let sync = Message::Sync {
+            game: GameRecord {
+                start: Board::default(),
+                moves: vec!["e2e4".parse().unwrap()],
+            },
+            colour: ChessPieceColour::Black,
+            time_control: Some(String::from("5+3")),
+            times: MoveTimes {
+                moves: vec![1500],
+                thinking: 200,
+            },
+            token: Some(String::from("abc")),
+        };
+        match Message::decode(&sync.encode()) {
+            Ok(Message::Sync {
+                game,
+                colour,
+                time_control,
+                times,
+                token,
+            }) => {
+                assert_eq!(1, game.moves.len());
+                assert_eq!(ChessPieceColour::Black, colour);
+                assert_eq!(Some(String::from("5+3")), time_control);
+                assert_eq!(vec![1500], times.moves);
+                assert_eq!(Some(String::from("abc")), token);
+            }
+            other => panic!("expected a sync, got {:?}", other),
+        }
+        // older versions don't send the times or the token
+        let mut older: serde_json::Value = serde_json::from_str(&sync.encode()).unwrap();
+        let fields = older.as_object_mut().unwrap();
+        fields.remove("times");
+        fields.remove("token");
+        assert!(matches!(
+            Message::decode(&older.to_string()),
+            Ok(Message::Sync { token: None, times, .. }) if times == MoveTimes::default()
+        ));
+
+        let played = Message::Played {
+            colour: ChessPieceColour::White,
+            message: Box::new(Message::Resign),
+        };
+        assert_eq!(
+            "{\"type\":\"played\",\"colour\":\"white\",\"message\":{\"type\":\"resign\"}}\n",
+            played.encode()
+        );
+        assert!(matches!(
+            Message::decode(&played.encode()),
+            Ok(Message::Played {
+                colour: ChessPieceColour::White,
+                message,
+            }) if matches!(*message, Message::Resign)
+        ));
+
+        assert!(Message::decode("{\"type\":\"castle\"}").is_err());
+        assert!(Message::decode("{\"type\":\"move\",\"uci\":\"e9\"}").is_err());
+    }
+}
@@ -0,0 +1,837 @@
+// a server for many games at once, which players join over WebSocket. players are paired into a
+// game in the order they say hello, the first one playing white. every move goes through the
+// game's `Session`, and finished games are written to a folder as PGN. anyone else can watch a
+// game that's in progress, spectators get the game so far and then everything that's played.
+// players that lose the connection can rejoin with the token they were sent with the game
+
+use std::fs;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tungstenite::handshake::server::{NoCallback, ServerHandshake};
+use tungstenite::handshake::{HandshakeError, MidHandshake};
+
+use crate::net::connection::Connection;
+use crate::net::protocol::{Message, PROTOCOL_VERSION};
+use crate::net::session::Session;
+use crate::net::RECONNECT_TIMEOUT;
+use crate::rules::board::{Board, Variant};
+use crate::rules::clock::TimeControl;
+use crate::rules::pgn::{pgn_date, write_pgn};
+use crate::rules::pieces::ChessPieceColour;
+
+// along with when it started
+type Handshake = (
+    MidHandshake<ServerHandshake<TcpStream, NoCallback>>,
+    Instant,
+);
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    // every game is played with the same rules
+    pub variant: Variant,
+    // the players keep their own clocks too, but the server's has the final say
+    pub time_control: Option<TimeControl>,
+    // where finished games are written to, they're not kept without a folder
+    pub games_dir: Option<PathBuf>,
+    // how long a player that lost the connection has to rejoin before forfeiting, connections
+    // that take longer than this to finish the WebSocket handshake or to say hello after it are
+    // dropped too
+    pub reconnect_timeout: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            variant: Variant::default(),
+            time_control: None,
+            games_dir: None,
+            reconnect_timeout: RECONNECT_TIMEOUT,
+        }
+    }
+}
+
+// where a connection goes once it has been sent the game
+enum Seat {
+    Spectator(u64),
+    Player(u64, ChessPieceColour),
+}
+
+// a player that's connected but not in a game yet
+struct Waiting {
+    connection: Connection,
+    connected: Instant,
+    // whether it said hello with the right protocol version
+    ready: bool,
+    // the game it asked to watch or rejoin instead, it's moved there at the end of the greeting
+    seat: Option<Seat>,
+    // it couldn't rejoin, so it has lost its game and is let go once it has been told
+    rejected: bool,
+}
+
+struct Game {
+    id: u64,
+    session: Session,
+    white: Option<Connection>,
+    black: Option<Connection>,
+    spectators: Vec<Connection>,
+    // when the players lost the connection, if they haven't rejoined yet
+    white_left: Option<Instant>,
+    black_left: Option<Instant>,
+    // the addresses the players connected from, for the PGN
+    white_name: String,
+    black_name: String,
+}
+
+impl Game {
+    fn connection(&mut self, colour: ChessPieceColour) -> &mut Option<Connection> {
+        match colour {
+            ChessPieceColour::White => &mut self.white,
+            ChessPieceColour::Black => &mut self.black,
+        }
+    }
+
+    fn left(&mut self, colour: ChessPieceColour) -> &mut Option<Instant> {
+        match colour {
+            ChessPieceColour::White => &mut self.white_left,
+            ChessPieceColour::Black => &mut self.black_left,
+        }
+    }
+
+    // a player that can't be reached anymore is dropped until it rejoins
+    fn send(&mut self, colour: ChessPieceColour, message: &Message) {
+        let id = self.id;
+        let connection = self.connection(colour);
+        if let Some(player) = connection {
+            if let Err(err) = player.send(message) {
+                log::warn!("game {}: {}", id, err);
+                *connection = None;
+            }
+        }
+    }
+
+    // what a player did, for everyone watching. spectators that can't be reached are dropped
+    fn send_spectators(&mut self, colour: ChessPieceColour, message: &Message) {
+        let played = Message::Played {
+            colour,
+            message: Box::new(message.clone()),
+        };
+        let id = self.id;
+        self.spectators
+            .retain_mut(|spectator| match spectator.send(&played) {
+                Ok(()) => true,
+                Err(err) => {
+                    log::info!("game {}: {}", id, err);
+                    false
+                }
+            });
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    config: ServerConfig,
+    handshakes: Vec<Handshake>,
+    waiting: Vec<Waiting>,
+    games: Vec<Game>,
+    next_game_id: u64,
+}
+
+impl Server {
+    pub fn bind(address: &str, config: ServerConfig) -> Result<Self, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|err| format!("couldn't listen on {}: {}", address, err))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|err| err.to_string())?;
+        if let Some(dir) = &config.games_dir {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("couldn't create {}: {}", dir.display(), err))?;
+        }
+
+        Ok(Self {
+            listener,
+            config,
+            handshakes: Vec::new(),
+            waiting: Vec::new(),
+            games: Vec::new(),
+            next_game_id: 1,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, String> {
+        self.listener.local_addr().map_err(|err| err.to_string())
+    }
+
+    pub fn game_count(&self) -> usize {
+        self.games.len()
+    }
+
+    // does whatever can be done without waiting, meant to be called in a loop
+    pub fn poll(&mut self) {
+        self.accept_players();
+        self.greet_players();
+        self.start_games();
+        for game in &mut self.games {
+            play(game, self.config.reconnect_timeout);
+        }
+        self.finish_games();
+    }
+
+    fn accept_players(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => match stream.set_nonblocking(true) {
+                    Ok(()) => self.handshake(tungstenite::accept(stream), Instant::now()),
+                    Err(err) => log::warn!("{}", err),
+                },
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    log::warn!("couldn't accept a player: {}", err);
+                    break;
+                }
+            }
+        }
+
+        for (handshake, started) in std::mem::take(&mut self.handshakes) {
+            if started.elapsed() > self.config.reconnect_timeout {
+                let peer = handshake.get_ref().get_ref().peer_addr();
+                log::info!(
+                    "handshake with {} timed out",
+                    peer.map_or(String::from("unknown"), |address| address.to_string())
+                );
+                continue;
+            }
+            self.handshake(handshake.handshake(), started);
+        }
+    }
+
+    fn handshake(
+        &mut self,
+        result: Result<
+            tungstenite::WebSocket<TcpStream>,
+            HandshakeError<ServerHandshake<TcpStream, NoCallback>>,
+        >,
+        started: Instant,
+    ) {
+        match result {
+            Ok(socket) => match Connection::from_websocket(socket) {
+                Ok(connection) => {
+                    log::info!("{} connected", connection.peer());
+                    self.waiting.push(Waiting {
+                        connection,
+                        connected: Instant::now(),
+                        ready: false,
+                        seat: None,
+                        rejected: false,
+                    });
+                }
+                Err(err) => log::warn!("{}", err),
+            },
+            Err(HandshakeError::Interrupted(handshake)) => {
+                self.handshakes.push((handshake, started))
+            }
+            Err(HandshakeError::Failure(err)) => log::warn!("handshake failed: {}", err),
+        }
+    }
+
+    fn greet_players(&mut self) {
+        let games = &self.games;
+        let timeout = self.config.reconnect_timeout;
+        self.waiting.retain_mut(|player| {
+            let messages = match player.connection.receive() {
+                Ok(messages) => messages,
+                Err(err) => {
+                    log::info!("{}", err);
+                    return false;
+                }
+            };
+            for message in messages {
+                let reply = match message {
+                    Message::Watch { version, game } if version == PROTOCOL_VERSION => {
+                        let watched = match game {
+                            Some(id) => games.iter().find(|game| game.id == id),
+                            None => games.last(),
+                        };
+                        match watched {
+                            Some(game) => {
+                                player.seat = Some(Seat::Spectator(game.id));
+                                game.session.watching(game.id)
+                            }
+                            None => Message::Error {
+                                reason: String::from("there's no game to watch"),
+                            },
+                        }
+                    }
+                    Message::Rejoin { version, token } if version == PROTOCOL_VERSION => {
+                        let rejoined = games.iter().find_map(|game| {
+                            game.session
+                                .player_with_token(&token)
+                                .map(|colour| (game, colour))
+                        });
+                        match rejoined {
+                            Some((game, colour)) => {
+                                player.seat = Some(Seat::Player(game.id, colour));
+                                game.session.sync(colour)
+                            }
+                            None => {
+                                player.rejected = true;
+                                Message::Error {
+                                    reason: String::from("there's no game to rejoin"),
+                                }
+                            }
+                        }
+                    }
+                    Message::Hello { version } if version == PROTOCOL_VERSION => {
+                        player.ready = true;
+                        Message::Hello {
+                            version: PROTOCOL_VERSION,
+                        }
+                    }
+                    Message::Hello { .. } | Message::Watch { .. } | Message::Rejoin { .. } => {
+                        Message::Error {
+                            reason: format!("protocol version {} is needed", PROTOCOL_VERSION),
+                        }
+                    }
+                    Message::Ping { id } => Message::Pong { id },
+                    _ => Message::Error {
+                        reason: String::from("there's no game yet"),
+                    },
+                };
+                if let Err(err) = player.connection.send(&reply) {
+                    log::info!("{}", err);
+                    return false;
+                }
+            }
+            let greeted = player.ready || player.seat.is_some() || player.rejected;
+            if !greeted && player.connected.elapsed() > timeout {
+                log::info!("{} didn't say hello in time", player.connection.peer());
+                return false;
+            }
+            true
+        });
+
+        let (leaving, waiting): (Vec<Waiting>, Vec<Waiting>) = std::mem::take(&mut self.waiting)
+            .into_iter()
+            .partition(|player| player.rejected);
+        for player in leaving {
+            player.connection.close();
+        }
+        let (seated, waiting): (Vec<Waiting>, Vec<Waiting>) = waiting
+            .into_iter()
+            .partition(|player| player.seat.is_some());
+        self.waiting = waiting;
+        for player in seated {
+            let (id, colour) = match player.seat {
+                Some(Seat::Spectator(id)) => (id, None),
+                Some(Seat::Player(id, colour)) => (id, Some(colour)),
+                None => continue,
+            };
+            let game = match self.games.iter_mut().find(|game| game.id == id) {
+                Some(game) => game,
+                None => continue,
+            };
+            match colour {
+                Some(colour) => {
+                    log::info!(
+                        "game {}: {} rejoined as {:?}",
+                        id,
+                        player.connection.peer(),
+                        colour
+                    );
+                    // the old connection might not have been noticed as gone yet
+                    if let Some(old) = game.connection(colour).replace(player.connection) {
+                        old.close();
+                    }
+                    *game.left(colour) = None;
+                }
+                None => {
+                    log::info!("game {}: {} is watching", id, player.connection.peer());
+                    game.spectators.push(player.connection);
+                }
+            }
+        }
+    }
+
+    fn start_games(&mut self) {
+        loop {
+            let ready: Vec<usize> = (0..self.waiting.len())
+                .filter(|i| self.waiting[*i].ready)
+                .take(2)
+                .collect();
+            if ready.len() < 2 {
+                break;
+            }
+            // the later one goes first, so that the earlier index stays the same
+            let black = self.waiting.remove(ready[1]).connection;
+            let white = self.waiting.remove(ready[0]).connection;
+
+            let mut game = Game {
+                id: self.next_game_id,
+                session: Session::new(
+                    Board::from_variant(self.config.variant),
+                    self.config.time_control,
+                ),
+                white_name: white.peer().to_string(),
+                black_name: black.peer().to_string(),
+                white: Some(white),
+                black: Some(black),
+                spectators: Vec::new(),
+                white_left: None,
+                black_left: None,
+            };
+            self.next_game_id += 1;
+            log::info!(
+                "game {}: {} plays {}",
+                game.id,
+                game.white_name,
+                game.black_name
+            );
+            for colour in [ChessPieceColour::White, ChessPieceColour::Black] {
+                let sync = game.session.sync(colour);
+                game.send(colour, &sync);
+            }
+            self.games.push(game);
+        }
+    }
+
+    fn finish_games(&mut self) {
+        let (finished, playing) = std::mem::take(&mut self.games)
+            .into_iter()
+            .partition(|game| game.session.is_finished());
+        self.games = playing;
+
+        for game in finished {
+            log::info!(
+                "game {} is over: {}",
+                game.id,
+                game.session
+                    .result()
+                    .map_or(String::new(), |result| result.to_string())
+            );
+            if let Err(err) = self.save(&game) {
+                log::warn!("game {}: {}", game.id, err);
+            }
+            let players = [game.white, game.black].into_iter().flatten();
+            for connection in players.chain(game.spectators) {
+                connection.close();
+            }
+        }
+    }
+
+    fn save(&self, game: &Game) -> Result<(), String> {
+        let dir = match &self.config.games_dir {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+
+        let record = game.session.record();
+        let pgn = write_pgn(
+            &[
+                ("Event", String::from("bevy_chess server")),
+                (
+                    "Site",
+                    self.local_addr()
+                        .map_or(String::from("?"), |address| address.to_string()),
+                ),
+                ("Date", pgn_date(now)),
+                ("Round", game.id.to_string()),
+                ("White", game.white_name.clone()),
+                ("Black", game.black_name.clone()),
+                ("Termination", game.session.termination().to_string()),
+            ],
+            &record.start,
+            &record.moves,
+            game.session.result(),
+        )?;
+        let path = dir.join(format!("{}-{}.pgn", now, game.id));
+        fs::write(&path, pgn).map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+    }
+}
+
+// passes the messages of both players through the session, a player that left forfeits unless it
+// rejoins in time
+fn play(game: &mut Game, reconnect_timeout: Duration) {
+    check_clock(game);
+    let id = game.id;
+    game.spectators.retain_mut(|spectator| {
+        let messages = match spectator.receive() {
+            Ok(messages) => messages,
+            Err(err) => {
+                log::info!("game {}: {}", id, err);
+                return false;
+            }
+        };
+        messages.into_iter().all(|message| {
+            let reply = match message {
+                Message::Ping { id } => Message::Pong { id },
+                Message::Pong { .. } => return true,
+                _ => Message::Error {
+                    reason: String::from("spectators can't play"),
+                },
+            };
+            spectator.send(&reply).is_ok()
+        })
+    });
+
+    for colour in [ChessPieceColour::White, ChessPieceColour::Black] {
+        let messages = match game.connection(colour) {
+            Some(connection) => connection.receive(),
+            None => continue,
+        };
+        let messages = messages.unwrap_or_else(|err| {
+            log::info!("game {}: {}", game.id, err);
+            *game.connection(colour) = None;
+            Vec::new()
+        });
+
+        for message in messages {
+            match message {
+                Message::Ping { id } => game.send(colour, &Message::Pong { id }),
+                // saying hello again gets the game again
+                Message::Hello { .. } => {
+                    let sync = game.session.sync(colour);
+                    game.send(colour, &sync);
+                }
+                message => match game.session.handle(colour, message) {
+                    Ok(Some(relayed)) => {
+                        // a move made too late gets the flag, which the player has to hear about
+                        // too
+                        if matches!(relayed, Message::Flag { .. }) {
+                            game.send(colour, &relayed);
+                        }
+                        game.send(!colour, &relayed);
+                        game.send_spectators(colour, &relayed);
+                    }
+                    Ok(None) => {}
+                    Err(reason) => {
+                        let sync = game.session.sync(colour);
+                        game.send(colour, &Message::Error { reason });
+                        game.send(colour, &sync);
+                    }
+                },
+            }
+        }
+    }
+
+    // running out of time while away loses on time rather than by forfeit
+    check_clock(game);
+    for colour in [ChessPieceColour::White, ChessPieceColour::Black] {
+        if game.connection(colour).is_some() || game.session.is_finished() {
+            continue;
+        }
+        let left = *game.left(colour).get_or_insert_with(Instant::now);
+        if left.elapsed() < reconnect_timeout {
+            continue;
+        }
+        log::info!("game {}: {:?} didn't come back", game.id, colour);
+        if let Some(abandoned) = game.session.abandon(colour) {
+            game.send(!colour, &abandoned);
+            game.send_spectators(colour, &abandoned);
+        }
+    }
+}
+
+// ends the game once the side to move runs out of time and lets everyone know
+fn check_clock(game: &mut Game) {
+    if let Some(flag) = game.session.check_clock() {
+        let colour = game.session.to_move();
+        log::info!("game {}: {:?} ran out of time", game.id, colour);
+        game.send(colour, &flag);
+        game.send(!colour, &flag);
+        game.send_spectators(colour, &flag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::net::connection::Connection;
+    use crate::net::protocol::{Message, PROTOCOL_VERSION};
+    use crate::net::server::{Server, ServerConfig};
+    use crate::rules::pieces::ChessPieceColour;
+
+    // waits for the first message the check picks out, skipping the ones before it
+    fn wait_for<T>(connection: &mut Connection, check: impl Fn(Message) -> Option<T>) -> T {
+        for _ in 0..200 {
+            for message in connection.receive().unwrap() {
+                if let Some(found) = check(message) {
+                    return found;
+                }
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out");
+    }
+
+    fn join(address: &str) -> Connection {
+        let mut connection = Connection::connect(address).unwrap();
+        connection
+            .send(&Message::Hello {
+                version: PROTOCOL_VERSION,
+            })
+            .unwrap();
+        connection
+    }
+
+    #[test]
+    fn unfinished_handshakes_time_out() {
+        let mut server = Server::bind(
+            "127.0.0.1:0",
+            ServerConfig {
+                reconnect_timeout: Duration::from_millis(50),
+                ..ServerConfig::default()
+            },
+        )
+        .unwrap();
+        let mut stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n").unwrap();
+        server.poll();
+        assert_eq!(1, server.handshakes.len());
+
+        thread::sleep(Duration::from_millis(100));
+        server.poll();
+        assert!(server.handshakes.is_empty());
+        stream
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(0, stream.read(&mut [0; 16]).unwrap());
+    }
+
+    #[test]
+    fn silent_connections_time_out() {
+        let mut server = Server::bind(
+            "127.0.0.1:0",
+            ServerConfig {
+                reconnect_timeout: Duration::from_millis(50),
+                ..ServerConfig::default()
+            },
+        )
+        .unwrap();
+        let address = format!("ws://{}/", server.local_addr().unwrap());
+        let connecting =
+            thread::spawn(move || (Connection::connect(&address).unwrap(), join(&address)));
+        while !connecting.is_finished() {
+            server.poll();
+            thread::sleep(Duration::from_millis(5));
+        }
+        let (mut silent, _ready) = connecting.join().unwrap();
+        for _ in 0..20 {
+            server.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(1, server.waiting.len());
+        assert!(server.waiting[0].ready);
+        for _ in 0..20 {
+            if silent.receive().is_err() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("the silent connection is still open");
+    }
+
+    #[test]
+    fn games_over_localhost() {
+        let games_dir = env::temp_dir().join(format!("bevy_chess_server_{}", std::process::id()));
+        let mut server = Server::bind(
+            "127.0.0.1:0",
+            ServerConfig {
+                games_dir: Some(games_dir.clone()),
+                ..ServerConfig::default()
+            },
+        )
+        .unwrap();
+        let address = format!("ws://{}/", server.local_addr().unwrap());
+        let polling = thread::spawn(move || {
+            // runs until the game has been played and written out
+            for _ in 0..1000 {
+                server.poll();
+                if server.next_game_id > 1 && server.game_count() == 0 {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+
+        let mut white = join(&address);
+        let mut black = join(&address);
+        let sync = |message| match message {
+            Message::Sync {
+                game,
+                colour,
+                token,
+                ..
+            } => Some((colour, game.moves.len(), token.unwrap())),
+            _ => None,
+        };
+        assert_eq!(ChessPieceColour::White, wait_for(&mut white, sync).0);
+        let (colour, _, black_token) = wait_for(&mut black, sync);
+        assert_eq!(ChessPieceColour::Black, colour);
+
+        black
+            .send(&Message::Move {
+                uci: "e7e5".parse().unwrap(),
+            })
+            .unwrap();
+        wait_for(&mut black, |message| match message {
+            Message::Error { reason } => Some(reason),
+            _ => None,
+        });
+
+        // the spectator gets the game so far and then everything that's played, but can't play
+        let mut spectator = Connection::connect(&address).unwrap();
+        spectator
+            .send(&Message::Watch {
+                version: PROTOCOL_VERSION,
+                game: None,
+            })
+            .unwrap();
+        let watched = wait_for(&mut spectator, |message| match message {
+            Message::Watching { id, game, .. } => Some((id, game.moves.len())),
+            _ => None,
+        });
+        assert_eq!((1, 0), watched);
+        spectator
+            .send(&Message::Move {
+                uci: "e2e4".parse().unwrap(),
+            })
+            .unwrap();
+        wait_for(&mut spectator, |message| match message {
+            Message::Error { reason } => Some(reason),
+            _ => None,
+        });
+
+        white
+            .send(&Message::Move {
+                uci: "e2e4".parse().unwrap(),
+            })
+            .unwrap();
+        let relayed = wait_for(&mut black, |message| match message {
+            Message::Move { uci } => Some(uci),
+            _ => None,
+        });
+        assert_eq!("e2e4", relayed.to_string());
+
+        // black loses the connection and gets back in with the token, the game goes on from there
+        black.close();
+        let mut black = Connection::connect(&address).unwrap();
+        black
+            .send(&Message::Rejoin {
+                version: PROTOCOL_VERSION,
+                token: String::from("guess"),
+            })
+            .unwrap();
+        wait_for(&mut black, |message| match message {
+            Message::Error { reason } => Some(reason),
+            _ => None,
+        });
+        let mut black = Connection::connect(&address).unwrap();
+        black
+            .send(&Message::Rejoin {
+                version: PROTOCOL_VERSION,
+                token: black_token,
+            })
+            .unwrap();
+        let (colour, moves, _) = wait_for(&mut black, sync);
+        assert_eq!((ChessPieceColour::Black, 1), (colour, moves));
+
+        white.send(&Message::Resign).unwrap();
+        wait_for(&mut black, |message| match message {
+            Message::Resign => Some(()),
+            _ => None,
+        });
+        // the spectator is let go once the game is over
+        let mut played = Vec::new();
+        for _ in 0..200 {
+            match spectator.receive() {
+                Ok(messages) => {
+                    played.extend(messages.into_iter().filter_map(|message| match message {
+                        Message::Played { colour, message } => Some((colour, *message)),
+                        _ => None,
+                    }))
+                }
+                Err(_) => break,
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(matches!(
+            &played[..],
+            [
+                (ChessPieceColour::White, Message::Move { uci }),
+                (ChessPieceColour::White, Message::Resign),
+            ] if uci.to_string() == "e2e4"
+        ));
+
+        polling.join().unwrap();
+        let saved: Vec<String> = fs::read_dir(&games_dir)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        fs::remove_dir_all(&games_dir).unwrap();
+        assert_eq!(1, saved.len());
+        assert!(saved[0].contains("[Result \"0-1\"]"));
+        assert!(saved[0].contains("[Termination \"normal\"]"));
+        assert!(saved[0].ends_with("1. e4 0-1\n"));
+    }
+
+    #[test]
+    fn players_that_dont_come_back_forfeit() {
+        let games_dir = env::temp_dir().join(format!(
+            "bevy_chess_server_abandoned_{}",
+            std::process::id()
+        ));
+        let mut server = Server::bind(
+            "127.0.0.1:0",
+            ServerConfig {
+                games_dir: Some(games_dir.clone()),
+                reconnect_timeout: Duration::from_millis(50),
+                ..ServerConfig::default()
+            },
+        )
+        .unwrap();
+        let address = format!("ws://{}/", server.local_addr().unwrap());
+        let polling = thread::spawn(move || {
+            for _ in 0..1000 {
+                server.poll();
+                if server.next_game_id > 1 && server.game_count() == 0 {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+
+        let white = join(&address);
+        let mut black = join(&address);
+        wait_for(&mut black, |message| match message {
+            Message::Sync { .. } => Some(()),
+            _ => None,
+        });
+        white.close();
+        let abandoned = wait_for(&mut black, |message| match message {
+            Message::Abandoned { colour } => Some(colour),
+            _ => None,
+        });
+        assert_eq!(ChessPieceColour::White, abandoned);
+
+        polling.join().unwrap();
+        let saved: Vec<String> = fs::read_dir(&games_dir)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        fs::remove_dir_all(&games_dir).unwrap();
+        assert!(saved[0].contains("[Result \"0-1\"]"));
+        assert!(saved[0].contains("[Termination \"abandoned\"]"));
+    }
+}
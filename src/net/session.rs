@@ -0,0 +1,466 @@
+use std::time::{Duration, Instant};
+
+use crate::net::protocol::{Message, MoveTimes};
+use crate::rules::board::Board;
+use crate::rules::clock::{ChessClock, TimeControl};
+use crate::rules::pgn::{GameResult, Termination};
+use crate::rules::pieces::ChessPieceColour;
+use crate::rules::serialisation::GameRecord;
+
+// the authoritative copy of a game played over the network. every message from a player goes
+// through `handle`, which checks it against the rules before it's passed on to the other player
+pub struct Session {
+    game: GameRecord,
+    board: Board,
+    time_control: Option<TimeControl>,
+    result: Option<GameResult>,
+    termination: Termination,
+    // the side whose draw offer hasn't been answered yet
+    draw_offer: Option<ChessPieceColour>,
+    // how long every move took, measured here so that the players' clocks can be set up again
+    move_times: Vec<Duration>,
+    turn_started: Instant,
+    // handed to the players with the game, to rejoin it with
+    white_token: String,
+    black_token: String,
+}
+
+impl Session {
+    pub fn new(start: Board, time_control: Option<TimeControl>) -> Self {
+        Self {
+            game: GameRecord {
+                start: start.clone(),
+                moves: Vec::new(),
+            },
+            board: start,
+            time_control,
+            result: None,
+            termination: Termination::Normal,
+            draw_offer: None,
+            move_times: Vec::new(),
+            turn_started: Instant::now(),
+            white_token: new_token(),
+            black_token: new_token(),
+        }
+    }
+
+    // continues a game that was already in progress
+    pub fn from_record(
+        game: GameRecord,
+        time_control: Option<TimeControl>,
+    ) -> Result<Self, String> {
+        let board = game.replay()?.pop().unwrap_or_else(|| game.start.clone());
+        let result = GameResult::from_position(&board, board.to_move);
+        // how long the moves took isn't known anymore
+        let move_times = vec![Duration::ZERO; game.moves.len()];
+        Ok(Self {
+            game,
+            board,
+            time_control,
+            result,
+            termination: Termination::Normal,
+            draw_offer: None,
+            move_times,
+            turn_started: Instant::now(),
+            white_token: new_token(),
+            black_token: new_token(),
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.game
+    }
+
+    pub fn to_move(&self) -> ChessPieceColour {
+        self.board.to_move
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.result.is_some()
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    pub fn termination(&self) -> Termination {
+        self.termination
+    }
+
+    pub fn token(&self, colour: ChessPieceColour) -> &str {
+        match colour {
+            ChessPieceColour::White => &self.white_token,
+            ChessPieceColour::Black => &self.black_token,
+        }
+    }
+
+    // the side a player that's rejoining played
+    pub fn player_with_token(&self, token: &str) -> Option<ChessPieceColour> {
+        [ChessPieceColour::White, ChessPieceColour::Black]
+            .into_iter()
+            .find(|colour| self.token(*colour) == token)
+    }
+
+    pub fn times(&self) -> MoveTimes {
+        MoveTimes {
+            moves: self
+                .move_times
+                .iter()
+                .map(|time| time.as_millis() as u64)
+                .collect(),
+            thinking: match self.result {
+                Some(_) => 0,
+                None => self.turn_started.elapsed().as_millis() as u64,
+            },
+        }
+    }
+
+    // the clocks as they are now, going by how long every move took
+    pub fn clock(&self) -> ChessClock {
+        ChessClock::replay(
+            self.time_control,
+            &self.move_times,
+            self.turn_started.elapsed(),
+        )
+    }
+
+    // ends the game once the side to move has run out of time, returns the flag to pass on to the
+    // players
+    pub fn check_clock(&mut self) -> Option<Message> {
+        if self.result.is_some() || !self.clock().is_flagged(self.to_move()) {
+            return None;
+        }
+        self.result = Some(match self.board.has_mating_material(!self.to_move()) {
+            true => GameResult::Win(!self.to_move()),
+            false => GameResult::Draw,
+        });
+        self.termination = Termination::TimeForfeit;
+        Some(Message::Flag {
+            colour: self.to_move(),
+        })
+    }
+
+    // the given side lost the connection and didn't come back in time, so it forfeits. returns
+    // what to pass on to the other player
+    pub fn abandon(&mut self, colour: ChessPieceColour) -> Option<Message> {
+        if self.result.is_some() {
+            return None;
+        }
+        self.result = Some(GameResult::Win(!colour));
+        self.termination = Termination::Abandoned;
+        Some(Message::Abandoned { colour })
+    }
+
+    // how a game that was lost on time or by forfeit ended, for a player that wasn't there to
+    // be told
+    pub fn ending(&self) -> Option<Message> {
+        match (self.termination, self.result?) {
+            (Termination::TimeForfeit, _) => Some(Message::Flag {
+                colour: self.to_move(),
+            }),
+            (Termination::Abandoned, GameResult::Win(winner)) => {
+                Some(Message::Abandoned { colour: !winner })
+            }
+            _ => None,
+        }
+    }
+
+    // the whole game, for the player of the given colour
+    pub fn sync(&self, colour: ChessPieceColour) -> Message {
+        Message::Sync {
+            game: self.game.clone(),
+            colour,
+            time_control: self
+                .time_control
+                .map(|time_control| time_control.to_string()),
+            times: self.times(),
+            token: Some(self.token(colour).to_string()),
+        }
+    }
+
+    // the whole game, for a spectator of the game with the given id
+    pub fn watching(&self, id: u64) -> Message {
+        Message::Watching {
+            id,
+            game: self.game.clone(),
+            time_control: self
+                .time_control
+                .map(|time_control| time_control.to_string()),
+            times: self.times(),
+        }
+    }
+
+    // applies a message from one of the players, returns what to pass on to the other player.
+    // messages about the connection itself, like pings, are left to the caller
+    pub fn handle(
+        &mut self,
+        from: ChessPieceColour,
+        message: Message,
+    ) -> Result<Option<Message>, String> {
+        match message {
+            Message::Move { uci } => {
+                // a move made once the flag has fallen doesn't count, the flag is passed on
+                // instead
+                if let Some(flag) = self.check_clock() {
+                    return Ok(Some(flag));
+                }
+                self.check_in_progress()?;
+                if from != self.to_move() {
+                    return Err(String::from("it's not your turn"));
+                }
+                let parsed = self.board.parse_move(&uci.to_string(), from)?;
+                self.board.play(&parsed);
+                self.game.moves.push(parsed.clone());
+                self.move_times.push(self.turn_started.elapsed());
+                self.turn_started = Instant::now();
+                self.draw_offer = None;
+                self.result = GameResult::from_position(&self.board, self.to_move());
+                Ok(Some(Message::Move { uci: parsed }))
+            }
+            Message::Resign => {
+                self.check_in_progress()?;
+                self.result = Some(GameResult::Win(!from));
+                Ok(Some(Message::Resign))
+            }
+            Message::Flag { colour } => {
+                self.check_in_progress()?;
+                if colour != self.to_move() {
+                    return Err(String::from("only the side to move can run out of time"));
+                }
+                self.check_clock()
+                    .map(Some)
+                    .ok_or(String::from("there's still time left on the clock"))
+            }
+            Message::OfferDraw => {
+                self.check_in_progress()?;
+                self.draw_offer = Some(from);
+                Ok(Some(Message::OfferDraw))
+            }
+            Message::AcceptDraw | Message::DeclineDraw => {
+                self.check_in_progress()?;
+                if self.draw_offer != Some(!from) {
+                    return Err(String::from("there's no draw offer to answer"));
+                }
+                self.draw_offer = None;
+                if matches!(message, Message::AcceptDraw) {
+                    self.result = Some(GameResult::Draw);
+                }
+                Ok(Some(message))
+            }
+            Message::Sync { .. } | Message::Watching { .. } | Message::Played { .. } => {
+                Err(String::from("only the host can send the game"))
+            }
+            Message::Abandoned { .. } => Err(String::from("only the host can end the game")),
+            Message::Watch { .. } => Err(String::from("games can only be watched on a server")),
+            Message::Rejoin { .. } => Err(String::from("you're already in the game")),
+            Message::Hello { .. }
+            | Message::Ping { .. }
+            | Message::Pong { .. }
+            | Message::Error { .. } => Ok(None),
+        }
+    }
+
+    fn check_in_progress(&self) -> Result<(), String> {
+        match self.result {
+            Some(_) => Err(String::from("the game is over")),
+            None => Ok(()),
+        }
+    }
+}
+
+// 128 bits from the operating system's random number generator
+fn new_token() -> String {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes).expect("the system has a random number generator");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::net::protocol::{Message, MoveTimes};
+    use crate::net::session::Session;
+    use crate::rules::board::Board;
+    use crate::rules::pgn::GameResult;
+    use crate::rules::pieces::ChessPieceColour;
+    use crate::rules::serialisation::GameRecord;
+
+    fn play(session: &mut Session, from: ChessPieceColour, uci: &str) -> Result<(), String> {
+        session
+            .handle(
+                from,
+                Message::Move {
+                    uci: uci.parse().unwrap(),
+                },
+            )
+            .map(|relayed| assert!(matches!(relayed, Some(Message::Move { .. }))))
+    }
+
+    #[test]
+    fn moves_are_validated() {
+        let mut session = Session::new(Board::default(), None);
+        assert!(play(&mut session, ChessPieceColour::Black, "e7e5").is_err());
+        assert!(play(&mut session, ChessPieceColour::White, "e2e5").is_err());
+        assert!(play(&mut session, ChessPieceColour::White, "e7e5").is_err());
+
+        play(&mut session, ChessPieceColour::White, "f2f3").unwrap();
+        assert!(play(&mut session, ChessPieceColour::White, "g2g4").is_err());
+        play(&mut session, ChessPieceColour::Black, "e7e5").unwrap();
+        play(&mut session, ChessPieceColour::White, "g2g4").unwrap();
+        play(&mut session, ChessPieceColour::Black, "d8h4").unwrap();
+
+        assert_eq!(
+            Some(GameResult::Win(ChessPieceColour::Black)),
+            session.result()
+        );
+        assert_eq!(4, session.record().moves.len());
+        assert_eq!(4, session.times().moves.len());
+        assert!(play(&mut session, ChessPieceColour::White, "a2a3").is_err());
+
+        let resumed = Session::from_record(session.record().clone(), None).unwrap();
+        assert_eq!(4, resumed.times().moves.len());
+        assert!(resumed.is_finished());
+        assert_eq!(ChessPieceColour::White, resumed.to_move());
+    }
+
+    #[test]
+    fn draw_offers_need_an_answer_from_the_other_side() {
+        let mut session = Session::new(Board::default(), Some("5+3".parse().unwrap()));
+        assert!(session
+            .handle(ChessPieceColour::White, Message::AcceptDraw)
+            .is_err());
+        assert!(session
+            .handle(ChessPieceColour::White, Message::OfferDraw)
+            .unwrap()
+            .is_some());
+        assert!(session
+            .handle(ChessPieceColour::White, Message::AcceptDraw)
+            .is_err());
+        session
+            .handle(ChessPieceColour::Black, Message::DeclineDraw)
+            .unwrap();
+        assert!(!session.is_finished());
+
+        session
+            .handle(ChessPieceColour::Black, Message::OfferDraw)
+            .unwrap();
+        session
+            .handle(ChessPieceColour::White, Message::AcceptDraw)
+            .unwrap();
+        assert_eq!(Some(GameResult::Draw), session.result());
+
+        assert!(session
+            .handle(
+                ChessPieceColour::White,
+                Message::Sync {
+                    game: GameRecord {
+                        start: Board::default(),
+                        moves: Vec::new(),
+                    },
+                    colour: ChessPieceColour::White,
+                    time_control: None,
+                    times: MoveTimes::default(),
+                    token: None,
+                },
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn the_clock_is_enforced() {
+        let white = ChessPieceColour::White;
+        let black = ChessPieceColour::Black;
+        let mut session = Session::new(Board::default(), Some("0.001+0".parse().unwrap()));
+        play(&mut session, white, "e2e4").unwrap();
+        assert!(session
+            .handle(black, Message::Flag { colour: white })
+            .is_err());
+        assert!(session
+            .handle(white, Message::Flag { colour: black })
+            .is_err());
+        assert!(session.check_clock().is_none());
+        assert!(session.ending().is_none());
+
+        // black moves after its flag fell
+        thread::sleep(Duration::from_millis(100));
+        assert!(matches!(
+            session.handle(
+                black,
+                Message::Move {
+                    uci: "e7e5".parse().unwrap()
+                }
+            ),
+            Ok(Some(Message::Flag {
+                colour: ChessPieceColour::Black
+            }))
+        ));
+        assert_eq!(1, session.record().moves.len());
+        assert_eq!(Some(GameResult::Win(white)), session.result());
+        assert!(matches!(
+            session.ending(),
+            Some(Message::Flag {
+                colour: ChessPieceColour::Black
+            })
+        ));
+
+        let mut session = Session::new(Board::default(), Some("0.001+0".parse().unwrap()));
+        play(&mut session, white, "e2e4").unwrap();
+        assert!(session
+            .handle(white, Message::Flag { colour: black })
+            .is_err());
+        assert!(session.check_clock().is_none());
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(matches!(
+            session.handle(white, Message::Flag { colour: black }),
+            Ok(Some(Message::Flag {
+                colour: ChessPieceColour::Black
+            }))
+        ));
+        assert_eq!(Some(GameResult::Win(white)), session.result());
+        assert!(session.check_clock().is_none());
+    }
+
+    #[test]
+    fn abandoned_games_are_forfeited() {
+        let mut session = Session::new(Board::default(), None);
+        assert!(matches!(
+            session.abandon(ChessPieceColour::Black),
+            Some(Message::Abandoned {
+                colour: ChessPieceColour::Black
+            })
+        ));
+        assert_eq!(
+            Some(GameResult::Win(ChessPieceColour::White)),
+            session.result()
+        );
+        assert!(matches!(
+            session.ending(),
+            Some(Message::Abandoned {
+                colour: ChessPieceColour::Black
+            })
+        ));
+        assert!(session.abandon(ChessPieceColour::White).is_none());
+    }
+
+    #[test]
+    fn players_rejoin_with_their_token() {
+        let session = Session::new(Board::default(), None);
+        let token = match session.sync(ChessPieceColour::Black) {
+            Message::Sync { token, .. } => token.unwrap(),
+            other => panic!("expected a sync, got {:?}", other),
+        };
+        assert_ne!(token, session.token(ChessPieceColour::White));
+        assert_eq!(
+            Some(ChessPieceColour::Black),
+            session.player_with_token(&token)
+        );
+        assert_eq!(None, session.player_with_token("guess"));
+    }
+}
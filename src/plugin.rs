@@ -0,0 +1,513 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::config::ChessConfig;
+use crate::game::actions::{expire_draw_offer, handle_game_actions, DrawOffer, GameActionEvent};
+use crate::game::annotations::{annotate, AnnotationDrag, BoardAnnotations};
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::book::{load_opening_book, Book};
+use crate::game::clock::tick_clock;
+use crate::game::clock::GameClock;
+use crate::game::computer::{answer_draw_offers, computer_move};
+use crate::game::correspondence::{
+    close_correspondence_game, open_dropped_games, sign_local_moves, Correspondence,
+    CorrespondenceDir,
+};
+use crate::game::history::{navigate_history, stop_viewing_history, ViewedPly};
+use crate::game::keyboard::{
+    keyboard_move, reset_keyboard_input, type_move, KeyboardCursor, MoveTextInput,
+};
+use crate::game::network::{
+    close_network_game, ping, receive_messages, reconnect, restore_sync, send_local_actions,
+    send_local_moves, start_session, wait_for_game, NetworkGame, PendingSync,
+};
+use crate::game::outcome::{detect_game_end, GameOutcome};
+use crate::game::save::{
+    autosave, autosave_on_pause, forget_finished_game, restore_game, PendingGame, SaveDir,
+};
+use crate::game::state::{
+    enter_game_over, finish_setup, reset_game, toggle_pause, AppState, GameSetup,
+};
+use crate::game::{
+    cancel_drag, make_move, mouse_click, mouse_release, BoardClickEvent, CheckEvent, DragEvent,
+    DragState, IsBlackTurn, MoveEvent, MoveRequestEvent,
+};
+use crate::settings::{remember_window_size, save_settings, Settings, SettingsFile};
+use crate::theme::{apply_theme, restyle_fonts, CurrentTheme, Theme, ThemeHandles, ThemeLoader};
+use crate::tilemap::animation::{animate_pieces, fade_out_pieces};
+use crate::tilemap::annotations::draw_annotations;
+use crate::tilemap::arrows::ArrowHeadMesh;
+use crate::tilemap::board::{create_board_tilemap, update_board_texture};
+use crate::tilemap::captured::{draw_captured_pieces, spawn_captured_trays};
+use crate::tilemap::checked_tile::{spawn_checked_tile, CheckedTileHandle};
+use crate::tilemap::clock::{draw_clocks, spawn_clocks};
+use crate::tilemap::despawn_game_entities;
+use crate::tilemap::drag::{follow_cursor, show_dragged_piece};
+use crate::tilemap::hover::{restyle_hover_ring, show_hover_ring, spawn_hover_ring};
+use crate::tilemap::last_move::draw_last_move;
+use crate::tilemap::move_indicators::{
+    spawn_move_indicators, MoveIndicatorHandle, TakeIndicatorHandle,
+};
+use crate::tilemap::orientation::{flip_board, orient_board, orient_for_player, BoardOrientation};
+use crate::tilemap::pieces::{sync_pieces, update_piece_atlas, ChessPieceAtlas};
+use crate::tilemap::ranks_and_files::{create_labels, restyle_labels};
+use crate::tilemap::turn_indicator::{draw_turn_indicators, spawn_turn_indicators};
+use crate::ui::actions::{
+    handle_action_buttons, place_action_buttons, spawn_action_buttons, update_action_buttons,
+};
+use crate::ui::book::{spawn_book_panel, update_book_panel};
+use crate::ui::menu::{handle_menu_buttons, spawn_main_menu, update_menu_labels, MainMenuScreen};
+use crate::ui::move_input::{spawn_move_input, update_move_input};
+use crate::ui::opening::{spawn_opening_name, update_opening_name};
+use crate::ui::overlays::{
+    handle_overlay_buttons, spawn_game_over_overlay, spawn_pause_overlay, spawn_review_panel,
+    Overlay,
+};
+use crate::ui::saves::{handle_saves_buttons, spawn_saves_screen, SavesScreen, SavesScreenMode};
+use crate::ui::settings::{
+    handle_settings_buttons, spawn_settings_screen, update_settings_labels, SettingsScreen,
+};
+use crate::ui::{despawn_with, highlight_buttons};
+use crate::utils::cursor::{update_cursor_pos, CursorPos};
+
+// the whole game: rules, board, mouse input and the menus around them.
+// the app starts in the main menu, the game's setup can be picked there or
+// inserted as a `GameSetup` resource before adding the plugin
+#[derive(Default)]
+pub struct ChessPlugin {
+    pub config: ChessConfig,
+}
+
+impl Plugin for ChessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .add_plugin(ChessRulesPlugin)
+            .add_plugin(ChessPersistencePlugin)
+            .add_plugin(ChessBookPlugin)
+            .add_plugin(ChessNetworkPlugin)
+            .add_plugin(ChessCorrespondencePlugin)
+            .add_plugin(ChessBoardRenderPlugin)
+            .add_plugin(ChessInputPlugin)
+            // main menu
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(spawn_main_menu))
+            .add_system_set(
+                SystemSet::on_update(AppState::MainMenu)
+                    .with_system(handle_menu_buttons)
+                    .with_system(update_menu_labels.after(handle_menu_buttons)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::MainMenu).with_system(despawn_with::<MainMenuScreen>),
+            )
+            // resign and draw buttons
+            .add_system_set(
+                SystemSet::on_update(AppState::Setup)
+                    .with_system(spawn_action_buttons)
+                    .with_system(spawn_move_input)
+                    .with_system(spawn_book_panel)
+                    .with_system(spawn_opening_name),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(handle_action_buttons.before(handle_game_actions))
+                    .with_system(update_action_buttons.after(handle_game_actions)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::GameOver).with_system(update_action_buttons),
+            )
+            // overlays
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(spawn_pause_overlay))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Paused).with_system(despawn_with::<Overlay>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::GameOver).with_system(spawn_game_over_overlay),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::GameOver).with_system(despawn_with::<Overlay>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Review).with_system(spawn_review_panel))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Review).with_system(despawn_with::<Overlay>),
+            )
+            // settings
+            .init_resource::<SettingsFile>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Settings).with_system(spawn_settings_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Settings)
+                    .with_system(handle_settings_buttons)
+                    .with_system(update_settings_labels.after(handle_settings_buttons)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Settings).with_system(despawn_with::<SettingsScreen>),
+            )
+            .add_system(remember_window_size)
+            .add_system(save_settings.after(remember_window_size))
+            // save slots
+            .init_resource::<SavesScreenMode>()
+            .add_system_set(SystemSet::on_enter(AppState::Saves).with_system(spawn_saves_screen))
+            .add_system_set(SystemSet::on_update(AppState::Saves).with_system(handle_saves_buttons))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Saves).with_system(despawn_with::<SavesScreen>),
+            )
+            .add_system(place_action_buttons)
+            .add_system(update_move_input)
+            .add_system(update_book_panel)
+            .add_system(update_opening_name)
+            .add_system(highlight_buttons)
+            .add_system(handle_overlay_buttons);
+    }
+}
+
+// the game state, moves, clocks and results, without anything drawn on screen.
+// games are started by setting the state to `AppState::Setup`
+pub struct ChessRulesPlugin;
+
+impl Plugin for ChessRulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(AppState::MainMenu)
+            .init_resource::<GameSetup>()
+            .init_resource::<ChessBoard>()
+            .init_resource::<BoardHistory>()
+            .init_resource::<ViewedPly>()
+            .init_resource::<IsBlackTurn>()
+            .init_resource::<GameOutcome>()
+            .init_resource::<DrawOffer>()
+            .init_resource::<BoardAnnotations>()
+            .init_resource::<GameClock>()
+            .add_event::<BoardClickEvent>()
+            .add_event::<CheckEvent>()
+            .add_event::<MoveEvent>()
+            .add_event::<MoveRequestEvent>()
+            .add_event::<DragEvent>()
+            .add_event::<GameActionEvent>()
+            .add_system_set(SystemSet::on_enter(AppState::Setup).with_system(reset_game))
+            .add_system_set(SystemSet::on_update(AppState::Setup).with_system(finish_setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(tick_clock)
+                    .with_system(computer_move.before(make_move))
+                    .with_system(answer_draw_offers.before(handle_game_actions))
+                    .with_system(make_move)
+                    .with_system(detect_game_end.after(make_move))
+                    .with_system(expire_draw_offer.after(make_move))
+                    .with_system(handle_game_actions)
+                    .with_system(
+                        enter_game_over
+                            .after(detect_game_end)
+                            .after(handle_game_actions)
+                            .after(tick_clock),
+                    ),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Playing).with_system(stop_viewing_history))
+            .add_system_set(SystemSet::on_exit(AppState::Review).with_system(stop_viewing_history));
+    }
+}
+
+// the save slots, saving the game in progress and loading saved games back in
+pub struct ChessPersistencePlugin;
+
+impl Plugin for ChessPersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveDir>()
+            .init_resource::<PendingGame>()
+            // a saved game is replayed once the board is ready
+            .add_system_set(
+                SystemSet::on_update(AppState::Setup)
+                    .with_system(restore_game.before(finish_setup)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(autosave.after(detect_game_end)),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(autosave_on_pause))
+            .add_system_set(
+                SystemSet::on_enter(AppState::GameOver).with_system(forget_finished_game),
+            );
+    }
+}
+
+// the opening book from the settings, without it the computer only plays random moves
+pub struct ChessBookPlugin;
+
+impl Plugin for ChessBookPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<Book>()
+            .add_system(load_opening_book);
+    }
+}
+
+// games against another player over the network, hosted, joined or watched from the main menu
+pub struct ChessNetworkPlugin;
+
+impl Plugin for ChessNetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkGame>()
+            .init_resource::<PendingSync>()
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(close_network_game))
+            .add_system_set(
+                SystemSet::on_update(AppState::MainMenu)
+                    .with_system(wait_for_game.after(handle_menu_buttons)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Setup)
+                    .with_system(start_session.before(finish_setup))
+                    .with_system(restore_sync.before(finish_setup)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(
+                        receive_messages
+                            .before(make_move)
+                            .before(handle_game_actions),
+                    )
+                    .with_system(send_local_moves.after(make_move))
+                    .with_system(send_local_actions.after(handle_action_buttons))
+                    .with_system(reconnect.before(receive_messages)),
+            )
+            // a player that comes back after the game ended is still told how it ended
+            .add_system_set(SystemSet::on_update(AppState::GameOver).with_system(receive_messages))
+            .add_system(ping);
+    }
+}
+
+// correspondence games, played by sending a game file back and forth and opened by dropping it
+// onto the window
+pub struct ChessCorrespondencePlugin;
+
+impl Plugin for ChessCorrespondencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CorrespondenceDir>()
+            .init_resource::<Correspondence>()
+            // the game in a dropped file is loaded like a saved game
+            .init_resource::<PendingGame>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::MainMenu).with_system(close_correspondence_game),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::MainMenu)
+                    .with_system(open_dropped_games.after(handle_menu_buttons)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(sign_local_moves.after(make_move)),
+            );
+    }
+}
+
+// the board, pieces and everything drawn around them
+pub struct ChessBoardRenderPlugin;
+
+impl Plugin for ChessBoardRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChessConfig>()
+            .init_resource::<MoveIndicatorHandle>()
+            .init_resource::<TakeIndicatorHandle>()
+            .add_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .init_resource::<CurrentTheme>()
+            .init_resource::<ThemeHandles>()
+            .init_resource::<CheckedTileHandle>()
+            .init_resource::<ChessPieceAtlas>()
+            .init_resource::<ArrowHeadMesh>()
+            .init_resource::<Settings>()
+            .init_resource::<BoardOrientation>()
+            .add_plugin(TilemapPlugin)
+            .add_system_set(
+                SystemSet::on_enter(AppState::MainMenu).with_system(despawn_game_entities),
+            )
+            // the board tilemap has to exist before anything is put around it
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(despawn_game_entities)
+                    .with_system(orient_for_player)
+                    .with_system(
+                        create_board_tilemap
+                            .after(reset_game)
+                            .after(orient_for_player),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Setup)
+                    .with_system(create_labels)
+                    .with_system(spawn_turn_indicators)
+                    .with_system(spawn_clocks)
+                    .with_system(spawn_captured_trays),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(sync_pieces.after(show_dragged_piece))
+                    .with_system(draw_last_move.after(orient_board))
+                    .with_system(draw_annotations.after(orient_board)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::GameOver)
+                    .with_system(sync_pieces.after(show_dragged_piece))
+                    .with_system(draw_last_move.after(orient_board))
+                    .with_system(draw_annotations.after(orient_board)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Review)
+                    .with_system(sync_pieces.after(show_dragged_piece))
+                    .with_system(draw_last_move.after(orient_board))
+                    .with_system(draw_annotations.after(orient_board)),
+            )
+            .add_system(apply_theme)
+            .add_system(restyle_fonts.after(apply_theme))
+            .add_system(update_board_texture.after(apply_theme))
+            .add_system(update_piece_atlas.after(apply_theme))
+            .add_system(restyle_labels.after(apply_theme))
+            .add_system(restyle_hover_ring.after(apply_theme))
+            .add_system(orient_board)
+            .add_system(draw_turn_indicators)
+            .add_system(draw_clocks)
+            .add_system(draw_captured_pieces)
+            .add_system(spawn_move_indicators)
+            .add_system(spawn_checked_tile)
+            .add_system(show_dragged_piece)
+            .add_system(follow_cursor.after(show_dragged_piece))
+            .add_system(animate_pieces)
+            .add_system(fade_out_pieces);
+    }
+}
+
+// picking and moving pieces with the mouse, by clicking or dragging them, or with the keyboard,
+// by moving a cursor with the arrow keys or typing moves in, browsing through finished games
+// with the arrow keys, drawing on the board with the right mouse button, flipping it with F and
+// pausing with escape
+pub struct ChessInputPlugin;
+
+impl Plugin for ChessInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorPos>()
+            .init_resource::<DragState>()
+            .init_resource::<AnnotationDrag>()
+            .init_resource::<KeyboardCursor>()
+            .init_resource::<MoveTextInput>()
+            .add_system_set(SystemSet::on_enter(AppState::Setup).with_system(reset_keyboard_input))
+            .add_system_set(SystemSet::on_update(AppState::Setup).with_system(spawn_hover_ring))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(mouse_click.after(update_cursor_pos).before(make_move))
+                    .with_system(
+                        mouse_release
+                            .after(update_cursor_pos)
+                            .after(mouse_click)
+                            .before(make_move),
+                    )
+                    .with_system(
+                        type_move
+                            .before(keyboard_move)
+                            .before(flip_board)
+                            .before(toggle_pause)
+                            .before(make_move),
+                    )
+                    .with_system(keyboard_move.before(toggle_pause).before(make_move))
+                    .with_system(navigate_history)
+                    .with_system(flip_board)
+                    .with_system(annotate.after(update_cursor_pos)),
+            )
+            .add_system_set(SystemSet::on_pause(AppState::Playing).with_system(cancel_drag))
+            .add_system_set(SystemSet::on_exit(AppState::Playing).with_system(cancel_drag))
+            .add_system_set(
+                SystemSet::on_update(AppState::GameOver)
+                    .with_system(flip_board)
+                    .with_system(annotate.after(update_cursor_pos)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Review)
+                    .with_system(navigate_history)
+                    .with_system(flip_board)
+                    .with_system(annotate.after(update_cursor_pos)),
+            )
+            .add_system(update_cursor_pos)
+            .add_system(
+                show_hover_ring
+                    .after(update_cursor_pos)
+                    .after(keyboard_move),
+            )
+            .add_system(toggle_pause);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy_ecs_tilemap::prelude::*;
+
+    use crate::game::actions::{DrawOffer, GameAction, GameActionEvent};
+    use crate::game::board::{BoardHistory, ChessBoard};
+    use crate::game::outcome::GameOutcome;
+    use crate::game::state::{AppState, GameSetup, Opponent};
+    use crate::game::{IsBlackTurn, MoveRequestEvent};
+    use crate::plugin::ChessRulesPlugin;
+    use crate::rules::pieces::ChessPieceColour;
+
+    #[test]
+    fn rules_plugin_without_rendering() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(ChessRulesPlugin);
+        app.update();
+
+        app.world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::Setup)
+            .unwrap();
+        app.update();
+        assert_eq!(
+            &AppState::Playing,
+            app.world.resource::<State<AppState>>().current()
+        );
+
+        // e2-e4, then an illegal move for black that should be ignored
+        app.world.send_event(MoveRequestEvent {
+            from: TilePos::new(4, 1),
+            to: TilePos::new(4, 3),
+        });
+        app.update();
+        app.world.send_event(MoveRequestEvent {
+            from: TilePos::new(4, 6),
+            to: TilePos::new(4, 3),
+        });
+        app.update();
+
+        let board = app.world.resource::<ChessBoard>();
+        assert!(board.get(4, 1).is_none());
+        assert!(board.get(4, 3).is_some());
+        assert!(board.get(4, 6).is_some());
+        assert_eq!(1, app.world.resource::<BoardHistory>().0.len());
+        assert!(app.world.resource::<IsBlackTurn>().0);
+    }
+
+    #[test]
+    fn the_computer_turns_down_draw_offers() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(ChessRulesPlugin)
+            .insert_resource(GameSetup {
+                opponent: Opponent::Computer(ChessPieceColour::Black),
+                ..GameSetup::default()
+            });
+        app.update();
+        app.world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::Setup)
+            .unwrap();
+        app.update();
+
+        app.world.send_event(GameActionEvent {
+            colour: ChessPieceColour::White,
+            action: GameAction::OfferDraw,
+        });
+        app.update();
+        assert_eq!(
+            Some(ChessPieceColour::White),
+            app.world.resource::<DrawOffer>().0
+        );
+        app.update();
+        assert_eq!(None, app.world.resource::<DrawOffer>().0);
+        assert!(app.world.resource::<GameOutcome>().0.is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::rules::{BoardPos, MoveFromTo};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnnotationColour {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl AnnotationColour {
+    fn letter(self) -> char {
+        match self {
+            AnnotationColour::Green => 'G',
+            AnnotationColour::Red => 'R',
+            AnnotationColour::Yellow => 'Y',
+            AnnotationColour::Blue => 'B',
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'G' => Some(AnnotationColour::Green),
+            'R' => Some(AnnotationColour::Red),
+            'Y' => Some(AnnotationColour::Yellow),
+            'B' => Some(AnnotationColour::Blue),
+            _ => None,
+        }
+    }
+}
+
+// marks drawn on the board while analysing a game. they're kept in PGN comments the same way
+// other chess software does it, e.g. `[%csl Ge4,Rd5][%cal Ge2e4,Bg1f3]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotations {
+    pub squares: Vec<(BoardPos, AnnotationColour)>,
+    pub arrows: Vec<(MoveFromTo, AnnotationColour)>,
+}
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        self.squares.is_empty() && self.arrows.is_empty()
+    }
+
+    // marking a square again in the same colour removes the mark, in another colour it's recoloured
+    pub fn toggle_square(&mut self, square: BoardPos, colour: AnnotationColour) {
+        toggle(&mut self.squares, square, colour);
+    }
+
+    // same as for squares, arrows are told apart by where they start and end
+    pub fn toggle_arrow(&mut self, arrow: MoveFromTo, colour: AnnotationColour) {
+        toggle(&mut self.arrows, arrow, colour);
+    }
+
+    // the `[%csl ...]` and `[%cal ...]` commands for a PGN comment, empty if there's nothing drawn
+    pub fn to_pgn_comment(&self) -> String {
+        let mut comment = String::new();
+        if !self.squares.is_empty() {
+            let squares: Vec<String> = self
+                .squares
+                .iter()
+                .map(|(square, colour)| format!("{}{}", colour.letter(), square))
+                .collect();
+            comment += &format!("[%csl {}]", squares.join(","));
+        }
+        if !self.arrows.is_empty() {
+            let arrows: Vec<String> = self
+                .arrows
+                .iter()
+                .map(|(arrow, colour)| format!("{}{}", colour.letter(), arrow))
+                .collect();
+            comment += &format!("[%cal {}]", arrows.join(","));
+        }
+        comment
+    }
+
+    // picks the annotations out of a PGN comment, anything else in the comment is ignored
+    pub fn from_pgn_comment(comment: &str) -> Result<Self, String> {
+        let mut annotations = Self::default();
+
+        let mut rest = comment;
+        while let Some(start) = rest.find("[%") {
+            let end = rest[start..]
+                .find(']')
+                .map(|end| start + end)
+                .ok_or(format!("unterminated command in comment: {}", comment))?;
+            let command = &rest[start + 2..end];
+            rest = &rest[end + 1..];
+
+            let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+            let args = args.split(',').map(str::trim).filter(|arg| !arg.is_empty());
+            match name {
+                "csl" => {
+                    for arg in args {
+                        let (colour, square) = parse_colour(arg)?;
+                        annotations.toggle_square(square.parse()?, colour);
+                    }
+                }
+                "cal" => {
+                    for arg in args {
+                        let (colour, arrow) = parse_colour(arg)?;
+                        annotations.toggle_arrow(arrow.parse()?, colour);
+                    }
+                }
+                // other commands, like clock times, aren't annotations
+                _ => {}
+            }
+        }
+
+        Ok(annotations)
+    }
+}
+
+fn toggle<T: PartialEq>(marks: &mut Vec<(T, AnnotationColour)>, mark: T, colour: AnnotationColour) {
+    match marks.iter().position(|(other, _)| *other == mark) {
+        Some(i) if marks[i].1 == colour => {
+            marks.remove(i);
+        }
+        Some(i) => marks[i].1 = colour,
+        None => marks.push((mark, colour)),
+    }
+}
+
+fn parse_colour(arg: &str) -> Result<(AnnotationColour, &str), String> {
+    let mut chars = arg.chars();
+    let colour = chars
+        .next()
+        .and_then(AnnotationColour::from_letter)
+        .ok_or(format!("invalid annotation: {}", arg))?;
+    Ok((colour, chars.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::annotations::{AnnotationColour, Annotations};
+    use crate::rules::{BoardPos, MoveFromTo};
+
+    #[test]
+    fn toggle_marks() {
+        let mut annotations = Annotations::default();
+        let e4 = BoardPos { x: 4, y: 3 };
+        annotations.toggle_square(e4, AnnotationColour::Green);
+        annotations.toggle_square(e4, AnnotationColour::Red);
+        assert_eq!(vec![(e4, AnnotationColour::Red)], annotations.squares);
+        annotations.toggle_square(e4, AnnotationColour::Red);
+        assert!(annotations.is_empty());
+
+        let e2e4 = MoveFromTo::new(4, 1, 4, 3);
+        annotations.toggle_arrow(e2e4.clone(), AnnotationColour::Blue);
+        annotations.toggle_arrow(MoveFromTo::new(4, 3, 4, 1), AnnotationColour::Blue);
+        assert_eq!(2, annotations.arrows.len());
+        annotations.toggle_arrow(e2e4, AnnotationColour::Blue);
+        assert_eq!(1, annotations.arrows.len());
+    }
+
+    #[test]
+    fn pgn_comments() {
+        let mut annotations = Annotations::default();
+        assert_eq!("", annotations.to_pgn_comment());
+
+        annotations.toggle_square(BoardPos { x: 4, y: 3 }, AnnotationColour::Green);
+        annotations.toggle_square(BoardPos { x: 3, y: 9 }, AnnotationColour::Yellow);
+        annotations.toggle_arrow(MoveFromTo::new(4, 1, 4, 3), AnnotationColour::Red);
+        let comment = annotations.to_pgn_comment();
+        assert_eq!("[%csl Ge4,Yd10][%cal Re2e4]", comment);
+        assert_eq!(Ok(annotations), Annotations::from_pgn_comment(&comment));
+
+        let annotations =
+            Annotations::from_pgn_comment("a good move [%clk 0:05:00] [%cal Bg1f3, Ge2e4]")
+                .unwrap();
+        assert!(annotations.squares.is_empty());
+        assert_eq!(
+            vec![
+                (MoveFromTo::new(6, 0, 5, 2), AnnotationColour::Blue),
+                (MoveFromTo::new(4, 1, 4, 3), AnnotationColour::Green),
+            ],
+            annotations.arrows
+        );
+
+        assert!(Annotations::from_pgn_comment("[%csl Xe4]").is_err());
+        assert!(Annotations::from_pgn_comment("[%cal Ge2e4").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+// parser for the subset of Betza's funny notation used to describe custom pieces
+//
+// supported:
+// - the leaper atoms W, F, D, N, A, H, C, Z and G
+// - the compounds R (WW), B (FF), Q (RB) and K (WF)
+// - doubling an atom to turn it into a rider, e.g. NN is the nightrider
+// - a number after an atom to limit its range, e.g. R2 moves up to two tiles orthogonally,
+//   while 0 means unlimited range
+//
+// modifiers (lowercase letters like m, c, f or b) are not supported
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovePattern {
+    // one offset for every direction the piece can go in
+    pub offsets: Vec<(i32, i32)>,
+    // how many times an offset can be applied in a row, None means until the piece is blocked
+    pub range: Option<u32>,
+}
+
+impl MovePattern {
+    fn leaper(x: i32, y: i32) -> Self {
+        let mut offsets = Vec::new();
+        for (x, y) in [(x, y), (y, x)] {
+            for (x_sign, y_sign) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                let offset = (x * x_sign, y * y_sign);
+                if !offsets.contains(&offset) {
+                    offsets.push(offset);
+                }
+            }
+        }
+
+        Self {
+            offsets,
+            range: Some(1),
+        }
+    }
+}
+
+fn atom(letter: char) -> Option<(i32, i32)> {
+    match letter {
+        'W' => Some((1, 0)),
+        'F' => Some((1, 1)),
+        'D' => Some((2, 0)),
+        'N' => Some((2, 1)),
+        'A' => Some((2, 2)),
+        'H' => Some((3, 0)),
+        'C' => Some((3, 1)),
+        'Z' => Some((3, 2)),
+        'G' => Some((3, 3)),
+        _ => None,
+    }
+}
+
+// returns the atoms making up a letter and whether the letter is a rider on its own
+fn expand(letter: char) -> Option<(Vec<(i32, i32)>, bool)> {
+    match letter {
+        'R' => Some((vec![(1, 0)], true)),
+        'B' => Some((vec![(1, 1)], true)),
+        'Q' => Some((vec![(1, 0), (1, 1)], true)),
+        'K' => Some((vec![(1, 0), (1, 1)], false)),
+        _ => atom(letter).map(|atom| (vec![atom], false)),
+    }
+}
+
+pub fn parse(betza: &str) -> Result<Vec<MovePattern>, String> {
+    let mut patterns = Vec::new();
+    let mut chars = betza.chars().peekable();
+
+    while let Some(letter) = chars.next() {
+        let (atoms, mut rider) = match expand(letter) {
+            Some(expanded) => expanded,
+            None if letter.is_lowercase() => {
+                return Err(format!("betza modifiers are not supported: {}", letter))
+            }
+            None => return Err(format!("unknown betza atom: {}", letter)),
+        };
+
+        if atom(letter).is_some() && chars.peek() == Some(&letter) {
+            chars.next();
+            rider = true;
+        }
+
+        let mut digits = String::new();
+        while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+            digits.push(digit);
+        }
+        let range = if digits.is_empty() {
+            match rider {
+                true => None,
+                false => Some(1),
+            }
+        } else {
+            match digits.parse::<u32>() {
+                Ok(0) => None,
+                Ok(range) => Some(range),
+                Err(err) => return Err(format!("invalid betza range {}: {}", digits, err)),
+            }
+        };
+
+        for (x, y) in atoms {
+            patterns.push(MovePattern {
+                range,
+                ..MovePattern::leaper(x, y)
+            });
+        }
+    }
+
+    if patterns.is_empty() {
+        return Err(String::from("betza notation is empty"));
+    }
+
+    Ok(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::betza::{parse, MovePattern};
+
+    #[test]
+    fn leapers() {
+        assert_eq!(Ok(vec![MovePattern::leaper(2, 1)]), parse("N"));
+        assert_eq!(8, MovePattern::leaper(2, 1).offsets.len());
+        assert_eq!(4, MovePattern::leaper(1, 0).offsets.len());
+        assert_eq!(4, MovePattern::leaper(2, 2).offsets.len());
+        assert_eq!(
+            Ok(vec![MovePattern::leaper(1, 0), MovePattern::leaper(1, 1)]),
+            parse("K")
+        );
+    }
+
+    #[test]
+    fn riders() {
+        let rook = parse("R").unwrap();
+        assert_eq!(1, rook.len());
+        assert_eq!(None, rook[0].range);
+        assert_eq!(rook, parse("WW").unwrap());
+
+        let nightrider = parse("NN").unwrap();
+        assert_eq!(None, nightrider[0].range);
+        assert_eq!(MovePattern::leaper(2, 1).offsets, nightrider[0].offsets);
+
+        let short_rook = parse("R2").unwrap();
+        assert_eq!(Some(2), short_rook[0].range);
+        assert_eq!(None, parse("W0").unwrap()[0].range);
+    }
+
+    #[test]
+    fn compounds() {
+        let amazon = parse("QN").unwrap();
+        assert_eq!(3, amazon.len());
+        assert_eq!(None, amazon[0].range);
+        assert_eq!(None, amazon[1].range);
+        assert_eq!(Some(1), amazon[2].range);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse("").is_err());
+        assert!(parse("X").is_err());
+        assert!(parse("mW").is_err());
+    }
+}
@@ -0,0 +1,941 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::rules::betza::MovePattern;
+use crate::rules::custom_pieces::{CustomPiece, PieceSet};
+use crate::rules::pieces::{ChessPiece, ChessPieceColour, ChessPieceKind};
+use crate::rules::{BoardPos, Move, MoveFromTo};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Variant {
+    #[default]
+    Standard,
+    // 10x8, adds the archbishop and the chancellor
+    Capablanca,
+    // 6x6, no bishops, no castling and no pawn double moves
+    LosAlamos,
+}
+
+impl Variant {
+    pub fn size(&self) -> (u32, u32) {
+        match self {
+            Variant::Standard => (8, 8),
+            Variant::Capablanca => (10, 8),
+            Variant::LosAlamos => (6, 6),
+        }
+    }
+
+    fn back_rank(&self) -> &'static [ChessPieceKind] {
+        use ChessPieceKind::*;
+        match self {
+            Variant::Standard => &[Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook],
+            Variant::Capablanca => &[
+                Rook, Knight, Archbishop, Bishop, Queen, King, Bishop, Chancellor, Knight, Rook,
+            ],
+            Variant::LosAlamos => &[Rook, Knight, Queen, King, Knight, Rook],
+        }
+    }
+
+    pub fn allows_castling(&self) -> bool {
+        !matches!(self, Variant::LosAlamos)
+    }
+
+    pub fn allows_pawn_double_move(&self) -> bool {
+        !matches!(self, Variant::LosAlamos)
+    }
+
+    // the files the king and the rook end up on when castling towards the given side, -1 for the
+    // queen side and 1 for the king side. like on the standard board the king goes next to the
+    // corner on the king side and two files from it on the queen side, e.g. to i1 and c1 in
+    // Capablanca chess
+    pub fn castling_files(&self, x_offset: i32) -> (u32, u32) {
+        let (width, _) = self.size();
+        match x_offset < 0 {
+            true => (2, 3),
+            false => (width - 2, width - 3),
+        }
+    }
+}
+
+// the other way around from parsing
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Variant::Standard => "standard",
+            Variant::Capablanca => "capablanca",
+            Variant::LosAlamos => "los_alamos",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "standard" => Ok(Variant::Standard),
+            "capablanca" => Ok(Variant::Capablanca),
+            "los_alamos" | "losalamos" => Ok(Variant::LosAlamos),
+            _ => Err(format!("unknown variant: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    pub(crate) board: Vec<Vec<Option<ChessPiece>>>,
+    pub(crate) variant: Variant,
+    pub(crate) custom_pieces: Arc<Vec<CustomPiece>>,
+    pub(crate) last_move: Option<MoveFromTo>,
+    pub check: Option<ChessPieceColour>,
+    // white on a new board, then switched by every move that's played
+    pub to_move: ChessPieceColour,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::from_variant(Variant::Standard)
+    }
+}
+
+impl Board {
+    // empty 8x8 board, mostly useful for setting up positions by hand
+    pub fn new() -> Self {
+        Self::with_variant(Variant::Standard)
+    }
+
+    // returns an empty board with the dimensions and rules of the given variant
+    pub fn with_variant(variant: Variant) -> Self {
+        let (width, height) = variant.size();
+        Self {
+            board: vec![vec![None; width as usize]; height as usize],
+            variant,
+            custom_pieces: Arc::new(Vec::new()),
+            last_move: None,
+            check: None,
+            to_move: ChessPieceColour::White,
+        }
+    }
+
+    // returns a board set up with the starting position of the given variant
+    pub fn from_variant(variant: Variant) -> Self {
+        let mut board = Self::with_variant(variant);
+        let top = board.height() - 1;
+
+        for (x, kind) in variant.back_rank().iter().enumerate() {
+            let x = x as u32;
+            board.set(x, top, ChessPieceColour::Black, *kind);
+            board.set(x, top - 1, ChessPieceColour::Black, ChessPieceKind::Pawn);
+            board.set(x, 1, ChessPieceColour::White, ChessPieceKind::Pawn);
+            board.set(x, 0, ChessPieceColour::White, *kind);
+        }
+
+        board
+    }
+
+    // makes the custom pieces of the set available on this board, and if the set has its own
+    // back rank, replaces the back ranks of both sides with it
+    pub fn with_piece_set(mut self, piece_set: PieceSet) -> Result<Self, String> {
+        self.custom_pieces = Arc::new(piece_set.pieces);
+
+        if let Some(back_rank) = piece_set.back_rank {
+            if back_rank.len() != self.width() as usize {
+                return Err(format!(
+                    "back rank has {} pieces, but the board is {} tiles wide",
+                    back_rank.len(),
+                    self.width()
+                ));
+            }
+            let top = self.height() - 1;
+            for (x, kind) in back_rank.into_iter().enumerate() {
+                self.set(x as u32, top, ChessPieceColour::Black, kind);
+                self.set(x as u32, 0, ChessPieceColour::White, kind);
+            }
+        }
+
+        Ok(self)
+    }
+
+    // the move that led to this position
+    pub fn last_move(&self) -> Option<&MoveFromTo> {
+        self.last_move.as_ref()
+    }
+
+    pub fn custom_piece(&self, id: u8) -> Option<&CustomPiece> {
+        self.custom_pieces.get(id as usize)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.board[0].len() as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.board.len() as u32
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (0..self.width() as i32).contains(&x) && (0..self.height() as i32).contains(&y)
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<&ChessPiece> {
+        self.board
+            .get((self.height() as usize - 1).checked_sub(y as usize)?)?
+            .get(x as usize)?
+            .as_ref()
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, colour: ChessPieceColour, kind: ChessPieceKind) {
+        let row = self.row(y);
+        self.board[row][x as usize] = Some(ChessPiece::new(x, y, colour, kind));
+    }
+
+    fn delete(&mut self, x: u32, y: u32) {
+        let row = self.row(y);
+        self.board[row][x as usize] = None;
+    }
+
+    // rows are stored top to bottom, so the bottom rank (y = 0) is the last row
+    pub(crate) fn row(&self, y: u32) -> usize {
+        (self.height() - 1 - y) as usize
+    }
+
+    pub fn _move(&mut self, from_x: u32, from_y: u32, to_x: u32, to_y: u32) {
+        if let Some(piece) = self.get(from_x, from_y) {
+            let mut piece = *piece;
+            if !piece.has_moved {
+                piece.has_moved = true;
+            }
+            piece.x = to_x;
+            piece.y = to_y;
+            let row = self.row(to_y);
+            self.board[row][to_x as usize] = Some(piece);
+            self.delete(from_x, from_y);
+
+            match piece.kind {
+                // the king only ever moves more than one tile to castle
+                ChessPieceKind::King if from_y == to_y && from_x.abs_diff(to_x) >= 2 => {
+                    let (towards_rook, rook_x): (i32, u32) = match to_x.checked_sub(from_x) {
+                        None => (-1, 0),
+                        Some(_) => (1, self.width() - 1),
+                    };
+                    let (_, rook_to_x) = self.variant.castling_files(towards_rook);
+                    self._move(rook_x, from_y, rook_to_x, from_y);
+                }
+                ChessPieceKind::Pawn => {
+                    let y_offset = match piece.colour {
+                        ChessPieceColour::White => 1,
+                        ChessPieceColour::Black => -1,
+                    };
+                    if from_x.abs_diff(to_x) == 1
+                        && from_y.abs_diff(to_y) == 1
+                        && self.last_move
+                            == Some(MoveFromTo::new(
+                                to_x,
+                                (to_y as i32 + y_offset) as u32,
+                                to_x,
+                                from_y,
+                            ))
+                    {
+                        self.delete(to_x, from_y);
+                    }
+                }
+                _ => (),
+            };
+
+            self.last_move = Some(MoveFromTo::new(from_x, from_y, to_x, to_y));
+        } else {
+            log::error!(
+                "tried to move an empty tile from {},{} to {},{}",
+                from_x,
+                from_y,
+                to_x,
+                to_y
+            );
+        }
+    }
+
+    // makes a move and works out whether it put the other side in check, without checking that
+    // the move is legal
+    pub fn play(&mut self, _move: &MoveFromTo) {
+        let (from, to) = (_move.from(), _move.to());
+        let colour = match self.get(from.x, from.y) {
+            Some(piece) => piece.colour,
+            None => return self._move(from.x, from.y, to.x, to.y),
+        };
+        self._move(from.x, from.y, to.x, to.y);
+        self.to_move = !colour;
+
+        self.check = self
+            .find_piece(ChessPieceKind::King, !colour)
+            .filter(|king| !self.get_king_attackers(king.colour).is_empty())
+            .map(|king| king.colour);
+    }
+
+    const KNIGHT_MOVE_OFFSETS: [(i32, i32); 8] = [
+        (1, 2),
+        (2, 1),
+        (-1, 2),
+        (-2, 1),
+        (1, -2),
+        (2, -1),
+        (-1, -2),
+        (-2, -1),
+    ];
+    const ROOK_MOVE_OFFSETS: [i32; 2] = [-1, 1];
+    const BISHOP_MOVE_OFFSETS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+    fn get_pawn_moves(
+        &self,
+        x: u32,
+        y: u32,
+        colour: &ChessPieceColour,
+        has_moved: bool,
+    ) -> HashSet<Move> {
+        let mut moves = HashSet::new();
+
+        let y_offset: i32 = match colour {
+            ChessPieceColour::White => 1,
+            ChessPieceColour::Black => -1,
+        };
+        // a situation where we check if a pawn can move off the top or bottom of the board should not happen
+        let potential_y = y as i32 + y_offset;
+
+        // basic move + double move
+        if let Some(_move) = self.can_do_move(x, potential_y as u32, colour) {
+            if !_move.takes {
+                moves.insert(_move);
+                if !has_moved && self.variant.allows_pawn_double_move() {
+                    if let Some(_move) =
+                        self.can_do_move(x, (potential_y + y_offset) as u32, colour)
+                    {
+                        if !_move.takes {
+                            moves.insert(_move);
+                        }
+                    }
+                }
+            }
+        }
+
+        // taking + en passant
+        for x_offset in [-1, 1] {
+            let potential_x = x as i32 + x_offset;
+            if let Some(_move) = self.can_do_move(potential_x as u32, potential_y as u32, colour) {
+                if _move.takes {
+                    moves.insert(_move);
+                } else if let Some(last_move) = &self.last_move {
+                    if last_move
+                        == &MoveFromTo::new(
+                            potential_x as u32,
+                            (potential_y + y_offset) as u32,
+                            potential_x as u32,
+                            y,
+                        )
+                    {
+                        if let Some(piece) = self.get(potential_x as u32, y) {
+                            if let ChessPieceKind::Pawn = piece.kind {
+                                // if last turn a pawn could make a move that allows for en passant,
+                                // then there shouldn't be a piece that belongs to the opponent
+                                // in the tile that we have to move to to perform en passant
+                                if let Some(_move) =
+                                    self.can_do_move(potential_x as u32, potential_y as u32, colour)
+                                {
+                                    moves.insert(_move);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        moves
+    }
+
+    fn get_knight_moves(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<Move> {
+        let mut moves = HashSet::new();
+
+        for (x_offset, y_offset) in Self::KNIGHT_MOVE_OFFSETS {
+            let potential_x = x as i32 + x_offset;
+            let potential_y = y as i32 + y_offset;
+            if self.contains(potential_x, potential_y) {
+                let potential_x = potential_x as u32;
+                let potential_y = potential_y as u32;
+                let potential_tile = self.get(potential_x, potential_y);
+                let takes: bool;
+                if let Some(piece) = potential_tile {
+                    if &piece.colour != colour {
+                        takes = true;
+                    } else {
+                        continue;
+                    }
+                } else {
+                    takes = false;
+                }
+                moves.insert(Move {
+                    x: potential_x,
+                    y: potential_y,
+                    takes,
+                });
+            }
+        }
+
+        moves
+    }
+
+    fn get_rook_moves(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<Move> {
+        let mut moves = HashSet::new();
+
+        for x_offset in Self::ROOK_MOVE_OFFSETS {
+            let mut potential_x = x as i32 + x_offset;
+            while let Some(_move) = self.can_do_move(potential_x as u32, y, colour) {
+                potential_x += x_offset;
+                let takes = _move.takes;
+                moves.insert(_move);
+                if takes {
+                    break;
+                }
+            }
+        }
+
+        for y_offset in Self::ROOK_MOVE_OFFSETS {
+            let mut potential_y = y as i32 + y_offset;
+            while let Some(_move) = self.can_do_move(x, potential_y as u32, colour) {
+                potential_y += y_offset;
+                let takes = _move.takes;
+                moves.insert(_move);
+                if takes {
+                    break;
+                }
+            }
+        }
+
+        moves
+    }
+
+    fn get_bishop_moves(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<Move> {
+        let mut moves = HashSet::new();
+
+        for (x_offset, y_offset) in Self::BISHOP_MOVE_OFFSETS {
+            let mut potential_x = x as i32 + x_offset;
+            let mut potential_y = y as i32 + y_offset;
+            while let Some(_move) = self.can_do_move(potential_x as u32, potential_y as u32, colour)
+            {
+                let takes = _move.takes;
+                moves.insert(_move);
+                if takes {
+                    break;
+                }
+                potential_x += x_offset;
+                potential_y += y_offset;
+            }
+        }
+
+        moves
+    }
+
+    fn get_queen_moves(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<Move> {
+        &self.get_rook_moves(x, y, colour) | &self.get_bishop_moves(x, y, colour)
+    }
+
+    fn get_archbishop_moves(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<Move> {
+        &self.get_bishop_moves(x, y, colour) | &self.get_knight_moves(x, y, colour)
+    }
+
+    fn get_chancellor_moves(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<Move> {
+        &self.get_rook_moves(x, y, colour) | &self.get_knight_moves(x, y, colour)
+    }
+
+    fn get_custom_moves(
+        &self,
+        x: u32,
+        y: u32,
+        colour: &ChessPieceColour,
+        patterns: &[MovePattern],
+    ) -> HashSet<Move> {
+        let mut moves = HashSet::new();
+
+        for pattern in patterns {
+            for (x_offset, y_offset) in &pattern.offsets {
+                let mut potential_x = x as i32 + x_offset;
+                let mut potential_y = y as i32 + y_offset;
+                let mut steps = 1;
+                while pattern.range.is_none_or(|range| steps <= range) {
+                    match self.can_do_move(potential_x as u32, potential_y as u32, colour) {
+                        Some(_move) => {
+                            let takes = _move.takes;
+                            moves.insert(_move);
+                            if takes {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                    potential_x += x_offset;
+                    potential_y += y_offset;
+                    steps += 1;
+                }
+            }
+        }
+
+        moves
+    }
+
+    fn get_king_moves(
+        &self,
+        x: u32,
+        y: u32,
+        colour: &ChessPieceColour,
+        has_moved: bool,
+    ) -> HashSet<Move> {
+        let mut moves = HashSet::new();
+
+        // basic moves
+        for y_offset in -1..=1 {
+            for x_offset in -1..=1 {
+                if x_offset == y_offset && x_offset == 0 {
+                    continue;
+                }
+                let potential_x = x as i32 + x_offset;
+                let potential_y = y as i32 + y_offset;
+                if let Some(_move) =
+                    self.can_do_move(potential_x as u32, potential_y as u32, colour)
+                {
+                    moves.insert(_move);
+                }
+            }
+        }
+
+        // castling
+        if !has_moved && self.variant.allows_castling() {
+            'x_sides: for x_offset in [-1, 1] {
+                let mut potential_x = x as i32 + x_offset;
+                while let Some(_move) = self.can_do_move(potential_x as u32, y, colour) {
+                    if _move.takes {
+                        continue 'x_sides;
+                    }
+                    potential_x += x_offset;
+                }
+                if [0, self.width() as i32 - 1].contains(&potential_x) {
+                    if let Some(piece) = self.get(potential_x as u32, y) {
+                        if let ChessPieceKind::Rook = piece.kind {
+                            // if we got this far without breaking the loop, then the chess piece
+                            // at either edge of the board has to be the same colour
+                            // ...plus the king is in check anyway if the rook in that position
+                            // is the wrong colour
+                            let (king_x, _) = self.variant.castling_files(x_offset);
+                            if !piece.has_moved && king_x.abs_diff(x) >= 2 {
+                                moves.insert(Move {
+                                    x: king_x,
+                                    y,
+                                    takes: false,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut threats: HashSet<BoardPos> = HashSet::new();
+        for piece in self
+            .iter()
+            .flatten()
+            .filter(|piece| &piece.colour != colour)
+        {
+            match self.get_threatened_tiles(piece.x, piece.y) {
+                Some(new_threats) => threats = &threats | &new_threats,
+                None => continue,
+            };
+        }
+        let moves_to_remove: Vec<Move> = moves
+            .iter()
+            .filter(|&_move| threats.iter().any(|threat| _move == threat))
+            .cloned()
+            .collect();
+        for _move in moves_to_remove {
+            moves.remove(&_move);
+        }
+
+        // can't castle out of, through or into check
+        if !has_moved {
+            moves.retain(|_move| {
+                _move.y != y
+                    || _move.x.abs_diff(x) < 2
+                    || (x.min(_move.x)..=x.max(_move.x))
+                        .all(|tile_x| !threats.contains(&BoardPos { x: tile_x, y }))
+            });
+        }
+
+        moves
+    }
+
+    fn get_pawn_threats(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<BoardPos> {
+        let mut threats = HashSet::new();
+
+        let y_offset: i32 = match colour {
+            ChessPieceColour::White => 1,
+            ChessPieceColour::Black => -1,
+        };
+        let potential_y = (y as i32 + y_offset) as u32;
+
+        if potential_y < self.height() {
+            for x_offset in [-1, 1] {
+                let potential_x = (x as i32 + x_offset) as u32;
+                if potential_x < self.width() {
+                    threats.insert(BoardPos {
+                        x: potential_x,
+                        y: potential_y,
+                    });
+                }
+            }
+        }
+
+        threats
+    }
+
+    fn get_knight_threats(&self, x: u32, y: u32) -> HashSet<BoardPos> {
+        let mut threats = HashSet::new();
+
+        for (x_offset, y_offset) in Self::KNIGHT_MOVE_OFFSETS {
+            let potential_x = (x as i32 + x_offset) as u32;
+            let potential_y = (y as i32 + y_offset) as u32;
+            if potential_x < self.width() && potential_y < self.height() {
+                threats.insert(BoardPos {
+                    x: potential_x,
+                    y: potential_y,
+                });
+            }
+        }
+
+        threats
+    }
+
+    fn get_rook_threats(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<BoardPos> {
+        let mut threats = HashSet::new();
+
+        for x_offset in Self::ROOK_MOVE_OFFSETS {
+            let mut potential_x = x as i32 + x_offset;
+            if self.contains(potential_x, y as i32) {
+                let mut next_tile = self.get(potential_x as u32, y);
+                loop {
+                    if let Some(piece) = next_tile {
+                        if &piece.colour == colour || piece.kind != ChessPieceKind::King {
+                            break;
+                        }
+                    }
+                    threats.insert(BoardPos {
+                        x: potential_x as u32,
+                        y,
+                    });
+                    potential_x += x_offset;
+                    if !self.contains(potential_x, y as i32) {
+                        break;
+                    }
+                    next_tile = self.get(potential_x as u32, y);
+                }
+                if self.get(potential_x as u32, y).is_some() {
+                    threats.insert(BoardPos {
+                        x: potential_x as u32,
+                        y,
+                    });
+                }
+            }
+        }
+
+        for y_offset in Self::ROOK_MOVE_OFFSETS {
+            let mut potential_y = y as i32 + y_offset;
+            if self.contains(x as i32, potential_y) {
+                let mut next_tile = self.get(x, potential_y as u32);
+                loop {
+                    if let Some(piece) = next_tile {
+                        if &piece.colour == colour || piece.kind != ChessPieceKind::King {
+                            break;
+                        }
+                    }
+                    threats.insert(BoardPos {
+                        x,
+                        y: potential_y as u32,
+                    });
+                    potential_y += y_offset;
+                    if !self.contains(x as i32, potential_y) {
+                        break;
+                    }
+                    next_tile = self.get(x, potential_y as u32);
+                }
+                if self.get(x, potential_y as u32).is_some() {
+                    threats.insert(BoardPos {
+                        x,
+                        y: potential_y as u32,
+                    });
+                }
+            }
+        }
+
+        threats
+    }
+
+    fn get_bishop_threats(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<BoardPos> {
+        let mut threats = HashSet::new();
+
+        for (x_offset, y_offset) in Self::BISHOP_MOVE_OFFSETS {
+            let mut potential_x = x as i32 + x_offset;
+            let mut potential_y = y as i32 + y_offset;
+            if self.contains(potential_x, potential_y) {
+                let mut next_tile = self.get(potential_x as u32, potential_y as u32);
+                loop {
+                    if let Some(piece) = next_tile {
+                        if &piece.colour == colour || piece.kind != ChessPieceKind::King {
+                            break;
+                        }
+                    }
+                    threats.insert(BoardPos {
+                        x: potential_x as u32,
+                        y: potential_y as u32,
+                    });
+                    potential_x += x_offset;
+                    potential_y += y_offset;
+                    if !self.contains(potential_x, potential_y) {
+                        break;
+                    }
+                    next_tile = self.get(potential_x as u32, potential_y as u32);
+                }
+                if self.get(potential_x as u32, potential_y as u32).is_some() {
+                    threats.insert(BoardPos {
+                        x: potential_x as u32,
+                        y: potential_y as u32,
+                    });
+                }
+            }
+        }
+
+        threats
+    }
+
+    fn get_queen_threats(&self, x: u32, y: u32, colour: &ChessPieceColour) -> HashSet<BoardPos> {
+        &self.get_rook_threats(x, y, colour) | &self.get_bishop_threats(x, y, colour)
+    }
+
+    fn get_archbishop_threats(
+        &self,
+        x: u32,
+        y: u32,
+        colour: &ChessPieceColour,
+    ) -> HashSet<BoardPos> {
+        &self.get_bishop_threats(x, y, colour) | &self.get_knight_threats(x, y)
+    }
+
+    fn get_chancellor_threats(
+        &self,
+        x: u32,
+        y: u32,
+        colour: &ChessPieceColour,
+    ) -> HashSet<BoardPos> {
+        &self.get_rook_threats(x, y, colour) | &self.get_knight_threats(x, y)
+    }
+
+    fn get_custom_threats(
+        &self,
+        x: u32,
+        y: u32,
+        colour: &ChessPieceColour,
+        patterns: &[MovePattern],
+    ) -> HashSet<BoardPos> {
+        let mut threats = HashSet::new();
+
+        for pattern in patterns {
+            for (x_offset, y_offset) in &pattern.offsets {
+                let mut potential_x = x as i32 + x_offset;
+                let mut potential_y = y as i32 + y_offset;
+                let mut steps = 1;
+                while pattern.range.is_none_or(|range| steps <= range)
+                    && self.contains(potential_x, potential_y)
+                {
+                    threats.insert(BoardPos {
+                        x: potential_x as u32,
+                        y: potential_y as u32,
+                    });
+                    // like the other riders, keep going through the opposing king
+                    if let Some(piece) = self.get(potential_x as u32, potential_y as u32) {
+                        if &piece.colour == colour || piece.kind != ChessPieceKind::King {
+                            break;
+                        }
+                    }
+                    potential_x += x_offset;
+                    potential_y += y_offset;
+                    steps += 1;
+                }
+            }
+        }
+
+        threats
+    }
+
+    fn get_king_threats(&self, x: u32, y: u32) -> HashSet<BoardPos> {
+        let mut threats = HashSet::new();
+
+        // basic moves
+        for y_offset in -1..=1 {
+            for x_offset in -1..=1 {
+                if x_offset == y_offset && x_offset == 0 {
+                    continue;
+                }
+                let potential_x = (x as i32 + x_offset) as u32;
+                let potential_y = (y as i32 + y_offset) as u32;
+                threats.insert(BoardPos {
+                    x: potential_x,
+                    y: potential_y,
+                });
+            }
+        }
+
+        threats
+    }
+
+    pub fn get_moves(&self, x: u32, y: u32) -> Option<HashSet<Move>> {
+        self.get(x, y).map(|piece| match piece.kind {
+            ChessPieceKind::Pawn => self.get_pawn_moves(x, y, &piece.colour, piece.has_moved),
+            ChessPieceKind::Knight => self.get_knight_moves(x, y, &piece.colour),
+            ChessPieceKind::Rook => self.get_rook_moves(x, y, &piece.colour),
+            ChessPieceKind::Bishop => self.get_bishop_moves(x, y, &piece.colour),
+            ChessPieceKind::Queen => self.get_queen_moves(x, y, &piece.colour),
+            ChessPieceKind::Archbishop => self.get_archbishop_moves(x, y, &piece.colour),
+            ChessPieceKind::Chancellor => self.get_chancellor_moves(x, y, &piece.colour),
+            ChessPieceKind::Custom(id) => match self.custom_piece(id) {
+                Some(custom) => self.get_custom_moves(x, y, &piece.colour, &custom.patterns),
+                None => HashSet::new(),
+            },
+            ChessPieceKind::King => self.get_king_moves(x, y, &piece.colour, piece.has_moved),
+        })
+    }
+
+    pub fn get_threatened_tiles(&self, x: u32, y: u32) -> Option<HashSet<BoardPos>> {
+        self.get(x, y).map(|piece| match piece.kind {
+            ChessPieceKind::Pawn => self.get_pawn_threats(x, y, &piece.colour),
+            ChessPieceKind::Knight => self.get_knight_threats(x, y),
+            ChessPieceKind::Rook => self.get_rook_threats(x, y, &piece.colour),
+            ChessPieceKind::Bishop => self.get_bishop_threats(x, y, &piece.colour),
+            ChessPieceKind::Queen => self.get_queen_threats(x, y, &piece.colour),
+            ChessPieceKind::Archbishop => self.get_archbishop_threats(x, y, &piece.colour),
+            ChessPieceKind::Chancellor => self.get_chancellor_threats(x, y, &piece.colour),
+            ChessPieceKind::Custom(id) => match self.custom_piece(id) {
+                Some(custom) => self.get_custom_threats(x, y, &piece.colour, &custom.patterns),
+                None => HashSet::new(),
+            },
+            ChessPieceKind::King => self.get_king_threats(x, y),
+        })
+    }
+
+    // like get_moves, but without the moves that would leave the king of the moving side in check
+    pub fn get_legal_moves(&self, x: u32, y: u32) -> Option<HashSet<Move>> {
+        let piece = self.get(x, y)?;
+        let mut moves = self.get_moves(x, y)?;
+
+        moves.retain(|_move| {
+            let mut board = self.clone();
+            board._move(x, y, _move.x, _move.y);
+            board.get_king_attackers(piece.colour).is_empty()
+        });
+
+        Some(moves)
+    }
+
+    pub fn has_legal_moves(&self, colour: ChessPieceColour) -> bool {
+        self.iter()
+            .flatten()
+            .filter(|piece| piece.colour == colour)
+            .any(|piece| {
+                self.get_legal_moves(piece.x, piece.y)
+                    .is_some_and(|moves| !moves.is_empty())
+            })
+    }
+
+    // if moving a piece with given colour to x,y is valid, returns that move as a Some(Move)
+    // else returns None
+    fn can_do_move(&self, x: u32, y: u32, colour: &ChessPieceColour) -> Option<Move> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        match self.get(x, y) {
+            Some(other_piece) => {
+                if &other_piece.colour != colour {
+                    Some(Move { x, y, takes: true })
+                } else {
+                    None
+                }
+            }
+            None => Some(Move { x, y, takes: false }),
+        }
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = Option<&ChessPiece>> + '_> {
+        let mut iter: Box<dyn Iterator<Item = Option<&ChessPiece>>> =
+            Box::new(self.board[0].iter().map(|el| el.as_ref()));
+        for row in self.board.iter().skip(1) {
+            iter = Box::new(iter.chain(row.iter().map(|el| el.as_ref())));
+        }
+        iter
+    }
+
+    pub fn find_piece(
+        &self,
+        kind: ChessPieceKind,
+        colour: ChessPieceColour,
+    ) -> Option<&ChessPiece> {
+        self.iter().find_map(|piece| {
+            piece.and_then(|piece| {
+                if kind == piece.kind && colour == piece.colour {
+                    return Some(piece);
+                }
+                None
+            })
+        })
+    }
+
+    // whether the given side has enough pieces left to possibly checkmate
+    pub fn has_mating_material(&self, colour: ChessPieceColour) -> bool {
+        let mut minor_pieces = 0;
+        for piece in self.iter().flatten().filter(|piece| piece.colour == colour) {
+            match piece.kind {
+                ChessPieceKind::King => (),
+                ChessPieceKind::Knight | ChessPieceKind::Bishop => minor_pieces += 1,
+                _ => return true,
+            }
+        }
+
+        minor_pieces > 1
+    }
+
+    pub fn get_king_attackers(&self, colour: ChessPieceColour) -> Vec<&ChessPiece> {
+        let mut attackers = Vec::new();
+
+        if let Some(king) = self.find_piece(ChessPieceKind::King, colour) {
+            for piece in self.iter().filter_map(|piece| {
+                piece.and_then(|piece| {
+                    if piece.colour != king.colour {
+                        if let Some(threats) = self.get_threatened_tiles(piece.x, piece.y) {
+                            if threats.contains(&BoardPos {
+                                x: king.x,
+                                y: king.y,
+                            }) {
+                                return Some(piece);
+                            }
+                        }
+                    }
+                    None
+                })
+            }) {
+                attackers.push(piece);
+            }
+        }
+
+        attackers
+    }
+}
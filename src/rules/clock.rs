@@ -0,0 +1,369 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::rules::pieces::ChessPieceColour;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeBonus {
+    // the bonus is added after every move
+    Fischer,
+    // after every move, the time spent on it is given back, up to the bonus
+    Bronstein,
+    // the clock only starts counting down once the bonus has passed on every move
+    SimpleDelay,
+}
+
+// neither the base time nor the bonus can be longer than a day, which also keeps the clock far
+// away from overflowing
+const MAX_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "TimeControlDefinition")
+)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub bonus: Duration,
+    pub bonus_kind: TimeBonus,
+}
+
+impl TimeControl {
+    pub fn new(base: Duration, bonus: Duration, bonus_kind: TimeBonus) -> Result<Self, String> {
+        let time_control = Self {
+            base,
+            bonus,
+            bonus_kind,
+        };
+        if base.is_zero() || base > MAX_TIME || bonus > MAX_TIME {
+            return Err(format!("invalid time control: {}", time_control));
+        }
+        Ok(time_control)
+    }
+}
+
+// time controls read from settings, saves or the network go through the same checks as parsed
+// ones
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TimeControlDefinition {
+    base: Duration,
+    bonus: Duration,
+    bonus_kind: TimeBonus,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TimeControlDefinition> for TimeControl {
+    type Error = String;
+
+    fn try_from(definition: TimeControlDefinition) -> Result<Self, Self::Error> {
+        Self::new(definition.base, definition.bonus, definition.bonus_kind)
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    // parses time controls written as base minutes and bonus seconds, separated by
+    // `+` for a Fischer increment, `b` for a Bronstein delay or `d` for a simple delay,
+    // e.g. `5+3`, `15b10` or `90d30`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (separator, bonus_kind) = [
+            ('+', TimeBonus::Fischer),
+            ('b', TimeBonus::Bronstein),
+            ('d', TimeBonus::SimpleDelay),
+        ]
+        .into_iter()
+        .find(|(separator, _)| s.contains(*separator))
+        .ok_or(format!("invalid time control: {}", s))?;
+        let (base, bonus) = s.split_once(separator).unwrap();
+
+        let base: f32 = base
+            .parse()
+            .map_err(|_| format!("invalid base time: {}", base))?;
+        let bonus: f32 = bonus
+            .parse()
+            .map_err(|_| format!("invalid time bonus: {}", bonus))?;
+        let invalid = || format!("invalid time control: {}", s);
+        let base = Duration::try_from_secs_f32(base * 60.).map_err(|_| invalid())?;
+        let bonus = Duration::try_from_secs_f32(bonus).map_err(|_| invalid())?;
+        Self::new(base, bonus, bonus_kind).map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = match self.bonus_kind {
+            TimeBonus::Fischer => '+',
+            TimeBonus::Bronstein => 'b',
+            TimeBonus::SimpleDelay => 'd',
+        };
+        write!(
+            f,
+            "{}{}{}",
+            self.base.as_secs_f32() / 60.,
+            separator,
+            self.bonus.as_secs_f32()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChessClock {
+    // None if the game isn't timed
+    pub time_control: Option<TimeControl>,
+    white: Duration,
+    black: Duration,
+    // time spent by the side to move on the current move
+    turn_elapsed: Duration,
+    // the clock starts running once white has made the first move
+    running: bool,
+}
+
+impl ChessClock {
+    pub fn new(time_control: Option<TimeControl>) -> Self {
+        let base = time_control.map_or(Duration::ZERO, |time_control| time_control.base);
+        Self {
+            time_control,
+            white: base,
+            black: base,
+            turn_elapsed: Duration::ZERO,
+            running: false,
+        }
+    }
+
+    pub fn remaining(&self, colour: ChessPieceColour) -> Duration {
+        match colour {
+            ChessPieceColour::White => self.white,
+            ChessPieceColour::Black => self.black,
+        }
+    }
+
+    fn remaining_mut(&mut self, colour: ChessPieceColour) -> &mut Duration {
+        match colour {
+            ChessPieceColour::White => &mut self.white,
+            ChessPieceColour::Black => &mut self.black,
+        }
+    }
+
+    // runs the clock of the given side out, when the host of a network game says it has
+    pub fn flag(&mut self, colour: ChessPieceColour) {
+        if self.time_control.is_some() {
+            *self.remaining_mut(colour) = Duration::ZERO;
+        }
+    }
+
+    pub fn is_flagged(&self, colour: ChessPieceColour) -> bool {
+        self.time_control.is_some() && self.remaining(colour).is_zero()
+    }
+
+    // counts down the clock of the side to move
+    pub fn tick(&mut self, delta: Duration, colour: ChessPieceColour) {
+        let time_control = match self.time_control {
+            Some(time_control) if self.running => time_control,
+            _ => return,
+        };
+
+        let spent = match time_control.bonus_kind {
+            TimeBonus::SimpleDelay => {
+                let before = self.turn_elapsed.saturating_sub(time_control.bonus);
+                self.turn_elapsed += delta;
+                self.turn_elapsed.saturating_sub(time_control.bonus) - before
+            }
+            TimeBonus::Fischer | TimeBonus::Bronstein => {
+                self.turn_elapsed += delta;
+                delta
+            }
+        };
+
+        let remaining = self.remaining_mut(colour);
+        *remaining = remaining.saturating_sub(spent);
+    }
+
+    // the clock of a game whose moves took the given times, white having made the first one.
+    // `thinking` is how long the side to move has been thinking since
+    pub fn replay(
+        time_control: Option<TimeControl>,
+        move_times: &[Duration],
+        thinking: Duration,
+    ) -> Self {
+        let mut clock = Self::new(time_control);
+        let mut colour = ChessPieceColour::White;
+        for time in move_times {
+            clock.tick(*time, colour);
+            clock.press(colour);
+            colour = !colour;
+        }
+        clock.tick(thinking, colour);
+        clock
+    }
+
+    // switches the clock over after the given side has made a move
+    pub fn press(&mut self, colour: ChessPieceColour) {
+        let time_control = match self.time_control {
+            Some(time_control) => time_control,
+            None => return,
+        };
+
+        let bonus = match time_control.bonus_kind {
+            TimeBonus::Fischer => time_control.bonus,
+            TimeBonus::Bronstein => self.turn_elapsed.min(time_control.bonus),
+            TimeBonus::SimpleDelay => Duration::ZERO,
+        };
+        *self.remaining_mut(colour) += bonus;
+
+        self.turn_elapsed = Duration::ZERO;
+        self.running = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::rules::clock::{ChessClock, TimeBonus, TimeControl};
+    use crate::rules::pieces::ChessPieceColour;
+
+    #[test]
+    fn parse_time_control() {
+        assert_eq!(
+            Ok(TimeControl {
+                base: Duration::from_secs(300),
+                bonus: Duration::from_secs(3),
+                bonus_kind: TimeBonus::Fischer,
+            }),
+            "5+3".parse()
+        );
+        assert_eq!(
+            Ok(TimeControl {
+                base: Duration::from_secs(30),
+                bonus: Duration::from_secs(2),
+                bonus_kind: TimeBonus::SimpleDelay,
+            }),
+            "0.5d2".parse()
+        );
+        assert_eq!(
+            Ok(TimeBonus::Bronstein),
+            "15b10".parse::<TimeControl>().map(|tc| tc.bonus_kind)
+        );
+        assert!("5".parse::<TimeControl>().is_err());
+        assert!("0+3".parse::<TimeControl>().is_err());
+        assert!("a+b".parse::<TimeControl>().is_err());
+        for invalid in [
+            "-1+0", "5+-1", "NaN+0", "5+inf", "1e40+0", "5+1e40", "1441+0",
+        ] {
+            assert!(invalid.parse::<TimeControl>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialise_time_control() {
+        let time_control: TimeControl = "5+3".parse().unwrap();
+        assert_eq!(
+            Ok(time_control),
+            ron::from_str(&ron::to_string(&time_control).unwrap())
+        );
+        for invalid in [
+            "(base: (secs: 0, nanos: 0), bonus: (secs: 3, nanos: 0), bonus_kind: Fischer)",
+            "(base: (secs: 86401, nanos: 0), bonus: (secs: 3, nanos: 0), bonus_kind: Fischer)",
+            "(base: (secs: 300, nanos: 0), bonus: (secs: 86401, nanos: 0), bonus_kind: Bronstein)",
+            "(base: (secs: -300, nanos: 0), bonus: (secs: 3, nanos: 0), bonus_kind: Fischer)",
+            "(base: (secs: 300, nanos: 0), bonus: (secs: -3, nanos: 0), bonus_kind: SimpleDelay)",
+        ] {
+            assert!(
+                ron::from_str::<TimeControl>(invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn display_time_control() {
+        for time_control in ["5+3", "0.5d2", "15b10", "90d30"] {
+            assert_eq!(
+                time_control,
+                time_control.parse::<TimeControl>().unwrap().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn fischer() {
+        let mut clock = ChessClock::new(Some("1+2".parse().unwrap()));
+        // the clock doesn't run before the first move
+        clock.tick(Duration::from_secs(5), ChessPieceColour::White);
+        assert_eq!(
+            Duration::from_secs(60),
+            clock.remaining(ChessPieceColour::White)
+        );
+
+        clock.press(ChessPieceColour::White);
+        clock.tick(Duration::from_secs(5), ChessPieceColour::Black);
+        clock.press(ChessPieceColour::Black);
+        assert_eq!(
+            Duration::from_secs(62),
+            clock.remaining(ChessPieceColour::White)
+        );
+        assert_eq!(
+            Duration::from_secs(57),
+            clock.remaining(ChessPieceColour::Black)
+        );
+    }
+
+    #[test]
+    fn bronstein() {
+        let mut clock = ChessClock::new(Some("1b3".parse().unwrap()));
+        clock.press(ChessPieceColour::White);
+        clock.tick(Duration::from_secs(2), ChessPieceColour::Black);
+        clock.press(ChessPieceColour::Black);
+        assert_eq!(
+            Duration::from_secs(60),
+            clock.remaining(ChessPieceColour::Black)
+        );
+
+        clock.tick(Duration::from_secs(5), ChessPieceColour::White);
+        clock.press(ChessPieceColour::White);
+        assert_eq!(
+            Duration::from_secs(58),
+            clock.remaining(ChessPieceColour::White)
+        );
+    }
+
+    #[test]
+    fn simple_delay() {
+        let mut clock = ChessClock::new(Some("1d3".parse().unwrap()));
+        clock.press(ChessPieceColour::White);
+        clock.tick(Duration::from_secs(2), ChessPieceColour::Black);
+        clock.tick(Duration::from_secs(2), ChessPieceColour::Black);
+        assert_eq!(
+            Duration::from_secs(59),
+            clock.remaining(ChessPieceColour::Black)
+        );
+
+        clock.tick(Duration::from_secs(100), ChessPieceColour::Black);
+        assert!(clock.is_flagged(ChessPieceColour::Black));
+        assert!(!clock.is_flagged(ChessPieceColour::White));
+    }
+
+    #[test]
+    fn replay() {
+        let times = [5, 10, 3].map(Duration::from_secs);
+        let clock =
+            ChessClock::replay(Some("1+2".parse().unwrap()), &times, Duration::from_secs(4));
+        // the time spent on white's first move doesn't count
+        assert_eq!(
+            Duration::from_secs(61),
+            clock.remaining(ChessPieceColour::White)
+        );
+        assert_eq!(
+            Duration::from_secs(48),
+            clock.remaining(ChessPieceColour::Black)
+        );
+    }
+}
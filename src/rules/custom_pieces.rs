@@ -0,0 +1,229 @@
+#[cfg(feature = "serde")]
+use std::fs;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+#[cfg(feature = "serde")]
+use crate::rules::betza;
+use crate::rules::betza::MovePattern;
+use crate::rules::pieces::{ChessPieceColour, ChessPieceKind};
+
+pub const PIECE_SET_PATH: &str = "assets/pieces.ron";
+
+// a custom piece as described in the piece set file, which is only read with the `serde` feature
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct PieceDefinition {
+    name: String,
+    betza: String,
+    // indices into the chess piece texture atlas
+    white_sprite: u8,
+    black_sprite: u8,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct PieceSetDefinition {
+    #[serde(default)]
+    pieces: Vec<PieceDefinition>,
+    // names of the pieces on the first rank, from left to right
+    #[serde(default)]
+    back_rank: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CustomPiece {
+    pub name: String,
+    // the definition the patterns were parsed from
+    pub betza: String,
+    pub patterns: Vec<MovePattern>,
+    pub white_sprite: u8,
+    pub black_sprite: u8,
+}
+
+impl CustomPiece {
+    pub fn sprite(&self, colour: ChessPieceColour) -> u8 {
+        match colour {
+            ChessPieceColour::White => self.white_sprite,
+            ChessPieceColour::Black => self.black_sprite,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PieceSet {
+    pub pieces: Vec<CustomPiece>,
+    pub back_rank: Option<Vec<ChessPieceKind>>,
+}
+
+#[cfg(feature = "serde")]
+impl PieceSet {
+    // `sprite_count` is the number of sprites in the piece texture the sprite indices refer to
+    pub fn load(path: impl AsRef<Path>, sprite_count: usize) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        Self::parse(&contents, sprite_count).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(contents: &str, sprite_count: usize) -> Result<Self, String> {
+        let definition: PieceSetDefinition =
+            ron::from_str(contents).map_err(|err| err.to_string())?;
+
+        let mut pieces = Vec::new();
+        for piece in definition.pieces {
+            match kind_from_name(&piece.name, &pieces) {
+                Some(ChessPieceKind::Custom(_)) => {
+                    return Err(format!("piece {} is defined more than once", piece.name))
+                }
+                Some(_) => return Err(format!("{} is the name of a standard piece", piece.name)),
+                None => {}
+            }
+            // the piece is referred to by its index, which has to fit in a `ChessPieceKind::Custom`
+            if u8::try_from(pieces.len()).is_err() {
+                return Err(String::from("too many custom pieces"));
+            }
+            for sprite in [piece.white_sprite, piece.black_sprite] {
+                if usize::from(sprite) >= sprite_count {
+                    return Err(format!(
+                        "{}: sprite {} isn't in the piece texture",
+                        piece.name, sprite
+                    ));
+                }
+            }
+            let patterns =
+                betza::parse(&piece.betza).map_err(|err| format!("{}: {}", piece.name, err))?;
+            pieces.push(CustomPiece {
+                name: piece.name,
+                betza: piece.betza,
+                patterns,
+                white_sprite: piece.white_sprite,
+                black_sprite: piece.black_sprite,
+            });
+        }
+
+        let back_rank = match definition.back_rank {
+            Some(names) => Some(
+                names
+                    .iter()
+                    .map(|name| {
+                        kind_from_name(name, &pieces).ok_or(format!("unknown piece: {}", name))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+
+        Ok(Self { pieces, back_rank })
+    }
+}
+
+// resolves a piece name to either one of the standard pieces or one of the given custom pieces
+#[cfg(feature = "serde")]
+fn kind_from_name(name: &str, pieces: &[CustomPiece]) -> Option<ChessPieceKind> {
+    match name.to_lowercase().as_str() {
+        "pawn" => Some(ChessPieceKind::Pawn),
+        "knight" => Some(ChessPieceKind::Knight),
+        "rook" => Some(ChessPieceKind::Rook),
+        "bishop" => Some(ChessPieceKind::Bishop),
+        "queen" => Some(ChessPieceKind::Queen),
+        "king" => Some(ChessPieceKind::King),
+        "archbishop" => Some(ChessPieceKind::Archbishop),
+        "chancellor" => Some(ChessPieceKind::Chancellor),
+        _ => pieces
+            .iter()
+            .position(|piece| piece.name.eq_ignore_ascii_case(name))
+            .map(|id| ChessPieceKind::Custom(id as u8)),
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::rules::custom_pieces::PieceSet;
+    use crate::rules::pieces::ChessPieceKind;
+
+    // the default piece texture is six sprites wide and three high
+    const SPRITES: usize = 18;
+
+    #[test]
+    fn parse_piece_set() {
+        let piece_set = PieceSet::parse(
+            r#"(
+                pieces: [
+                    (name: "Amazon", betza: "QN", white_sprite: 16, black_sprite: 17),
+                    (name: "Nightrider", betza: "NN", white_sprite: 3, black_sprite: 9),
+                ],
+                back_rank: Some(["Rook", "Nightrider", "Bishop", "Amazon", "King"]),
+            )"#,
+            SPRITES,
+        )
+        .unwrap();
+
+        assert_eq!(2, piece_set.pieces.len());
+        assert_eq!(3, piece_set.pieces[0].patterns.len());
+        assert_eq!(
+            Some(vec![
+                ChessPieceKind::Rook,
+                ChessPieceKind::Custom(1),
+                ChessPieceKind::Bishop,
+                ChessPieceKind::Custom(0),
+                ChessPieceKind::King,
+            ]),
+            piece_set.back_rank
+        );
+    }
+
+    #[test]
+    fn invalid_piece_set() {
+        assert!(PieceSet::parse(
+            r#"(pieces: [(name: "Bad", betza: "X", white_sprite: 0, black_sprite: 0)])"#,
+            SPRITES
+        )
+        .is_err());
+        assert_eq!(
+            Err(String::from("Imp: sprite 18 isn't in the piece texture")),
+            PieceSet::parse(
+                r#"(pieces: [(name: "Imp", betza: "W", white_sprite: 0, black_sprite: 18)])"#,
+                SPRITES
+            )
+            .map(|_| ())
+        );
+        assert_eq!(
+            Err(String::from("Queen is the name of a standard piece")),
+            PieceSet::parse(
+                r#"(pieces: [(name: "Queen", betza: "N", white_sprite: 0, black_sprite: 0)])"#,
+                SPRITES
+            )
+            .map(|_| ())
+        );
+        assert_eq!(
+            Err(String::from("piece imp is defined more than once")),
+            PieceSet::parse(
+                r#"(pieces: [
+                    (name: "Imp", betza: "W", white_sprite: 0, black_sprite: 0),
+                    (name: "imp", betza: "F", white_sprite: 0, black_sprite: 0),
+                ])"#,
+                SPRITES
+            )
+            .map(|_| ())
+        );
+        let too_many: Vec<String> = (0..=256)
+            .map(|i| {
+                format!(
+                    "(name: \"P{}\", betza: \"W\", white_sprite: 0, black_sprite: 0)",
+                    i
+                )
+            })
+            .collect();
+        assert!(PieceSet::parse(
+            &format!("(pieces: [{}])", too_many[..256].join(",")),
+            SPRITES
+        )
+        .is_ok());
+        assert!(PieceSet::parse(&format!("(pieces: [{}])", too_many.join(",")), SPRITES).is_err());
+        assert!(PieceSet::parse(r#"(back_rank: Some(["Unicorn"]))"#, SPRITES).is_err());
+    }
+}
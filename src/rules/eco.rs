@@ -0,0 +1,150 @@
+// opening names by their ECO (Encyclopaedia of Chess Openings) code, e.g. `C50 Italian Game`.
+// openings are told apart by their positions rather than their moves, so that a game reaching
+// one in a different move order still gets its name. only standard chess has named openings
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::rules::board::{Board, Variant};
+
+// bundled with the game rather than loaded, it's the same for every game and doesn't change
+const ECO_TABLE: &str = include_str!("eco.tsv");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    pub eco: String,
+    pub name: String,
+}
+
+impl fmt::Display for Opening {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.eco, self.name)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OpeningTable {
+    // by the Polyglot key of the position
+    openings: HashMap<u64, Opening>,
+}
+
+impl OpeningTable {
+    // one opening per line: the code, the name and the moves in SAN separated by tabs, e.g.
+    // `C50	Italian Game	1. e4 e5 2. Nf3 Nc6 3. Bc4`. lines starting with `#` are comments
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut openings = HashMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |err: String| format!("line {}: {}", i + 1, err);
+            let mut fields = line.split('\t');
+            let (eco, name, moves) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(eco), Some(name), Some(moves)) => (eco, name, moves),
+                _ => return Err(error(String::from("expected a code, a name and moves"))),
+            };
+
+            let mut board = Board::default();
+            // move numbers are skipped
+            for san in moves
+                .split_whitespace()
+                .filter(|token| !token.ends_with('.'))
+            {
+                let _move = board.parse_move(san, board.to_move).map_err(error)?;
+                board.play(&_move);
+            }
+            let key = board
+                .polyglot_key()
+                .expect("standard chess positions always have a key");
+            let opening = Opening {
+                eco: eco.to_string(),
+                name: name.to_string(),
+            };
+            if let Some(named) = openings.insert(key, opening) {
+                return Err(error(format!("the position is already named {}", named)));
+            }
+        }
+        Ok(Self { openings })
+    }
+
+    // the table bundled with the game, it's only parsed the first time it's needed
+    pub fn bundled() -> &'static Self {
+        static TABLE: OnceLock<OpeningTable> = OnceLock::new();
+        TABLE.get_or_init(|| Self::parse(ECO_TABLE).expect("the bundled ECO table is valid"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.openings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.openings.is_empty()
+    }
+
+    // the opening of the last named position of a game, given its positions from the start.
+    // the name stays once the game has left the table
+    pub fn recognise<'a>(
+        &self,
+        positions: impl IntoIterator<Item = &'a Board>,
+    ) -> Option<&Opening> {
+        positions
+            .into_iter()
+            .filter(|board| board.variant == Variant::Standard)
+            .filter_map(|board| self.openings.get(&board.polyglot_key()?))
+            .last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::board::{Board, Variant};
+    use crate::rules::eco::OpeningTable;
+
+    fn positions(moves: &[&str]) -> Vec<Board> {
+        let mut positions = vec![Board::default()];
+        for _move in moves {
+            let mut board = positions.last().unwrap().clone();
+            let parsed = board.parse_move(_move, board.to_move).unwrap();
+            board.play(&parsed);
+            positions.push(board);
+        }
+        positions
+    }
+
+    #[test]
+    fn openings_are_recognised() {
+        let table = OpeningTable::bundled();
+        assert!(table.len() > 200);
+        let name = |moves: &[&str]| table.recognise(&positions(moves)).map(|o| o.to_string());
+
+        assert_eq!(None, name(&[]));
+        assert_eq!(
+            Some(String::from("C50 Italian Game")),
+            name(&["e4", "e5", "Nf3", "Nc6", "Bc4"])
+        );
+        // out of the table, but still in the Italian Game
+        assert_eq!(
+            Some(String::from("C50 Italian Game")),
+            name(&["e4", "e5", "Nf3", "Nc6", "Bc4", "h6", "h3"])
+        );
+        // the Queen's Gambit Declined by way of the English Opening
+        assert_eq!(
+            Some(String::from("D31 Queen's Gambit Declined")),
+            name(&["c4", "e6", "Nc3", "d5", "d4"])
+        );
+        assert_eq!(
+            None,
+            table.recognise(&[Board::from_variant(Variant::Capablanca)])
+        );
+    }
+
+    #[test]
+    fn tables_are_checked() {
+        assert!(OpeningTable::parse("# nothing yet\n\n").unwrap().is_empty());
+        assert!(OpeningTable::parse("C50\tItalian Game").is_err());
+        assert!(OpeningTable::parse("C50\tItalian Game\t1. e4 e5 2. Bc5").is_err());
+        assert!(OpeningTable::parse("B00\tKing's Pawn\t1. e4\nB00\tAgain\t1. e4").is_err());
+    }
+}
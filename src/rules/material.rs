@@ -0,0 +1,105 @@
+use crate::rules::board::Board;
+use crate::rules::pieces::{ChessPieceColour, ChessPieceKind};
+
+// the usual rough piece values, in pawns. custom pieces aren't counted, as there's no telling
+// what they're worth
+pub fn piece_value(kind: ChessPieceKind) -> u32 {
+    match kind {
+        ChessPieceKind::Pawn => 1,
+        ChessPieceKind::Knight | ChessPieceKind::Bishop => 3,
+        ChessPieceKind::Rook => 5,
+        ChessPieceKind::Archbishop => 7,
+        ChessPieceKind::Chancellor => 8,
+        ChessPieceKind::Queen => 9,
+        ChessPieceKind::King | ChessPieceKind::Custom(_) => 0,
+    }
+}
+
+impl Board {
+    pub fn material(&self, colour: ChessPieceColour) -> u32 {
+        self.iter()
+            .flatten()
+            .filter(|piece| piece.colour == colour)
+            .map(|piece| piece.kind)
+            .map(piece_value)
+            .sum()
+    }
+
+    // how many points the given side is ahead by, negative if it's behind
+    pub fn material_balance(&self, colour: ChessPieceColour) -> i32 {
+        self.material(colour) as i32 - self.material(!colour) as i32
+    }
+
+    // the pieces of the given side that were on the starting board but aren't on this one
+    // anymore, least valuable first with custom pieces at the end
+    pub fn captured_pieces(&self, start: &Board, colour: ChessPieceColour) -> Vec<ChessPieceKind> {
+        let mut remaining: Vec<ChessPieceKind> = self
+            .iter()
+            .flatten()
+            .filter(|piece| piece.colour == colour)
+            .map(|piece| piece.kind)
+            .collect();
+
+        let mut captured = Vec::new();
+        for piece in start
+            .iter()
+            .flatten()
+            .filter(|piece| piece.colour == colour)
+        {
+            match remaining.iter().position(|kind| *kind == piece.kind) {
+                Some(i) => {
+                    remaining.swap_remove(i);
+                }
+                None => captured.push(piece.kind),
+            }
+        }
+
+        captured.sort_by_key(|kind| {
+            (
+                matches!(kind, ChessPieceKind::Custom(_)),
+                piece_value(*kind),
+            )
+        });
+        captured
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::board::{Board, Variant};
+    use crate::rules::pieces::{ChessPieceColour, ChessPieceKind};
+
+    #[test]
+    fn captured_pieces() {
+        let start = Board::from_variant(Variant::Standard);
+        let mut board = start.clone();
+        assert!(board
+            .captured_pieces(&start, ChessPieceColour::Black)
+            .is_empty());
+        assert_eq!(0, board.material_balance(ChessPieceColour::White));
+
+        // 1. e4 d5 2. exd5 Qxd5 3. Nc3 Qxa2 4. Rxa2
+        for (from_x, from_y, to_x, to_y) in [
+            (4, 1, 4, 3),
+            (3, 6, 3, 4),
+            (4, 3, 3, 4),
+            (3, 7, 3, 4),
+            (1, 0, 2, 2),
+            (3, 4, 0, 1),
+            (0, 0, 0, 1),
+        ] {
+            board._move(from_x, from_y, to_x, to_y);
+        }
+
+        assert_eq!(
+            vec![ChessPieceKind::Pawn, ChessPieceKind::Queen],
+            board.captured_pieces(&start, ChessPieceColour::Black)
+        );
+        assert_eq!(
+            vec![ChessPieceKind::Pawn, ChessPieceKind::Pawn],
+            board.captured_pieces(&start, ChessPieceColour::White)
+        );
+        assert_eq!(8, board.material_balance(ChessPieceColour::White));
+        assert_eq!(-8, board.material_balance(ChessPieceColour::Black));
+    }
+}
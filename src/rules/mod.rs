@@ -0,0 +1,68 @@
+// the rules of the game, without anything bevy related, so that they can also be used
+// on their own (e.g. on a server) by building without the `bevy` feature
+
+pub mod annotations;
+pub mod betza;
+pub mod board;
+pub mod clock;
+pub mod custom_pieces;
+pub mod eco;
+pub mod material;
+pub mod notation;
+pub mod pgn;
+pub mod pieces;
+pub mod polyglot;
+#[cfg(feature = "serde")]
+pub mod serialisation;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub x: u32,
+    pub y: u32,
+    pub takes: bool,
+}
+
+impl PartialEq<BoardPos> for Move {
+    fn eq(&self, other: &BoardPos) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct BoardPos {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl PartialEq<Move> for BoardPos {
+    fn eq(&self, other: &Move) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct MoveFromTo {
+    from: BoardPos,
+    to: BoardPos,
+}
+
+impl MoveFromTo {
+    pub fn new(from_x: u32, from_y: u32, to_x: u32, to_y: u32) -> Self {
+        Self {
+            from: BoardPos {
+                x: from_x,
+                y: from_y,
+            },
+            to: BoardPos { x: to_x, y: to_y },
+        }
+    }
+
+    pub fn from(&self) -> BoardPos {
+        self.from
+    }
+
+    pub fn to(&self) -> BoardPos {
+        self.to
+    }
+}
@@ -0,0 +1,381 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::rules::board::Board;
+use crate::rules::pieces::{ChessPieceColour, ChessPieceKind};
+use crate::rules::{BoardPos, MoveFromTo};
+
+// files are lettered from a, so boards can be at most 26 tiles wide
+pub fn file_name(x: u32) -> char {
+    match u8::try_from(x) {
+        Ok(x) if x < 26 => char::from(b'a' + x),
+        _ => panic!("tried to get file name for x: {}", x),
+    }
+}
+
+// ranks are numbered from 1, and can go past 9 on tall boards
+pub fn rank_name(y: u32) -> String {
+    (y + 1).to_string()
+}
+
+impl fmt::Display for BoardPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", file_name(self.x), rank_name(self.y))
+    }
+}
+
+impl FromStr for BoardPos {
+    type Err = String;
+
+    // parses square names such as `e4` or `b10`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let x = match chars.next() {
+            Some(file @ 'a'..='z') => file as u32 - 'a' as u32,
+            _ => return Err(format!("invalid square: {}", s)),
+        };
+        let y = match chars.as_str().parse::<u32>() {
+            Ok(rank) if rank > 0 => rank - 1,
+            _ => return Err(format!("invalid square: {}", s)),
+        };
+
+        Ok(Self { x, y })
+    }
+}
+
+// moves are written in long algebraic notation as used by UCI, e.g. `e2e4`
+impl fmt::Display for MoveFromTo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)
+    }
+}
+
+impl FromStr for MoveFromTo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // the destination starts at the second letter, as the rank can have more than one digit
+        let split = s
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| c.is_ascii_lowercase())
+            .map(|(i, _)| i)
+            .ok_or(format!("invalid move: {}", s))?;
+        let (from, to) = s.split_at(split);
+
+        Ok(Self {
+            from: from.parse()?,
+            to: to.parse()?,
+        })
+    }
+}
+
+// the letters pieces go by in standard algebraic notation, pawns and custom pieces don't have one
+pub fn piece_letter(kind: ChessPieceKind) -> Option<char> {
+    match kind {
+        ChessPieceKind::Knight => Some('N'),
+        ChessPieceKind::Rook => Some('R'),
+        ChessPieceKind::Bishop => Some('B'),
+        ChessPieceKind::Queen => Some('Q'),
+        ChessPieceKind::King => Some('K'),
+        ChessPieceKind::Archbishop => Some('A'),
+        ChessPieceKind::Chancellor => Some('C'),
+        ChessPieceKind::Pawn | ChessPieceKind::Custom(_) => None,
+    }
+}
+
+impl Board {
+    // finds the legal move of the given side written either in UCI notation (`e2e4`) or in
+    // standard algebraic notation (`Nf3`, `exd5`, `R1a3`, `O-O`). custom pieces don't have
+    // a letter, so they can only be moved with UCI notation
+    pub fn parse_move(&self, s: &str, colour: ChessPieceColour) -> Result<MoveFromTo, String> {
+        let s = s.trim();
+        if let Ok(_move) = s.parse::<MoveFromTo>() {
+            return match self.is_legal_move(&_move, colour) {
+                true => Ok(_move),
+                false => Err(format!("illegal move: {}", s)),
+            };
+        }
+
+        let san = s.trim_end_matches(['+', '#', '!', '?']);
+        let castling = match san {
+            "O-O" | "0-0" => Some(1),
+            "O-O-O" | "0-0-0" => Some(-1),
+            _ => None,
+        };
+        if let Some(x_offset) = castling {
+            let king = self
+                .find_piece(ChessPieceKind::King, colour)
+                .ok_or(format!("illegal move: {}", s))?;
+            let (to_x, _) = self.variant.castling_files(x_offset);
+            let _move = MoveFromTo::new(king.x, king.y, to_x, king.y);
+            return match self.is_legal_move(&_move, colour) {
+                true => Ok(_move),
+                false => Err(format!("illegal move: {}", s)),
+            };
+        }
+
+        let (kind, rest) = match san.chars().next() {
+            Some(letter) if letter.is_ascii_uppercase() => {
+                let kind = [
+                    ChessPieceKind::Knight,
+                    ChessPieceKind::Rook,
+                    ChessPieceKind::Bishop,
+                    ChessPieceKind::Queen,
+                    ChessPieceKind::King,
+                    ChessPieceKind::Archbishop,
+                    ChessPieceKind::Chancellor,
+                ]
+                .into_iter()
+                .find(|kind| piece_letter(*kind) == Some(letter))
+                .ok_or(format!("unknown piece: {}", letter))?;
+                (kind, &san[1..])
+            }
+            _ => (ChessPieceKind::Pawn, san),
+        };
+
+        // the destination is at the end, before it there can be the file and/or rank the piece
+        // comes from and an `x` for captures
+        let to_start = rest
+            .rfind(|c: char| c.is_ascii_lowercase() && c != 'x')
+            .ok_or(format!("invalid move: {}", s))?;
+        let to: BoardPos = rest[to_start..]
+            .parse()
+            .map_err(|_| format!("invalid move: {}", s))?;
+        let from_hint = rest[..to_start].trim_end_matches('x');
+        let mut hint_chars = from_hint.chars();
+        let from_file = match from_hint.chars().next() {
+            Some(file @ 'a'..='z') => {
+                hint_chars.next();
+                Some(file as u32 - 'a' as u32)
+            }
+            _ => None,
+        };
+        let from_rank = match hint_chars.as_str() {
+            "" => None,
+            rank => match rank.parse::<u32>() {
+                Ok(rank) if rank > 0 => Some(rank - 1),
+                _ => return Err(format!("invalid move: {}", s)),
+            },
+        };
+
+        let mut candidates = self
+            .iter()
+            .flatten()
+            .filter(|piece| piece.colour == colour && piece.kind == kind)
+            .filter(|piece| from_file.is_none_or(|x| piece.x == x))
+            .filter(|piece| from_rank.is_none_or(|y| piece.y == y))
+            .map(|piece| MoveFromTo::new(piece.x, piece.y, to.x, to.y))
+            .filter(|_move| self.is_legal_move(_move, colour));
+        match (candidates.next(), candidates.next()) {
+            (Some(_move), None) => Ok(_move),
+            (None, _) => Err(format!("illegal move: {}", s)),
+            (Some(_), Some(_)) => Err(format!("ambiguous move: {}", s)),
+        }
+    }
+
+    // writes a legal move in standard algebraic notation, the other way around from `parse_move`.
+    // moves of custom pieces are written in UCI notation, as they don't have a letter
+    pub fn san(&self, _move: &MoveFromTo) -> String {
+        let (from, to) = (_move.from(), _move.to());
+        let piece = match self.get(from.x, from.y) {
+            Some(piece) => *piece,
+            None => return _move.to_string(),
+        };
+
+        let mut san = match piece.kind {
+            ChessPieceKind::King if from.y == to.y && from.x.abs_diff(to.x) >= 2 => {
+                match to.x > from.x {
+                    true => String::from("O-O"),
+                    false => String::from("O-O-O"),
+                }
+            }
+            ChessPieceKind::Custom(_) => _move.to_string(),
+            // pawns only change files when they take, en passant included
+            ChessPieceKind::Pawn => match from.x != to.x {
+                true => format!("{}x{}", file_name(from.x), to),
+                false => to.to_string(),
+            },
+            kind => {
+                // the other pieces of the same kind that could go there as well
+                let others: Vec<BoardPos> = self
+                    .iter()
+                    .flatten()
+                    .filter(|other| other.colour == piece.colour && other.kind == kind)
+                    .map(|other| BoardPos {
+                        x: other.x,
+                        y: other.y,
+                    })
+                    .filter(|other| *other != from)
+                    .filter(|other| {
+                        self.is_legal_move(
+                            &MoveFromTo::new(other.x, other.y, to.x, to.y),
+                            piece.colour,
+                        )
+                    })
+                    .collect();
+                let hint = if others.is_empty() {
+                    String::new()
+                } else if others.iter().all(|other| other.x != from.x) {
+                    file_name(from.x).to_string()
+                } else if others.iter().all(|other| other.y != from.y) {
+                    rank_name(from.y)
+                } else {
+                    from.to_string()
+                };
+                let takes = match self.get(to.x, to.y) {
+                    Some(_) => "x",
+                    None => "",
+                };
+                format!(
+                    "{}{}{}{}",
+                    piece_letter(kind).map(String::from).unwrap_or_default(),
+                    hint,
+                    takes,
+                    to
+                )
+            }
+        };
+
+        let mut after = self.clone();
+        after.play(_move);
+        if after.check.is_some() {
+            san.push(match after.has_legal_moves(!piece.colour) {
+                true => '+',
+                false => '#',
+            });
+        }
+        san
+    }
+
+    fn is_legal_move(&self, _move: &MoveFromTo, colour: ChessPieceColour) -> bool {
+        let (from, to) = (_move.from(), _move.to());
+        self.get(from.x, from.y)
+            .is_some_and(|piece| piece.colour == colour)
+            && self
+                .get_legal_moves(from.x, from.y)
+                .is_some_and(|moves| moves.iter().any(|other| *other == to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::board::{Board, Variant};
+    use crate::rules::pieces::{ChessPieceColour, ChessPieceKind};
+    use crate::rules::{BoardPos, MoveFromTo};
+
+    #[test]
+    fn square_names() {
+        assert_eq!("a1", BoardPos { x: 0, y: 0 }.to_string());
+        assert_eq!("j10", BoardPos { x: 9, y: 9 }.to_string());
+        assert_eq!(Ok(BoardPos { x: 4, y: 3 }), "e4".parse());
+        assert_eq!(Ok(BoardPos { x: 1, y: 9 }), "b10".parse());
+        assert!("e0".parse::<BoardPos>().is_err());
+        assert!("4e".parse::<BoardPos>().is_err());
+        assert!("e".parse::<BoardPos>().is_err());
+    }
+
+    #[test]
+    fn uci_moves() {
+        assert_eq!("e2e4", MoveFromTo::new(4, 1, 4, 3).to_string());
+        assert_eq!(Ok(MoveFromTo::new(4, 1, 4, 3)), "e2e4".parse());
+        assert_eq!(Ok(MoveFromTo::new(0, 9, 1, 7)), "a10b8".parse());
+        assert!("e2".parse::<MoveFromTo>().is_err());
+        assert!("e2e".parse::<MoveFromTo>().is_err());
+    }
+
+    #[test]
+    fn parse_moves() {
+        let white = ChessPieceColour::White;
+        let black = ChessPieceColour::Black;
+        let board = Board::from_variant(Variant::Standard);
+        assert_eq!(
+            Ok(MoveFromTo::new(4, 1, 4, 3)),
+            board.parse_move("e4", white)
+        );
+        assert_eq!(
+            Ok(MoveFromTo::new(4, 1, 4, 3)),
+            board.parse_move("e2e4", white)
+        );
+        assert_eq!(
+            Ok(MoveFromTo::new(6, 0, 5, 2)),
+            board.parse_move("Nf3", white)
+        );
+        assert_eq!(
+            Ok(MoveFromTo::new(6, 7, 5, 5)),
+            board.parse_move("Nf6", black)
+        );
+        assert!(board.parse_move("e5", white).is_err());
+        assert!(board.parse_move("Ke2", white).is_err());
+        assert!(board.parse_move("e2e4", black).is_err());
+        assert!(board.parse_move("Zf3", white).is_err());
+        assert!(board.parse_move("hello", white).is_err());
+
+        // two knights can go to d2 once the pieces in the way are gone
+        let mut board = Board::with_variant(Variant::Standard);
+        board.set(4, 0, white, ChessPieceKind::King);
+        board.set(4, 7, black, ChessPieceKind::King);
+        board.set(1, 0, white, ChessPieceKind::Knight);
+        board.set(5, 2, white, ChessPieceKind::Knight);
+        board.set(7, 0, white, ChessPieceKind::Rook);
+        board.set(3, 3, black, ChessPieceKind::Pawn);
+        board.set(4, 2, white, ChessPieceKind::Pawn);
+        assert!(board
+            .parse_move("Nd2", white)
+            .unwrap_err()
+            .starts_with("ambiguous"));
+        assert_eq!(
+            Ok(MoveFromTo::new(1, 0, 3, 1)),
+            board.parse_move("Nbd2", white)
+        );
+        assert_eq!(
+            Ok(MoveFromTo::new(5, 2, 3, 1)),
+            board.parse_move("N3d2", white)
+        );
+        assert_eq!(
+            Ok(MoveFromTo::new(4, 2, 3, 3)),
+            board.parse_move("exd4", white)
+        );
+        assert_eq!(
+            Ok(MoveFromTo::new(5, 2, 3, 3)),
+            board.parse_move("Nxd4+", white)
+        );
+        assert_eq!(
+            Ok(MoveFromTo::new(4, 0, 6, 0)),
+            board.parse_move("O-O", white)
+        );
+        assert!(board.parse_move("O-O-O", white).is_err());
+
+        let mut board = Board::with_variant(Variant::Capablanca);
+        board.set(9, 0, ChessPieceColour::White, ChessPieceKind::Rook);
+        board.set(5, 0, ChessPieceColour::White, ChessPieceKind::King);
+        board.set(5, 7, ChessPieceColour::Black, ChessPieceKind::King);
+        let castling = MoveFromTo::new(5, 0, 8, 0);
+        assert_eq!(Ok(castling.clone()), board.parse_move("O-O", white));
+        assert_eq!("O-O", board.san(&castling));
+    }
+
+    #[test]
+    fn write_moves() {
+        let white = ChessPieceColour::White;
+        let black = ChessPieceColour::Black;
+        let mut board = Board::with_variant(Variant::Standard);
+        board.set(4, 0, white, ChessPieceKind::King);
+        board.set(4, 7, black, ChessPieceKind::King);
+        board.set(1, 0, white, ChessPieceKind::Knight);
+        board.set(5, 2, white, ChessPieceKind::Knight);
+        board.set(7, 0, white, ChessPieceKind::Rook);
+        board.set(3, 3, black, ChessPieceKind::Pawn);
+        board.set(4, 2, white, ChessPieceKind::Pawn);
+        for san in ["Nbd2", "Nfd2", "exd4", "Nxd4", "O-O", "Rh8+", "Kf2"] {
+            let _move = board.parse_move(san, white).unwrap();
+            assert_eq!(san, board.san(&_move));
+        }
+
+        let mut board = Board::from_variant(Variant::Standard);
+        for _move in ["f2f3", "e7e5", "g2g4"] {
+            board.play(&_move.parse().unwrap());
+        }
+        assert_eq!("Qh4#", board.san(&"d8h4".parse().unwrap()));
+    }
+}
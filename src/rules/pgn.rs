@@ -0,0 +1,255 @@
+// writing games in PGN, the format most chess software can read. games of other variants get a
+// `Variant` tag, and moves of custom pieces are written in UCI notation as they have no letter
+
+use std::fmt;
+
+use crate::rules::board::{Board, Variant};
+use crate::rules::eco::OpeningTable;
+use crate::rules::pieces::ChessPieceColour;
+use crate::rules::MoveFromTo;
+
+// movetext lines are kept shorter than this, as the PGN standard asks
+const LINE_LENGTH: usize = 80;
+
+// why a game ended, written in the `Termination` tag. games that ended on the board, by
+// resignation or by agreement end normally
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Termination {
+    #[default]
+    Normal,
+    // a player lost the connection and didn't come back in time
+    Abandoned,
+    TimeForfeit,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let termination = match self {
+            Termination::Normal => "normal",
+            Termination::Abandoned => "abandoned",
+            Termination::TimeForfeit => "time forfeit",
+        };
+        write!(f, "{}", termination)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    Win(ChessPieceColour),
+    Draw,
+}
+
+impl GameResult {
+    // checkmate or stalemate, if the side to move has no legal moves left
+    pub fn from_position(board: &Board, to_move: ChessPieceColour) -> Option<Self> {
+        if board.has_legal_moves(to_move) {
+            return None;
+        }
+        Some(match board.check {
+            Some(_) => GameResult::Win(!to_move),
+            None => GameResult::Draw,
+        })
+    }
+}
+
+// written the way PGN does, e.g. `1-0`
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = match self {
+            GameResult::Win(ChessPieceColour::White) => "1-0",
+            GameResult::Win(ChessPieceColour::Black) => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        };
+        write!(f, "{}", result)
+    }
+}
+
+// the tags every PGN game starts with, in this order. the ones that aren't given are unknown
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+// the game played from the start position. the seven tag roster comes first, with the `Result`
+// tag being `*` for games that are still going, then the other tags in the order given followed
+// by `ECO` and `Opening` tags for games of standard chess that went through a named opening. the
+// moves are checked on the way
+pub fn write_pgn(
+    tags: &[(&str, String)],
+    start: &Board,
+    moves: &[MoveFromTo],
+    result: Option<GameResult>,
+) -> Result<String, String> {
+    let result = result.map_or(String::from("*"), |result| result.to_string());
+
+    let mut board = start.clone();
+    let mut positions = vec![board.clone()];
+    // counted as if white had moved first, so that white's moves still start the move numbers
+    let offset = match board.to_move {
+        ChessPieceColour::White => 0,
+        ChessPieceColour::Black => 1,
+    };
+    let mut tokens = Vec::new();
+    for (ply, _move) in moves.iter().enumerate() {
+        let colour = board.to_move;
+        let parsed = board.parse_move(&_move.to_string(), colour)?;
+        match (ply, colour) {
+            (_, ChessPieceColour::White) => tokens.push(format!("{}.", (ply + offset) / 2 + 1)),
+            // a game where black moves first starts with the move number and an ellipsis
+            (0, ChessPieceColour::Black) => tokens.push(String::from("1...")),
+            _ => (),
+        }
+        tokens.push(board.san(&parsed));
+        board.play(&parsed);
+        positions.push(board.clone());
+    }
+    tokens.push(result.clone());
+
+    let mut pgn = String::new();
+    for (name, unknown) in SEVEN_TAG_ROSTER {
+        let value = match name {
+            "Result" => Some(&result),
+            _ => tags
+                .iter()
+                .find(|(given, _)| *given == name)
+                .map(|(_, value)| value),
+        };
+        pgn.push_str(&tag(name, value.map_or(unknown, String::as_str)));
+    }
+    for (name, value) in tags {
+        if SEVEN_TAG_ROSTER.iter().all(|(roster, _)| roster != name) {
+            pgn.push_str(&tag(name, value));
+        }
+    }
+    if let Some(opening) = OpeningTable::bundled().recognise(&positions) {
+        pgn.push_str(&tag("ECO", &opening.eco));
+        pgn.push_str(&tag("Opening", &opening.name));
+    }
+    if start.variant != Variant::Standard {
+        pgn.push_str(&tag("Variant", &start.variant.to_string()));
+    }
+    pgn.push('\n');
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() >= LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+    Ok(pgn)
+}
+
+// the UTC date of a unix timestamp, for the `Date` tag, e.g. `2023.01.31`
+pub fn pgn_date(timestamp: u64) -> String {
+    // days to a civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+fn tag(name: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", name, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::board::{Board, Variant};
+    use crate::rules::pgn::{pgn_date, write_pgn, GameResult};
+    use crate::rules::pieces::ChessPieceColour;
+    use crate::rules::MoveFromTo;
+
+    #[test]
+    fn games_are_written_in_pgn() {
+        let moves: Vec<MoveFromTo> = ["f2f3", "e7e5", "g2g4", "d8h4"]
+            .iter()
+            .map(|_move| _move.parse().unwrap())
+            .collect();
+        let pgn = write_pgn(
+            &[
+                ("Annotator", String::from("bevy_chess")),
+                ("White", String::from("Someone \"quoted\"")),
+                ("Event", String::from("Casual game")),
+            ],
+            &Board::default(),
+            &moves,
+            Some(GameResult::Win(ChessPieceColour::Black)),
+        )
+        .unwrap();
+        assert_eq!(
+            "[Event \"Casual game\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+             [White \"Someone \\\"quoted\\\"\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\
+             [Annotator \"bevy_chess\"]\n[ECO \"A00\"]\n[Opening \"Barnes Opening\"]\n\n\
+             1. f3 e5 2. g4 Qh4# 0-1\n",
+            pgn
+        );
+
+        let pgn = write_pgn(&[], &Board::from_variant(Variant::LosAlamos), &[], None).unwrap();
+        assert!(pgn.starts_with("[Event \"?\"]\n"));
+        assert!(pgn.ends_with("[Result \"*\"]\n[Variant \"los_alamos\"]\n\n*\n"));
+
+        let illegal = [moves[0].clone(), moves[0].clone()];
+        assert!(write_pgn(&[], &Board::default(), &illegal, None).is_err());
+    }
+
+    #[test]
+    fn move_numbers_when_black_moves_first() {
+        let moves: Vec<MoveFromTo> = ["e7e5", "e2e4", "g8f6"]
+            .iter()
+            .map(|_move| _move.parse().unwrap())
+            .collect();
+        let start = Board {
+            to_move: ChessPieceColour::Black,
+            ..Board::default()
+        };
+        let pgn = write_pgn(&[], &start, &moves, None).unwrap();
+        assert_eq!(Some("1... e5 2. e4 Nf6 *\n"), pgn.split("\n\n").nth(1));
+    }
+
+    #[test]
+    fn long_games_are_wrapped() {
+        // the knights go back and forth
+        let moves: Vec<MoveFromTo> = ["g1f3", "g8f6", "f3g1", "f6g8"]
+            .repeat(10)
+            .iter()
+            .map(|_move| _move.parse().unwrap())
+            .collect();
+        let pgn = write_pgn(&[], &Board::default(), &moves, Some(GameResult::Draw)).unwrap();
+        let movetext: Vec<&str> = pgn.split("\n\n").nth(1).unwrap().lines().collect();
+        assert!(movetext.len() > 1);
+        assert!(movetext.iter().all(|line| line.len() < 80));
+        assert!(pgn.ends_with("20. Ng1 Ng8 1/2-1/2\n"));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!("1970.01.01", pgn_date(0));
+        assert_eq!("2000.02.29", pgn_date(951782400));
+        assert_eq!("2023.12.31", pgn_date(1704067199));
+    }
+}
@@ -1,7 +1,13 @@
 use std::ops::Not;
 
-#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum ChessPieceColour {
+    #[default]
     White,
     Black,
 }
@@ -18,6 +24,11 @@ impl Not for ChessPieceColour {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum ChessPieceKind {
     Pawn,
     Knight,
@@ -25,9 +36,16 @@ pub enum ChessPieceKind {
     Bishop,
     Queen,
     King,
+    // bishop + knight
+    Archbishop,
+    // rook + knight
+    Chancellor,
+    // index into the custom pieces of the board
+    Custom(u8),
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChessPiece {
     pub x: u32,
     pub y: u32,
@@ -52,9 +70,15 @@ impl ChessPiece {
 mod tests {
     use std::collections::HashSet;
 
-    use crate::game::board::Board;
-    use crate::game::pieces::{ChessPiece, ChessPieceColour, ChessPieceKind};
-    use crate::game::{Move, MoveFromTo};
+    use crate::rules::board::{Board, Variant};
+    #[cfg(feature = "serde")]
+    use crate::rules::custom_pieces::PieceSet;
+    use crate::rules::pieces::{ChessPiece, ChessPieceColour, ChessPieceKind};
+    use crate::rules::{Move, MoveFromTo};
+
+    // the default piece texture is six sprites wide and three high
+    #[cfg(feature = "serde")]
+    const SPRITES: usize = 18;
 
     #[test]
     fn pawn_basic_moves() {
@@ -1016,6 +1040,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn king_castling_capablanca() {
+        let mut board = Board::with_variant(Variant::Capablanca);
+        board.set(0, 0, ChessPieceColour::White, ChessPieceKind::Rook);
+        board.set(9, 0, ChessPieceColour::White, ChessPieceKind::Rook);
+        board.set(5, 0, ChessPieceColour::White, ChessPieceKind::King);
+        board.set(5, 7, ChessPieceColour::Black, ChessPieceKind::King);
+
+        let moves = board.get_moves(5, 0).unwrap();
+        for x in [2, 8] {
+            assert!(moves.contains(&Move {
+                x,
+                y: 0,
+                takes: false
+            }));
+        }
+        assert!(!moves.contains(&Move {
+            x: 7,
+            y: 0,
+            takes: false
+        }));
+
+        let mut kingside = board.clone();
+        kingside._move(5, 0, 8, 0);
+        assert_eq!(
+            Some(ChessPieceKind::King),
+            kingside.get(8, 0).map(|piece| piece.kind)
+        );
+        assert_eq!(
+            Some(ChessPieceKind::Rook),
+            kingside.get(7, 0).map(|piece| piece.kind)
+        );
+        assert!(kingside.get(9, 0).is_none());
+
+        board._move(5, 0, 2, 0);
+        assert_eq!(
+            Some(ChessPieceKind::King),
+            board.get(2, 0).map(|piece| piece.kind)
+        );
+        assert_eq!(
+            Some(ChessPieceKind::Rook),
+            board.get(3, 0).map(|piece| piece.kind)
+        );
+        assert!(board.get(0, 0).is_none());
+    }
+
     #[test]
     fn king_castling_obstructed() {
         let mut board = Board::new();
@@ -1092,4 +1162,308 @@ mod tests {
             board.get_moves(4, 7)
         );
     }
+
+    #[test]
+    fn archbishop_moves() {
+        let mut board = Board::with_variant(Variant::Capablanca);
+        board.set(7, 2, ChessPieceColour::Black, ChessPieceKind::Pawn);
+
+        board.set(9, 0, ChessPieceColour::White, ChessPieceKind::Archbishop);
+
+        assert_eq!(
+            Some(HashSet::from([
+                Move {
+                    x: 8,
+                    y: 1,
+                    takes: false
+                },
+                Move {
+                    x: 7,
+                    y: 2,
+                    takes: true
+                },
+                Move {
+                    x: 8,
+                    y: 2,
+                    takes: false
+                },
+                Move {
+                    x: 7,
+                    y: 1,
+                    takes: false
+                },
+            ])),
+            board.get_moves(9, 0)
+        );
+    }
+
+    #[test]
+    fn chancellor_moves() {
+        let mut board = Board::with_variant(Variant::LosAlamos);
+        board.set(0, 1, ChessPieceColour::White, ChessPieceKind::Pawn);
+        board.set(3, 0, ChessPieceColour::Black, ChessPieceKind::Pawn);
+
+        board.set(0, 0, ChessPieceColour::White, ChessPieceKind::Chancellor);
+
+        assert_eq!(
+            Some(HashSet::from([
+                Move {
+                    x: 1,
+                    y: 0,
+                    takes: false
+                },
+                Move {
+                    x: 2,
+                    y: 0,
+                    takes: false
+                },
+                Move {
+                    x: 3,
+                    y: 0,
+                    takes: true
+                },
+                Move {
+                    x: 1,
+                    y: 2,
+                    takes: false
+                },
+                Move {
+                    x: 2,
+                    y: 1,
+                    takes: false
+                },
+            ])),
+            board.get_moves(0, 0)
+        );
+    }
+
+    #[test]
+    fn variant_setup() {
+        let board = Board::from_variant(Variant::Capablanca);
+        assert_eq!((10, 8), (board.width(), board.height()));
+        assert_eq!(
+            Some(ChessPieceKind::Archbishop),
+            board.get(2, 0).map(|piece| piece.kind)
+        );
+        assert_eq!(
+            Some(ChessPieceKind::Chancellor),
+            board.get(7, 7).map(|piece| piece.kind)
+        );
+        assert_eq!(
+            Some(ChessPieceColour::Black),
+            board.get(9, 6).map(|piece| piece.colour)
+        );
+        assert!(board.get(10, 0).is_none());
+
+        let board = Board::from_variant(Variant::LosAlamos);
+        assert_eq!((6, 6), (board.width(), board.height()));
+        assert_eq!(
+            Some(ChessPieceKind::King),
+            board.get(3, 5).map(|piece| piece.kind)
+        );
+        assert!(board.get(0, 6).is_none());
+    }
+
+    #[test]
+    fn los_alamos_rules() {
+        let mut board = Board::with_variant(Variant::LosAlamos);
+        board.set(0, 0, ChessPieceColour::White, ChessPieceKind::Rook);
+        board.set(5, 0, ChessPieceColour::White, ChessPieceKind::Rook);
+        board.set(3, 0, ChessPieceColour::White, ChessPieceKind::King);
+        board.set(1, 1, ChessPieceColour::White, ChessPieceKind::Pawn);
+        board.set(2, 1, ChessPieceColour::White, ChessPieceKind::Pawn);
+        board.set(3, 1, ChessPieceColour::White, ChessPieceKind::Pawn);
+        board.set(4, 1, ChessPieceColour::White, ChessPieceKind::Pawn);
+
+        // no pawn double moves
+        assert_eq!(
+            Some(HashSet::from([Move {
+                x: 1,
+                y: 2,
+                takes: false
+            }])),
+            board.get_moves(1, 1)
+        );
+        // no castling
+        assert_eq!(
+            Some(HashSet::from([
+                Move {
+                    x: 2,
+                    y: 0,
+                    takes: false
+                },
+                Move {
+                    x: 4,
+                    y: 0,
+                    takes: false
+                },
+            ])),
+            board.get_moves(3, 0)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn custom_piece_moves() {
+        let piece_set = PieceSet::parse(
+            r#"(pieces: [(name: "Nightrider", betza: "NN", white_sprite: 3, black_sprite: 9)])"#,
+            SPRITES,
+        )
+        .unwrap();
+        let mut board = Board::new().with_piece_set(piece_set).unwrap();
+        board.set(2, 4, ChessPieceColour::Black, ChessPieceKind::Pawn);
+        board.set(6, 3, ChessPieceColour::Black, ChessPieceKind::King);
+
+        board.set(0, 0, ChessPieceColour::White, ChessPieceKind::Custom(0));
+
+        assert_eq!(
+            Some(HashSet::from([
+                Move {
+                    x: 1,
+                    y: 2,
+                    takes: false
+                },
+                Move {
+                    x: 2,
+                    y: 4,
+                    takes: true
+                },
+                Move {
+                    x: 2,
+                    y: 1,
+                    takes: false
+                },
+                Move {
+                    x: 4,
+                    y: 2,
+                    takes: false
+                },
+                Move {
+                    x: 6,
+                    y: 3,
+                    takes: true
+                },
+            ])),
+            board.get_moves(0, 0)
+        );
+        assert_eq!(1, board.get_king_attackers(ChessPieceColour::Black).len());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn custom_back_rank() {
+        let piece_set = PieceSet::parse(
+            r#"(
+                pieces: [(name: "Amazon", betza: "QN", white_sprite: 16, black_sprite: 17)],
+                back_rank: Some(["Rook", "Knight", "Amazon", "King", "Knight", "Rook"]),
+            )"#,
+            SPRITES,
+        )
+        .unwrap();
+
+        assert!(Board::from_variant(Variant::Standard)
+            .with_piece_set(piece_set.clone())
+            .is_err());
+
+        let board = Board::from_variant(Variant::LosAlamos)
+            .with_piece_set(piece_set)
+            .unwrap();
+        assert_eq!(
+            Some(ChessPieceKind::Custom(0)),
+            board.get(2, 0).map(|piece| piece.kind)
+        );
+        assert_eq!(
+            Some(ChessPieceKind::Custom(0)),
+            board.get(2, 5).map(|piece| piece.kind)
+        );
+        assert_eq!("Amazon", board.custom_piece(0).unwrap().name);
+    }
+
+    #[test]
+    fn mating_material() {
+        let mut board = Board::new();
+        board.set(4, 0, ChessPieceColour::White, ChessPieceKind::King);
+        board.set(4, 7, ChessPieceColour::Black, ChessPieceKind::King);
+        board.set(2, 0, ChessPieceColour::White, ChessPieceKind::Bishop);
+        board.set(1, 7, ChessPieceColour::Black, ChessPieceKind::Knight);
+
+        assert!(!board.has_mating_material(ChessPieceColour::White));
+        assert!(!board.has_mating_material(ChessPieceColour::Black));
+
+        board.set(6, 7, ChessPieceColour::Black, ChessPieceKind::Knight);
+        board.set(0, 1, ChessPieceColour::White, ChessPieceKind::Pawn);
+
+        assert!(board.has_mating_material(ChessPieceColour::White));
+        assert!(board.has_mating_material(ChessPieceColour::Black));
+    }
+
+    #[test]
+    fn legal_moves() {
+        let mut board = Board::new();
+        board.set(4, 0, ChessPieceColour::White, ChessPieceKind::King);
+        board.set(4, 3, ChessPieceColour::White, ChessPieceKind::Knight);
+        board.set(2, 2, ChessPieceColour::White, ChessPieceKind::Bishop);
+        board.set(4, 7, ChessPieceColour::Black, ChessPieceKind::Rook);
+        board.set(0, 4, ChessPieceColour::Black, ChessPieceKind::Bishop);
+        board.set(0, 7, ChessPieceColour::Black, ChessPieceKind::King);
+
+        // the knight is pinned
+        assert_eq!(Some(HashSet::new()), board.get_legal_moves(4, 3));
+        // the bishop is pinned too, but can still move along the pin
+        assert_eq!(
+            Some(HashSet::from([
+                Move {
+                    x: 3,
+                    y: 1,
+                    takes: false
+                },
+                Move {
+                    x: 1,
+                    y: 3,
+                    takes: false
+                },
+                Move {
+                    x: 0,
+                    y: 4,
+                    takes: true
+                },
+            ])),
+            board.get_legal_moves(2, 2)
+        );
+
+        // with the knight and the black bishop gone, the king is in check and the bishop can only
+        // block
+        board.board[7 - 3][4] = None;
+        board.board[7 - 4][0] = None;
+        assert_eq!(
+            Some(HashSet::from([Move {
+                x: 4,
+                y: 4,
+                takes: false
+            }])),
+            board.get_legal_moves(2, 2)
+        );
+        assert!(board.has_legal_moves(ChessPieceColour::White));
+    }
+
+    #[test]
+    fn checkmate_and_stalemate() {
+        let mut board = Board::default();
+        board._move(5, 1, 5, 2);
+        board._move(4, 6, 4, 4);
+        board._move(6, 1, 6, 3);
+        board._move(3, 7, 7, 3);
+
+        assert_eq!(1, board.get_king_attackers(ChessPieceColour::White).len());
+        assert!(!board.has_legal_moves(ChessPieceColour::White));
+
+        let mut board = Board::new();
+        board.set(0, 0, ChessPieceColour::White, ChessPieceKind::King);
+        board.set(1, 2, ChessPieceColour::Black, ChessPieceKind::Queen);
+        board.set(2, 2, ChessPieceColour::Black, ChessPieceKind::King);
+
+        assert!(board.get_king_attackers(ChessPieceColour::White).is_empty());
+        assert!(!board.has_legal_moves(ChessPieceColour::White));
+        assert!(board.has_legal_moves(ChessPieceColour::Black));
+    }
 }
@@ -0,0 +1,272 @@
+// serde support for positions, moves and games, enabled with the `serde` feature. the
+// representation is meant to stay the same between versions, in JSON it looks like this:
+//
+// - colours are `"white"` or `"black"`
+// - piece kinds are `"pawn"`, `"knight"`, `"rook"`, `"bishop"`, `"queen"`, `"king"`,
+//   `"archbishop"`, `"chancellor"`, or `{"custom": 0}` for the first custom piece of the board
+// - squares are named like in algebraic notation, e.g. `"e4"`, with files going up to `z` and
+//   ranks past 9 on bigger boards
+// - moves are written in long algebraic notation, e.g. `"e2e4"`, or as
+//   `{"x": 4, "y": 3, "takes": false}` for the destinations returned by `Board::get_moves`
+// - pieces are `{"x": 4, "y": 0, "colour": "white", "kind": "king", "has_moved": false}`,
+//   counting squares from 0 with a1 at `x: 0, y: 0`
+// - positions are
+//   `{"variant": "standard", "pieces": [...], "custom_pieces": [...], "last_move": "e2e4",
+//   "check": null, "to_move": "black"}`, where the variant is `"standard"`, `"capablanca"` or
+//   `"los_alamos"`, the pieces are listed rank by rank from a1, custom pieces are
+//   `{"name": "Amazon", "betza": "QN", "white_sprite": 16, "black_sprite": 17}`, `check` is
+//   the colour in check, if any, and `to_move` is white unless it says otherwise
+// - games are `{"start": <position>, "moves": ["e2e4", "e7e5", ...]}`
+
+use std::sync::Arc;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::rules::betza;
+use crate::rules::board::{Board, Variant};
+use crate::rules::custom_pieces::CustomPiece;
+use crate::rules::pieces::{ChessPiece, ChessPieceColour, ChessPieceKind};
+use crate::rules::{BoardPos, MoveFromTo};
+
+impl Serialize for BoardPos {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoardPos {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for MoveFromTo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MoveFromTo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CustomPieceRepr {
+    name: String,
+    betza: String,
+    white_sprite: u8,
+    black_sprite: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BoardRepr {
+    variant: Variant,
+    pieces: Vec<ChessPiece>,
+    #[serde(default)]
+    custom_pieces: Vec<CustomPieceRepr>,
+    #[serde(default)]
+    last_move: Option<MoveFromTo>,
+    #[serde(default)]
+    check: Option<ChessPieceColour>,
+    #[serde(default)]
+    to_move: ChessPieceColour,
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut pieces: Vec<ChessPiece> = self.iter().flatten().copied().collect();
+        pieces.sort_by_key(|piece| (piece.y, piece.x));
+
+        BoardRepr {
+            variant: self.variant,
+            pieces,
+            custom_pieces: self
+                .custom_pieces
+                .iter()
+                .map(|piece| CustomPieceRepr {
+                    name: piece.name.clone(),
+                    betza: piece.betza.clone(),
+                    white_sprite: piece.white_sprite,
+                    black_sprite: piece.black_sprite,
+                })
+                .collect(),
+            last_move: self.last_move.clone(),
+            check: self.check,
+            to_move: self.to_move,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BoardRepr::deserialize(deserializer)?;
+
+        let mut custom_pieces = Vec::new();
+        for piece in repr.custom_pieces {
+            let patterns = betza::parse(&piece.betza)
+                .map_err(|err| D::Error::custom(format!("{}: {}", piece.name, err)))?;
+            custom_pieces.push(CustomPiece {
+                name: piece.name,
+                betza: piece.betza,
+                patterns,
+                white_sprite: piece.white_sprite,
+                black_sprite: piece.black_sprite,
+            });
+        }
+
+        let mut board = Board::with_variant(repr.variant);
+        for piece in repr.pieces {
+            if piece.x >= board.width() || piece.y >= board.height() {
+                return Err(D::Error::custom(format!(
+                    "piece at {},{} is off the board",
+                    piece.x, piece.y
+                )));
+            }
+            if board.get(piece.x, piece.y).is_some() {
+                return Err(D::Error::custom(format!(
+                    "more than one piece at {},{}",
+                    piece.x, piece.y
+                )));
+            }
+            if let ChessPieceKind::Custom(id) = piece.kind {
+                if id as usize >= custom_pieces.len() {
+                    return Err(D::Error::custom(format!("unknown custom piece {}", id)));
+                }
+            }
+            // placed as is rather than with `set`, which would forget whether it has moved
+            let row = board.row(piece.y);
+            board.board[row][piece.x as usize] = Some(piece);
+        }
+        board.custom_pieces = Arc::new(custom_pieces);
+        board.last_move = repr.last_move;
+        board.check = repr.check;
+        board.to_move = repr.to_move;
+
+        Ok(board)
+    }
+}
+
+// a game as the position it started from and the moves played since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub start: Board,
+    pub moves: Vec<MoveFromTo>,
+}
+
+impl GameRecord {
+    // the positions after each move, checking that the moves are legal
+    pub fn replay(&self) -> Result<Vec<Board>, String> {
+        let mut board = self.start.clone();
+        let mut positions = Vec::new();
+        for _move in &self.moves {
+            let parsed = board.parse_move(&_move.to_string(), board.to_move)?;
+            board.play(&parsed);
+            positions.push(board.clone());
+        }
+        Ok(positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::board::{Board, Variant};
+    use crate::rules::custom_pieces::PieceSet;
+    use crate::rules::pieces::{ChessPieceColour, ChessPieceKind};
+    use crate::rules::serialisation::GameRecord;
+    use crate::rules::{BoardPos, Move, MoveFromTo};
+
+    const SPRITES: usize = 18;
+
+    #[test]
+    fn json_representation() {
+        assert_eq!(
+            r#""white""#,
+            serde_json::to_string(&ChessPieceColour::White).unwrap()
+        );
+        assert_eq!(
+            r#"{"custom":2}"#,
+            serde_json::to_string(&ChessPieceKind::Custom(2)).unwrap()
+        );
+        assert_eq!(
+            r#""b10""#,
+            serde_json::to_string(&BoardPos { x: 1, y: 9 }).unwrap()
+        );
+        assert_eq!(
+            r#""e2e4""#,
+            serde_json::to_string(&MoveFromTo::new(4, 1, 4, 3)).unwrap()
+        );
+        assert_eq!(
+            r#"{"x":4,"y":3,"takes":false}"#,
+            serde_json::to_string(&Move {
+                x: 4,
+                y: 3,
+                takes: false
+            })
+            .unwrap()
+        );
+
+        let mut board = Board::with_variant(Variant::LosAlamos);
+        board.set(3, 0, ChessPieceColour::White, ChessPieceKind::King);
+        assert_eq!(
+            r#"{"variant":"los_alamos","pieces":[{"x":3,"y":0,"colour":"white","kind":"king","has_moved":false}],"custom_pieces":[],"last_move":null,"check":null,"to_move":"white"}"#,
+            serde_json::to_string(&board).unwrap()
+        );
+    }
+
+    #[test]
+    fn positions_round_trip() {
+        let piece_set = PieceSet::parse(
+            r#"(pieces: [(name: "Amazon", betza: "QN", white_sprite: 16, black_sprite: 17)])"#,
+            SPRITES,
+        )
+        .unwrap();
+        let mut board = Board::from_variant(Variant::Capablanca)
+            .with_piece_set(piece_set)
+            .unwrap();
+        board.set(4, 3, ChessPieceColour::Black, ChessPieceKind::Custom(0));
+        board.play(&"e2e4".parse().unwrap());
+
+        let json = serde_json::to_string(&board).unwrap();
+        let loaded: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(json, serde_json::to_string(&loaded).unwrap());
+        assert!(loaded.get(4, 3).unwrap().has_moved);
+        assert_eq!(Some(&MoveFromTo::new(4, 1, 4, 3)), loaded.last_move());
+        assert_eq!(ChessPieceColour::Black, loaded.to_move);
+        assert_eq!("QN", loaded.custom_piece(0).unwrap().betza);
+
+        assert!(serde_json::from_str::<Board>(
+            r#"{"variant":"los_alamos","pieces":[{"x":6,"y":0,"colour":"white","kind":"king","has_moved":false}]}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<Board>(
+            r#"{"variant":"standard","pieces":[{"x":0,"y":0,"colour":"white","kind":{"custom":0},"has_moved":false}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn games_replay() {
+        let game: GameRecord = serde_json::from_str(&format!(
+            r#"{{"start":{},"moves":["f2f3","e7e5","g2g4","d8h4"]}}"#,
+            serde_json::to_string(&Board::default()).unwrap()
+        ))
+        .unwrap();
+        let positions = game.replay().unwrap();
+        assert_eq!(4, positions.len());
+        assert_eq!(Some(ChessPieceColour::White), positions[3].check);
+
+        let illegal = GameRecord {
+            moves: vec!["e2e4".parse().unwrap(), "e2e4".parse().unwrap()],
+            ..game
+        };
+        assert!(illegal.replay().is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ChessConfig;
+use crate::net::DEFAULT_PORT;
+use crate::theme::DEFAULT_THEME_PATH;
+
+// bumped whenever a change to the settings can't be read by older versions of the game
+pub const SETTINGS_VERSION: u32 = 1;
+
+// which side is drawn at the bottom when a game starts
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreferredOrientation {
+    // the human's side against the computer or over the network, white otherwise
+    Player,
+    White,
+    Black,
+}
+
+// preferences that can be changed in the settings screen, kept in a file in the user's config
+// folder between runs
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub window_width: f32,
+    pub window_height: f32,
+    // tint the tiles the last move was made from and to
+    pub highlight_last_move: bool,
+    // draw an arrow along the last move
+    pub last_move_arrow: bool,
+    // slide pieces to where they're moved and fade out captured ones
+    pub animations: bool,
+    pub orientation: PreferredOrientation,
+    // path of the theme asset the board is drawn with
+    pub theme: String,
+    // the host to join network games on, and the port to host them on, e.g. `127.0.0.1:7878`,
+    // or a game server to join like `ws://127.0.0.1:7878/`. only changed in the file for now
+    pub server_address: String,
+    // the id of the game to watch on a game server, the one that started last if there's none.
+    // only changed in the file for now
+    pub watched_game: Option<u64>,
+    // written into correspondence game files as this side's name. only changed in the file for now
+    pub player_name: String,
+    // a Polyglot `.bin` book the computer plays its openings from. only changed in the file for now
+    pub opening_book: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            window_width: 1600.,
+            window_height: 900.,
+            highlight_last_move: true,
+            last_move_arrow: false,
+            animations: true,
+            orientation: PreferredOrientation::Player,
+            theme: String::from(DEFAULT_THEME_PATH),
+            server_address: format!("127.0.0.1:{}", DEFAULT_PORT),
+            watched_game: None,
+            player_name: String::from("?"),
+            opening_book: None,
+        }
+    }
+}
+
+impl Settings {
+    // `bevy_chess/settings.ron` in the user's config folder, if the platform has one
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("bevy_chess").join("settings.ron"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        Self::parse(&contents).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    // settings missing from the file keep their defaults, so that files written before a
+    // setting was added can still be read
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let settings: Settings = ron::from_str(contents).map_err(|err| err.to_string())?;
+        if settings.version > SETTINGS_VERSION {
+            return Err(format!(
+                "settings version {} is newer than this game's {}",
+                settings.version, SETTINGS_VERSION
+            ));
+        }
+        Ok(Self {
+            version: SETTINGS_VERSION,
+            ..settings
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("couldn't create {}: {}", dir.display(), err))?;
+        }
+        fs::write(path, contents)
+            .map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+    }
+
+    pub fn animation_duration(&self, config: &ChessConfig) -> f32 {
+        match self.animations {
+            true => config.animation_duration,
+            false => 0.,
+        }
+    }
+}
+
+// where the settings are saved to whenever they change, nothing is saved without a path
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SettingsFile(pub Option<PathBuf>);
+
+pub fn save_settings(settings: Res<Settings>, file: Res<SettingsFile>) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    if let Some(path) = &file.0 {
+        if let Err(err) = settings.save(path) {
+            warn!("{}", err);
+        }
+    }
+}
+
+pub fn remember_window_size(
+    windows: Res<Windows>,
+    mut window_resized_ev: EventReader<WindowResized>,
+    mut settings: ResMut<Settings>,
+) {
+    let primary = windows.primary().id();
+    for ev in window_resized_ev.iter().filter(|ev| ev.id == primary) {
+        if settings.window_width != ev.width || settings.window_height != ev.height {
+            settings.window_width = ev.width;
+            settings.window_height = ev.height;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::settings::{PreferredOrientation, Settings, SETTINGS_VERSION};
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = Settings {
+            window_width: 1280.,
+            last_move_arrow: true,
+            orientation: PreferredOrientation::Black,
+            watched_game: Some(3),
+            ..Settings::default()
+        };
+        let contents =
+            ron::ser::to_string_pretty(&settings, ron::ser::PrettyConfig::default()).unwrap();
+        assert_eq!(Ok(settings), Settings::parse(&contents));
+    }
+
+    #[test]
+    fn missing_settings_keep_their_defaults() {
+        assert_eq!(
+            Ok(Settings {
+                animations: false,
+                ..Settings::default()
+            }),
+            Settings::parse("(version: 1, animations: false)")
+        );
+        assert_eq!(Ok(Settings::default()), Settings::parse("()"));
+    }
+
+    #[test]
+    fn bad_settings_are_rejected() {
+        assert!(Settings::parse("(animations: maybe)").is_err());
+        assert!(Settings::parse("not settings").is_err());
+        assert!(Settings::parse(&format!("(version: {})", SETTINGS_VERSION + 1)).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+
+use crate::config::ChessConfig;
+use crate::rules::pieces::{ChessPieceColour, ChessPieceKind};
+use crate::settings::Settings;
+
+pub const DEFAULT_THEME_PATH: &str = "themes/classic.theme.ron";
+
+// the look of the board, loaded from `.theme.ron` files in the assets folder, see
+// `assets/themes/classic.theme.ron`
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid)]
+#[uuid = "199f2b4f-1702-465f-ae74-ea4766532d37"]
+pub struct Theme {
+    pub name: String,
+    // a texture with the two tiles next to each other, the second one is used for a1, both
+    // `TEXTURE_TILE_SIZE` pixels wide
+    pub board_tiles: String,
+    pub pieces: PieceTextures,
+    pub colours: ThemeColours,
+    pub font: String,
+    pub label_font: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PieceTextures {
+    // cut up into a grid of square pieces
+    pub texture: String,
+    pub tile_size: f32,
+    pub columns: usize,
+    pub rows: usize,
+    pub white: PieceIndices,
+    pub black: PieceIndices,
+}
+
+// where each piece is in the piece texture, counting left to right and then top to bottom
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub struct PieceIndices {
+    pub king: u8,
+    pub queen: u8,
+    pub bishop: u8,
+    pub knight: u8,
+    pub rook: u8,
+    pub pawn: u8,
+    pub archbishop: u8,
+    pub chancellor: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThemeColours {
+    pub last_move: Color,
+    pub last_move_arrow: Color,
+    pub labels: Color,
+    // the turn indicators are faded out while it's the other side's turn
+    pub white_turn_indicator: Color,
+    pub black_turn_indicator: Color,
+    pub clock: Color,
+    pub flagged_clock: Color,
+    // multiplied with the indicator textures, so white leaves them as they are
+    pub move_indicator: Color,
+    pub capture_indicator: Color,
+    pub hover_ring: Color,
+    pub check_indicator: Color,
+    // the text around the board: the opening name, the book moves and the move being typed
+    pub text: Color,
+    pub hint_text: Color,
+    pub error_text: Color,
+}
+
+impl Default for Theme {
+    // the same as the classic theme, used until a theme has been loaded
+    fn default() -> Self {
+        Self {
+            name: String::from("Classic"),
+            board_tiles: String::from("chessboard_tiles_96px.png"),
+            pieces: PieceTextures {
+                texture: String::from("chess_pieces_96px.png"),
+                tile_size: 96.,
+                columns: 6,
+                rows: 3,
+                white: PieceIndices {
+                    king: 0,
+                    queen: 1,
+                    bishop: 2,
+                    knight: 3,
+                    rook: 4,
+                    pawn: 5,
+                    archbishop: 12,
+                    chancellor: 13,
+                },
+                black: PieceIndices {
+                    king: 6,
+                    queen: 7,
+                    bishop: 8,
+                    knight: 9,
+                    rook: 10,
+                    pawn: 11,
+                    archbishop: 14,
+                    chancellor: 15,
+                },
+            },
+            colours: ThemeColours {
+                last_move: Color::rgb(1.0, 0.95, 0.55),
+                last_move_arrow: Color::rgba(1.0, 0.65, 0.0, 0.8),
+                labels: Color::WHITE,
+                white_turn_indicator: Color::WHITE,
+                black_turn_indicator: Color::BLACK,
+                clock: Color::WHITE,
+                flagged_clock: Color::RED,
+                move_indicator: Color::WHITE,
+                capture_indicator: Color::WHITE,
+                hover_ring: Color::WHITE,
+                check_indicator: Color::WHITE,
+                text: Color::WHITE,
+                hint_text: Color::rgba(1., 1., 1., 0.5),
+                error_text: Color::rgb(1., 0.4, 0.4),
+            },
+            font: String::from("fonts/FreeSansBold.ttf"),
+            label_font: String::from("fonts/UbuntuMono-R.ttf"),
+        }
+    }
+}
+
+impl PieceTextures {
+    pub fn sprite_count(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    // custom pieces bring their own indices
+    pub fn index(&self, colour: ChessPieceColour, kind: ChessPieceKind) -> Option<u8> {
+        let indices = match colour {
+            ChessPieceColour::White => &self.white,
+            ChessPieceColour::Black => &self.black,
+        };
+        match kind {
+            ChessPieceKind::King => Some(indices.king),
+            ChessPieceKind::Queen => Some(indices.queen),
+            ChessPieceKind::Bishop => Some(indices.bishop),
+            ChessPieceKind::Knight => Some(indices.knight),
+            ChessPieceKind::Rook => Some(indices.rook),
+            ChessPieceKind::Pawn => Some(indices.pawn),
+            ChessPieceKind::Archbishop => Some(indices.archbishop),
+            ChessPieceKind::Chancellor => Some(indices.chancellor),
+            ChessPieceKind::Custom(_) => None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let theme: Theme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+// the theme everything is drawn with, a copy of the selected theme asset
+#[derive(Resource, Default)]
+pub struct CurrentTheme(pub Theme);
+
+// keeps all the themes from the config loaded, so that they can be picked from in the settings
+#[derive(Resource)]
+pub struct ThemeHandles(pub Vec<(String, Handle<Theme>)>);
+
+impl FromWorld for ThemeHandles {
+    fn from_world(world: &mut World) -> Self {
+        let paths = world.resource::<ChessConfig>().assets.themes.clone();
+        let asset_server = world.resource::<AssetServer>();
+        Self(
+            paths
+                .into_iter()
+                .map(|path| {
+                    let handle = asset_server.load(path.as_str());
+                    (path, handle)
+                })
+                .collect(),
+        )
+    }
+}
+
+// switches to the theme picked in the settings once it's loaded, and again whenever its file
+// changes while the asset server watches for changes
+pub fn apply_theme(
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    themes: Res<Assets<Theme>>,
+    mut theme_ev: EventReader<AssetEvent<Theme>>,
+    mut selected: Local<Handle<Theme>>,
+    mut current: ResMut<CurrentTheme>,
+) {
+    if settings.is_changed() {
+        *selected = asset_server.load(settings.theme.as_str());
+    }
+    let reloaded = theme_ev.iter().any(|ev| match ev {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => *handle == *selected,
+        AssetEvent::Removed { .. } => false,
+    });
+
+    if settings.is_changed() || reloaded {
+        if let Some(theme) = themes.get(&selected) {
+            if current.0 != *theme {
+                info!("using the {} theme", theme.name);
+                current.0 = theme.clone();
+            }
+        }
+    }
+}
+
+// swaps the fonts of all the text that's already there over to the new theme's
+pub fn restyle_fonts(
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    mut previous: Local<Option<(Handle<Font>, Handle<Font>)>>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+    let fonts: (Handle<Font>, Handle<Font>) = (
+        asset_server.load(theme.0.font.as_str()),
+        asset_server.load(theme.0.label_font.as_str()),
+    );
+
+    if let Some((font, label_font)) = previous.replace(fonts.clone()) {
+        for mut text in text_q.iter_mut() {
+            for section in text.sections.iter_mut() {
+                if section.style.font == font {
+                    section.style.font = fonts.0.clone();
+                } else if section.style.font == label_font {
+                    section.style.font = fonts.1.clone();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::theme::{Theme, DEFAULT_THEME_PATH};
+
+    #[test]
+    fn default_theme_matches_classic_theme() {
+        let contents = fs::read_to_string(format!("assets/{}", DEFAULT_THEME_PATH)).unwrap();
+        let theme: Theme = ron::from_str(&contents).unwrap();
+        assert_eq!(Theme::default(), theme);
+    }
+
+    #[test]
+    fn themes_parse() {
+        for entry in fs::read_dir("assets/themes").unwrap() {
+            let path = entry.unwrap().path();
+            let contents = fs::read_to_string(&path).unwrap();
+            if let Err(err) = ron::from_str::<Theme>(&contents) {
+                panic!("{}: {}", path.display(), err);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use crate::config::ChessConfig;
+use crate::settings::Settings;
+
+// moving pieces are drawn over the ones standing still
+const MOVING_PIECE_Z: f32 = 5.0;
+
+// slides a piece from one place to another
+#[derive(Component)]
+pub struct PieceAnimation {
+    from: Vec3,
+    to: Vec3,
+    elapsed: f32,
+}
+
+impl PieceAnimation {
+    pub fn new(from: Vec3, to: Vec3) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.,
+        }
+    }
+}
+
+// a captured piece fading away, it's despawned once it's gone
+#[derive(Component, Default)]
+pub struct FadeOut {
+    elapsed: f32,
+}
+
+// eases in and out, so that pieces don't start and stop abruptly
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3. - 2. * t)
+}
+
+pub fn animate_pieces(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<ChessConfig>,
+    settings: Res<Settings>,
+    mut animation_q: Query<(Entity, &mut PieceAnimation, &mut Transform)>,
+) {
+    for (entity, mut animation, mut transform) in animation_q.iter_mut() {
+        animation.elapsed += time.delta_seconds();
+        let duration = settings.animation_duration(&config);
+        let t = match duration > 0. {
+            true => (animation.elapsed / duration).min(1.),
+            false => 1.,
+        };
+
+        if t < 1. {
+            transform.translation = animation
+                .from
+                .lerp(animation.to, smoothstep(t))
+                .truncate()
+                .extend(MOVING_PIECE_Z);
+        } else {
+            transform.translation = animation.to;
+            commands.entity(entity).remove::<PieceAnimation>();
+        }
+    }
+}
+
+pub fn fade_out_pieces(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<ChessConfig>,
+    settings: Res<Settings>,
+    mut fade_q: Query<(Entity, &mut FadeOut, &mut TextureAtlasSprite)>,
+) {
+    for (entity, mut fade, mut sprite) in fade_q.iter_mut() {
+        fade.elapsed += time.delta_seconds();
+        let t = fade.elapsed / settings.animation_duration(&config);
+        if t < 1. {
+            sprite.color.set_a(1. - t);
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+}
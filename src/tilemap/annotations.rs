@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::config::{ChessConfig, TEXTURE_TILE_SIZE};
+use crate::game::annotations::{viewed_ply_index, BoardAnnotations};
+use crate::game::board::BoardHistory;
+use crate::game::history::ViewedPly;
+use crate::rules::annotations::AnnotationColour;
+use crate::rules::BoardPos;
+use crate::tilemap::arrows::{spawn_arrow, ArrowHeadMesh};
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::orientation::BoardOrientation;
+use crate::tilemap::pieces::tile_translation;
+use crate::tilemap::GameEntity;
+
+// marked squares go under the pieces, arrows over them and over the last move arrow
+const ANNOTATED_SQUARE_Z: f32 = 0.6;
+const ANNOTATION_ARROW_Z: f32 = 1.6;
+
+#[derive(Component)]
+pub struct AnnotationMark;
+
+fn annotation_colour(colour: AnnotationColour, alpha: f32) -> Color {
+    match colour {
+        AnnotationColour::Green => Color::rgba(0.1, 0.6, 0.2, alpha),
+        AnnotationColour::Red => Color::rgba(0.8, 0.1, 0.1, alpha),
+        AnnotationColour::Yellow => Color::rgba(0.9, 0.7, 0.0, alpha),
+        AnnotationColour::Blue => Color::rgba(0.1, 0.4, 0.8, alpha),
+    }
+}
+
+// shows what's been drawn on the position that's on the board
+#[allow(clippy::too_many_arguments)]
+pub fn draw_annotations(
+    mut commands: Commands,
+    config: Res<ChessConfig>,
+    annotations: Res<BoardAnnotations>,
+    history: Res<BoardHistory>,
+    viewed_ply: Res<ViewedPly>,
+    orientation: Res<BoardOrientation>,
+    head_mesh: Res<ArrowHeadMesh>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    tilemap_q: Query<(&TilemapGridSize, &TilemapType, &Transform), With<BoardTilemap>>,
+    mark_q: Query<Entity, With<AnnotationMark>>,
+) {
+    if !annotations.is_changed()
+        && !history.is_changed()
+        && !viewed_ply.is_changed()
+        && !orientation.is_changed()
+    {
+        return;
+    }
+    let (grid_size, map_type, tilemap_transform) = match tilemap_q.get_single() {
+        Ok(tilemap) => tilemap,
+        Err(_) => return,
+    };
+
+    for entity in mark_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let annotations = match annotations.get(viewed_ply_index(&viewed_ply, &history)) {
+        Some(annotations) => annotations,
+        None => return,
+    };
+    let translation = |square: BoardPos, z: f32| {
+        tile_translation(
+            &TilePos::new(square.x, square.y),
+            grid_size,
+            map_type,
+            tilemap_transform,
+        )
+        .truncate()
+        .extend(z)
+    };
+
+    for (square, colour) in annotations.squares.iter() {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: annotation_colour(*colour, 0.6),
+                    custom_size: Some(Vec2::splat(TEXTURE_TILE_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(translation(*square, ANNOTATED_SQUARE_Z))
+                    .with_scale(tilemap_transform.scale),
+                ..default()
+            },
+            AnnotationMark,
+            GameEntity,
+        ));
+    }
+
+    for (arrow, colour) in annotations.arrows.iter() {
+        spawn_arrow(
+            &mut commands,
+            &head_mesh,
+            &mut materials,
+            translation(arrow.from(), ANNOTATION_ARROW_Z),
+            translation(arrow.to(), ANNOTATION_ARROW_Z),
+            config.tile_size,
+            annotation_colour(*colour, 0.8),
+        )
+        .insert((AnnotationMark, GameEntity));
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+
+// relative to the tile size
+const ARROW_WIDTH: f32 = 0.15;
+const ARROW_HEAD_RADIUS: f32 = 0.3;
+
+#[derive(Resource)]
+pub struct ArrowHeadMesh(Handle<Mesh>);
+
+impl FromWorld for ArrowHeadMesh {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        Self(meshes.add(shape::RegularPolygon::new(1., 3).into()))
+    }
+}
+
+// spawns an arrow pointing from one point to another, both in world coordinates.
+// the returned entity is the parent of the arrow's shaft and head
+pub fn spawn_arrow<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    head_mesh: &ArrowHeadMesh,
+    materials: &mut Assets<ColorMaterial>,
+    from: Vec3,
+    to: Vec3,
+    tile_size: f32,
+    colour: Color,
+) -> EntityCommands<'w, 's, 'a> {
+    let direction = (to - from).truncate();
+    let length = direction.length();
+    let head_radius = ARROW_HEAD_RADIUS * tile_size;
+    // a triangle's base is half its radius below the centre
+    let shaft_length = (length - 1.5 * head_radius).max(0.);
+
+    let mut arrow = commands.spawn(SpatialBundle {
+        transform: Transform {
+            translation: from,
+            rotation: Quat::from_rotation_z(direction.y.atan2(direction.x)),
+            ..default()
+        },
+        ..default()
+    });
+    arrow.with_children(|parent| {
+        parent.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: colour,
+                custom_size: Some(Vec2::new(shaft_length, ARROW_WIDTH * tile_size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(shaft_length / 2., 0., 0.),
+            ..default()
+        });
+        // the triangle points up, it's turned to point along the arrow
+        parent.spawn(MaterialMesh2dBundle {
+            mesh: head_mesh.0.clone().into(),
+            material: materials.add(ColorMaterial::from(colour)),
+            transform: Transform {
+                translation: Vec3::new(length - head_radius, 0., 0.),
+                rotation: Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2),
+                scale: Vec3::splat(head_radius),
+            },
+            ..default()
+        });
+    });
+
+    arrow
+}
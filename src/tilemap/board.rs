@@ -1,14 +1,30 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
+use crate::config::{ChessConfig, TEXTURE_TILE_SIZE};
+use crate::game::board::ChessBoard;
+use crate::theme::CurrentTheme;
+use crate::tilemap::orientation::BoardOrientation;
+use crate::tilemap::GameEntity;
+
 #[derive(Component)]
 pub struct BoardTilemap;
 
-pub fn create_board_tilemap(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let texture_handle: Handle<Image> = asset_server.load("chessboard_tiles_96px.png");
+pub fn create_board_tilemap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<ChessConfig>,
+    theme: Res<CurrentTheme>,
+    orientation: Res<BoardOrientation>,
+    board: Res<ChessBoard>,
+) {
+    let texture_handle: Handle<Image> = asset_server.load(theme.0.board_tiles.as_str());
 
-    let map_size = TilemapSize { x: 8, y: 8 };
-    let tilemap_entity = commands.spawn(BoardTilemap).id();
+    let map_size = TilemapSize {
+        x: board.width(),
+        y: board.height(),
+    };
+    let tilemap_entity = commands.spawn((BoardTilemap, GameEntity)).id();
     let mut tile_storage = TileStorage::empty(map_size);
 
     for x in 0..map_size.x {
@@ -16,18 +32,24 @@ pub fn create_board_tilemap(mut commands: Commands, asset_server: Res<AssetServe
             let is_either_odd = !((x % 2 == 0) ^ (y % 2 == 0));
             let tile_pos = TilePos { x, y };
             let tile_entity = commands
-                .spawn(TileBundle {
-                    position: tile_pos,
-                    tilemap_id: TilemapId(tilemap_entity),
-                    texture_index: TileTextureIndex(is_either_odd.into()),
-                    ..default()
-                })
+                .spawn((
+                    TileBundle {
+                        position: tile_pos,
+                        tilemap_id: TilemapId(tilemap_entity),
+                        texture_index: TileTextureIndex(is_either_odd.into()),
+                        ..default()
+                    },
+                    GameEntity,
+                ))
                 .id();
             tile_storage.set(&tile_pos, tile_entity);
         }
     }
 
-    let tile_size = TilemapTileSize { x: 96.0, y: 96.0 };
+    let tile_size = TilemapTileSize {
+        x: TEXTURE_TILE_SIZE,
+        y: TEXTURE_TILE_SIZE,
+    };
     let grid_size = tile_size.into();
     let map_type = TilemapType::default();
 
@@ -38,7 +60,19 @@ pub fn create_board_tilemap(mut commands: Commands, asset_server: Res<AssetServe
         storage: tile_storage,
         texture: TilemapTexture::Single(texture_handle),
         tile_size,
-        transform: get_tilemap_center_transform(&map_size, &grid_size, &map_type, 0.0),
+        transform: orientation.tilemap_transform(&config, &map_size, &grid_size, &map_type),
         ..default()
     });
 }
+
+pub fn update_board_texture(
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    mut tilemap_q: Query<&mut TilemapTexture, With<BoardTilemap>>,
+) {
+    if theme.is_changed() {
+        for mut texture in tilemap_q.iter_mut() {
+            *texture = TilemapTexture::Single(asset_server.load(theme.0.board_tiles.as_str()));
+        }
+    }
+}
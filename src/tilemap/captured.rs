@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::config::TEXTURE_TILE_SIZE;
+
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::history::ViewedPly;
+use crate::rules::pieces::{ChessPiece, ChessPieceColour};
+use crate::theme::CurrentTheme;
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::orientation::{BoardAnchor, BoardOrientation};
+use crate::tilemap::pieces::{piece_texture, ChessPieceAtlas};
+use crate::tilemap::GameEntity;
+
+// the captured pieces are much smaller than the ones on the board, and overlap a bit
+const CAPTURED_PIECE_SCALE: f32 = 0.3;
+const CAPTURED_PIECE_STEP: f32 = 8.;
+// pieces of different kinds are spaced out a bit more
+const CAPTURED_KIND_STEP: f32 = 16.;
+
+// the pieces a side has taken, drawn on that side of the board
+#[derive(Component)]
+pub struct CapturedTray(ChessPieceColour);
+
+pub fn spawn_captured_trays(
+    mut commands: Commands,
+    orientation: Res<BoardOrientation>,
+    board: Res<ChessBoard>,
+    tilemap_q: Query<
+        (&TilemapSize, &TilemapGridSize, &TilemapType, &Transform),
+        With<BoardTilemap>,
+    >,
+) {
+    let (map_size, grid_size, map_type, tilemap_transform) = tilemap_q.single();
+
+    // between the turn indicators and the middle of the board
+    for (colour, tile_pos, y_offset) in [
+        (ChessPieceColour::White, TilePos { x: 0, y: 0 }, 80.),
+        (
+            ChessPieceColour::Black,
+            TilePos {
+                x: 0,
+                y: board.height() - 1,
+            },
+            -80.,
+        ),
+    ] {
+        let tile_center = tile_pos.center_in_world(grid_size, map_type);
+        let anchor = BoardAnchor::keeping_side(Vec3::new(
+            tile_center.x - 330.,
+            tile_center.y + y_offset,
+            2.0,
+        ));
+        let transform = anchor.transform(&orientation, map_size, grid_size, tilemap_transform);
+
+        commands.spawn((
+            SpatialBundle::from_transform(transform),
+            CapturedTray(colour),
+            anchor,
+            GameEntity,
+        ));
+    }
+}
+
+// fills the trays with the pieces missing from the position on the board, followed by how far
+// ahead the side is
+#[allow(clippy::too_many_arguments)]
+pub fn draw_captured_pieces(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    atlas: Res<ChessPieceAtlas>,
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    viewed_ply: Res<ViewedPly>,
+    tray_q: Query<(Entity, &CapturedTray)>,
+    added_q: Query<(), Added<CapturedTray>>,
+) {
+    if !board.is_changed() && !viewed_ply.is_changed() && !theme.is_changed() && added_q.is_empty()
+    {
+        return;
+    }
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+    let shown = viewed_ply.board(&board, &history);
+    let start = history.0.first().unwrap_or(&board);
+
+    for (tray_entity, tray) in tray_q.iter() {
+        let mut tray_commands = commands.entity(tray_entity);
+        tray_commands.despawn_descendants();
+
+        let mut x = 0.;
+        let mut previous = None;
+        tray_commands.with_children(|parent| {
+            for kind in shown.captured_pieces(start, !tray.0) {
+                if let Some(last) = previous {
+                    x += match last == kind {
+                        true => CAPTURED_PIECE_STEP,
+                        false => CAPTURED_KIND_STEP,
+                    };
+                }
+                previous = Some(kind);
+
+                let piece = ChessPiece::new(0, 0, !tray.0, kind);
+                if let Some(texture) = piece_texture(&theme.0, shown, &piece) {
+                    parent.spawn(SpriteSheetBundle {
+                        sprite: TextureAtlasSprite::new(texture as usize),
+                        texture_atlas: atlas.0.clone(),
+                        transform: Transform::from_xyz(x, 0., x / 1000.).with_scale(Vec3::splat(
+                            CAPTURED_PIECE_SCALE * TEXTURE_TILE_SIZE / theme.0.pieces.tile_size,
+                        )),
+                        ..default()
+                    });
+                }
+            }
+
+            let balance = shown.material_balance(tray.0);
+            if balance > 0 {
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        format!("+{}", balance),
+                        TextStyle {
+                            font: font_handle.clone(),
+                            font_size: 36.,
+                            color: theme.0.colours.labels,
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER_LEFT),
+                    transform: Transform::from_xyz(x + 24., 0., 0.),
+                    ..default()
+                });
+            }
+        });
+    }
+}
@@ -1,28 +1,41 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
+use crate::config::ChessConfig;
 use crate::game::CheckEvent;
+use crate::theme::CurrentTheme;
 use crate::tilemap::board;
+use crate::tilemap::orientation::{BoardAnchor, BoardOrientation};
+use crate::tilemap::GameEntity;
 
 #[derive(Resource)]
 pub struct CheckedTileHandle(Handle<Image>);
 
 impl FromWorld for CheckedTileHandle {
     fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        Self(asset_server.load("checked_tile.png"))
+        let path = world.resource::<ChessConfig>().assets.checked_tile.clone();
+        Self(world.resource::<AssetServer>().load(path))
     }
 }
 
 #[derive(Component)]
 pub struct CheckedTile(Entity);
 
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_checked_tile(
     mut commands: Commands,
     checked_tile_handle: Res<CheckedTileHandle>,
+    theme: Res<CurrentTheme>,
+    orientation: Res<BoardOrientation>,
     mut check_ev: EventReader<CheckEvent>,
     tilemap_q: Query<
-        (&TilemapGridSize, &TilemapType, &TileStorage, &Transform),
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapType,
+            &TileStorage,
+            &Transform,
+        ),
         With<board::BoardTilemap>,
     >,
     tiles_q: Query<Entity, With<CheckedTile>>,
@@ -36,19 +49,28 @@ pub fn spawn_checked_tile(
             }
         }
 
-        let (grid_size, map_type, tile_storage, map_transform) = tilemap_q.single();
+        let (map_size, grid_size, map_type, tile_storage, map_transform) = tilemap_q.single();
 
         if let Some(check) = ev.0 {
             if let Some(tile_entity) = tile_storage.get(&check) {
                 let tile_center = check.center_in_world(grid_size, map_type).extend(0.5);
-                let transform = *map_transform * Transform::from_translation(tile_center);
+                let anchor = BoardAnchor::new(tile_center);
+                let transform = anchor.transform(&orientation, map_size, grid_size, map_transform);
 
                 let checked_tile = commands
-                    .spawn(SpriteBundle {
-                        texture: checked_tile_handle.0.clone(),
-                        transform,
-                        ..default()
-                    })
+                    .spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: theme.0.colours.check_indicator,
+                                ..default()
+                            },
+                            texture: checked_tile_handle.0.clone(),
+                            transform,
+                            ..default()
+                        },
+                        anchor,
+                        GameEntity,
+                    ))
                     .id();
 
                 commands
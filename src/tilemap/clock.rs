@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::game::board::ChessBoard;
+use crate::game::clock::GameClock;
+use crate::rules::pieces::ChessPieceColour;
+use crate::theme::CurrentTheme;
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::orientation::{BoardAnchor, BoardOrientation};
+use crate::tilemap::GameEntity;
+
+#[derive(Component)]
+pub struct ClockDisplay(ChessPieceColour);
+
+pub fn spawn_clocks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    orientation: Res<BoardOrientation>,
+    board: Res<ChessBoard>,
+    clock: Res<GameClock>,
+    tilemap_q: Query<
+        (&TilemapSize, &TilemapGridSize, &TilemapType, &Transform),
+        With<BoardTilemap>,
+    >,
+) {
+    if clock.time_control.is_none() {
+        return;
+    }
+
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+    let (map_size, grid_size, map_type, tilemap_transform) = tilemap_q.single();
+
+    // the clocks go right next to the turn indicators, away from the middle of the board
+    for (colour, tile_pos, y_offset) in [
+        (ChessPieceColour::White, TilePos { x: 0, y: 0 }, -80.),
+        (
+            ChessPieceColour::Black,
+            TilePos {
+                x: 0,
+                y: board.height() - 1,
+            },
+            80.,
+        ),
+    ] {
+        let tile_center = tile_pos.center_in_world(grid_size, map_type);
+        let anchor = BoardAnchor::keeping_side(Vec3::new(
+            tile_center.x - 256.,
+            tile_center.y + y_offset,
+            2.0,
+        ));
+        let transform = anchor.transform(&orientation, map_size, grid_size, tilemap_transform);
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format_duration(clock.remaining(colour)),
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 56.,
+                        color: theme.0.colours.clock,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER_LEFT),
+                transform,
+                ..default()
+            },
+            ClockDisplay(colour),
+            anchor,
+            GameEntity,
+        ));
+    }
+}
+
+pub fn draw_clocks(
+    clock: Res<GameClock>,
+    theme: Res<CurrentTheme>,
+    mut clock_q: Query<(&ClockDisplay, &mut Text)>,
+) {
+    if clock.is_changed() || theme.is_changed() {
+        for (display, mut text) in clock_q.iter_mut() {
+            let section = &mut text.sections[0];
+            section.value = format_duration(clock.remaining(display.0));
+            section.style.color = match clock.is_flagged(display.0) {
+                true => theme.0.colours.flagged_clock,
+                false => theme.0.colours.clock,
+            };
+        }
+    }
+}
+
+// m:ss, with tenths of a second once there's less than 10 seconds left
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 10 {
+        format!("0:0{}.{}", seconds, duration.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::game::DragEvent;
+use crate::tilemap::animation::PieceAnimation;
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::pieces::{tile_translation, PieceSprite};
+use crate::utils::cursor::CursorPos;
+
+// above everything else on the board
+const DRAGGED_PIECE_Z: f32 = 10.0;
+
+// the piece following the cursor
+#[derive(Component)]
+pub struct DraggedPiece;
+
+pub fn show_dragged_piece(
+    mut commands: Commands,
+    mut drag_ev: EventReader<DragEvent>,
+    tilemap_q: Query<(&TilemapGridSize, &TilemapType, &Transform), With<BoardTilemap>>,
+    piece_q: Query<(Entity, &PieceSprite, &Transform, Option<&DraggedPiece>)>,
+) {
+    for ev in drag_ev.iter() {
+        match ev {
+            DragEvent::Lift(tile_pos) => {
+                for (entity, sprite, _, _) in piece_q.iter() {
+                    if &sprite.pos == tile_pos {
+                        commands
+                            .entity(entity)
+                            .remove::<PieceAnimation>()
+                            .insert(DraggedPiece);
+                    }
+                }
+            }
+            // the piece slides back to its tile, if it was dropped on another one
+            // that'll be overridden once the move has been made
+            DragEvent::Drop => {
+                let (grid_size, map_type, tilemap_transform) = match tilemap_q.get_single() {
+                    Ok(tilemap) => tilemap,
+                    Err(_) => continue,
+                };
+                for (entity, sprite, transform, dragged) in piece_q.iter() {
+                    if dragged.is_some() {
+                        let to =
+                            tile_translation(&sprite.pos, grid_size, map_type, tilemap_transform);
+                        commands
+                            .entity(entity)
+                            .remove::<DraggedPiece>()
+                            .insert(PieceAnimation::new(transform.translation, to));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn follow_cursor(
+    cursor_pos: Res<CursorPos>,
+    mut dragged_q: Query<&mut Transform, With<DraggedPiece>>,
+) {
+    for mut transform in dragged_q.iter_mut() {
+        transform.translation = cursor_pos.0.truncate().extend(DRAGGED_PIECE_Z);
+    }
+}
@@ -2,6 +2,9 @@ use bevy::math::Vec4Swizzles;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
+use crate::config::ChessConfig;
+use crate::game::keyboard::KeyboardCursor;
+use crate::theme::CurrentTheme;
 use crate::tilemap::board;
 use crate::utils::cursor;
 
@@ -13,13 +16,19 @@ pub struct HoverRing;
 pub fn spawn_hover_ring(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    config: Res<ChessConfig>,
+    theme: Res<CurrentTheme>,
     tilemap: Query<Entity, With<board::BoardTilemap>>,
 ) {
-    let texture_handle: Handle<Image> = asset_server.load("hover_ring.png");
+    let texture_handle: Handle<Image> = asset_server.load(config.assets.hover_ring.as_str());
 
     let ring = commands
         .spawn((
             SpriteBundle {
+                sprite: Sprite {
+                    color: theme.0.colours.hover_ring,
+                    ..default()
+                },
                 visibility: Visibility::INVISIBLE,
                 texture: texture_handle,
                 transform: Transform {
@@ -36,12 +45,24 @@ pub fn spawn_hover_ring(
     commands.entity(tilemap).push_children(&[ring]);
 }
 
+pub fn restyle_hover_ring(
+    theme: Res<CurrentTheme>,
+    mut sprite_q: Query<&mut Sprite, With<HoverRing>>,
+) {
+    if theme.is_changed() {
+        for mut sprite in sprite_q.iter_mut() {
+            sprite.color = theme.0.colours.hover_ring;
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct HasHoverRing;
 
 pub fn show_hover_ring(
     mut commands: Commands,
     cursor_pos: Res<cursor::CursorPos>,
+    keyboard_cursor: Res<KeyboardCursor>,
     tilemap_q: Query<
         (
             &TilemapSize,
@@ -55,7 +76,12 @@ pub fn show_hover_ring(
     hovered_tiles_q: Query<Entity, With<HasHoverRing>>,
     mut hover_ring_q: Query<(&mut Transform, &mut Visibility), With<HoverRing>>,
 ) {
-    if cursor_pos.is_changed() {
+    // the ring goes to whichever of the mouse and the keyboard cursor moved last
+    let keyboard_tile = keyboard_cursor
+        .is_changed()
+        .then_some(keyboard_cursor.0)
+        .flatten();
+    if cursor_pos.is_changed() || keyboard_tile.is_some() {
         for hovered_tile_entity in hovered_tiles_q.iter() {
             let (_, mut hover_ring_visibility) = hover_ring_q.single_mut();
             hover_ring_visibility.is_visible = false;
@@ -72,9 +98,9 @@ pub fn show_hover_ring(
                 cursor_in_map_pos.xy()
             };
 
-            if let Some(tile_pos) =
+            if let Some(tile_pos) = keyboard_tile.or_else(|| {
                 TilePos::from_world_pos(&cursor_in_map_pos, map_size, grid_size, map_type)
-            {
+            }) {
                 if let Some(tile_entity) = tile_storage.get(&tile_pos) {
                     let tile_center = tile_pos
                         .center_in_world(grid_size, map_type)
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::config::ChessConfig;
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::history::ViewedPly;
+use crate::settings::Settings;
+use crate::theme::CurrentTheme;
+use crate::tilemap::arrows::{spawn_arrow, ArrowHeadMesh};
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::orientation::BoardOrientation;
+use crate::tilemap::pieces::tile_translation;
+use crate::tilemap::GameEntity;
+
+// above the pieces, below the move indicators
+const LAST_MOVE_ARROW_Z: f32 = 1.5;
+
+#[derive(Component)]
+pub struct LastMoveArrow;
+
+// shows the move that led to the position on the board, which is an earlier one while
+// looking through the history
+#[allow(clippy::too_many_arguments)]
+pub fn draw_last_move(
+    mut commands: Commands,
+    config: Res<ChessConfig>,
+    settings: Res<Settings>,
+    theme: Res<CurrentTheme>,
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    viewed_ply: Res<ViewedPly>,
+    orientation: Res<BoardOrientation>,
+    head_mesh: Res<ArrowHeadMesh>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    tilemap_q: Query<
+        (&TilemapGridSize, &TilemapType, &Transform, &TileStorage),
+        With<BoardTilemap>,
+    >,
+    mut tile_colour_q: Query<&mut TileColor>,
+    arrow_q: Query<Entity, With<LastMoveArrow>>,
+) {
+    if !board.is_changed()
+        && !viewed_ply.is_changed()
+        && !settings.is_changed()
+        && !orientation.is_changed()
+        && !theme.is_changed()
+    {
+        return;
+    }
+    let (grid_size, map_type, tilemap_transform, tile_storage) = match tilemap_q.get_single() {
+        Ok(tilemap) => tilemap,
+        Err(_) => return,
+    };
+
+    for entity in arrow_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for tile_entity in tile_storage.iter().flatten() {
+        if let Ok(mut colour) = tile_colour_q.get_mut(*tile_entity) {
+            *colour = TileColor::default();
+        }
+    }
+
+    let last_move = match viewed_ply.board(&board, &history).last_move() {
+        Some(last_move) => last_move,
+        None => return,
+    };
+    let from = TilePos::new(last_move.from().x, last_move.from().y);
+    let to = TilePos::new(last_move.to().x, last_move.to().y);
+
+    if settings.highlight_last_move {
+        for tile_pos in [from, to] {
+            if let Some(tile_entity) = tile_storage.get(&tile_pos) {
+                if let Ok(mut colour) = tile_colour_q.get_mut(tile_entity) {
+                    *colour = theme.0.colours.last_move.into();
+                }
+            }
+        }
+    }
+
+    if settings.last_move_arrow {
+        let [from, to] = [from, to].map(|tile_pos| {
+            tile_translation(&tile_pos, grid_size, map_type, tilemap_transform)
+                .truncate()
+                .extend(LAST_MOVE_ARROW_Z)
+        });
+        spawn_arrow(
+            &mut commands,
+            &head_mesh,
+            &mut materials,
+            from,
+            to,
+            config.tile_size,
+            theme.0.colours.last_move_arrow,
+        )
+        .insert((LastMoveArrow, GameEntity));
+    }
+}
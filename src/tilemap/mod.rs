@@ -1,7 +1,30 @@
+use bevy::prelude::*;
+
+pub mod animation;
+pub mod annotations;
+pub mod arrows;
 pub mod board;
+pub mod captured;
 pub mod checked_tile;
+pub mod clock;
+pub mod drag;
 pub mod hover;
+pub mod last_move;
 pub mod move_indicators;
+pub mod orientation;
 pub mod pieces;
 pub mod ranks_and_files;
 pub mod turn_indicator;
+
+// everything that belongs to a single game, despawned when a new game starts
+#[derive(Component)]
+pub struct GameEntity;
+
+pub fn despawn_game_entities(
+    mut commands: Commands,
+    game_entity_q: Query<Entity, With<GameEntity>>,
+) {
+    for entity in game_entity_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
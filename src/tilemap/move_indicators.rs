@@ -1,8 +1,13 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
-use crate::game::{BoardClickEvent, Move};
+use crate::config::ChessConfig;
+use crate::game::BoardClickEvent;
+use crate::rules::Move;
+use crate::theme::CurrentTheme;
 use crate::tilemap::board;
+use crate::tilemap::orientation::{BoardAnchor, BoardOrientation};
+use crate::tilemap::GameEntity;
 
 const MOVE_INDICATOR_Z: f32 = 2.0;
 
@@ -14,15 +19,23 @@ pub struct TakeIndicatorHandle(Handle<Image>);
 
 impl FromWorld for MoveIndicatorHandle {
     fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        Self(asset_server.load("move_indicator.png"))
+        let path = world
+            .resource::<ChessConfig>()
+            .assets
+            .move_indicator
+            .clone();
+        Self(world.resource::<AssetServer>().load(path))
     }
 }
 
 impl FromWorld for TakeIndicatorHandle {
     fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        Self(asset_server.load("take_indicator.png"))
+        let path = world
+            .resource::<ChessConfig>()
+            .assets
+            .take_indicator
+            .clone();
+        Self(world.resource::<AssetServer>().load(path))
     }
 }
 
@@ -37,9 +50,17 @@ pub fn spawn_move_indicators(
     mut commands: Commands,
     move_indicator_handle: Res<MoveIndicatorHandle>,
     take_indicator_handle: Res<TakeIndicatorHandle>,
+    theme: Res<CurrentTheme>,
+    orientation: Res<BoardOrientation>,
     mut click_ev: EventReader<BoardClickEvent>,
     tilemap_q: Query<
-        (&TilemapGridSize, &TilemapType, &TileStorage, &Transform),
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapType,
+            &TileStorage,
+            &Transform,
+        ),
         With<board::BoardTilemap>,
     >,
     tiles_w_indicator_q: Query<Entity, With<MoveIndicator>>,
@@ -57,10 +78,13 @@ pub fn spawn_move_indicators(
             commands.entity(tile_entity).remove::<SelectedTile>();
         }
 
-        let (grid_size, map_type, tile_storage, map_transform) = tilemap_q.single();
+        let (map_size, grid_size, map_type, tile_storage, map_transform) = tilemap_q.single();
 
-        if let Some(tile_entity) = tile_storage.get(&ev.tile) {
-            commands.entity(tile_entity).insert(SelectedTile);
+        // events without moves only clear the selection
+        if ev.moves.is_some() {
+            if let Some(tile_entity) = tile_storage.get(&ev.tile) {
+                commands.entity(tile_entity).insert(SelectedTile);
+            }
         }
 
         if let Some(moves) = &ev.moves {
@@ -69,20 +93,33 @@ pub fn spawn_move_indicators(
                     let tile_center = <&Move as Into<TilePos>>::into(_move)
                         .center_in_world(grid_size, map_type)
                         .extend(MOVE_INDICATOR_Z);
-                    let transform = *map_transform * Transform::from_translation(tile_center);
+                    let anchor = BoardAnchor::new(tile_center);
+                    let transform =
+                        anchor.transform(&orientation, map_size, grid_size, map_transform);
 
-                    let texture = if _move.takes {
-                        take_indicator_handle.0.clone()
+                    let (texture, color) = if _move.takes {
+                        (
+                            take_indicator_handle.0.clone(),
+                            theme.0.colours.capture_indicator,
+                        )
                     } else {
-                        move_indicator_handle.0.clone()
+                        (
+                            move_indicator_handle.0.clone(),
+                            theme.0.colours.move_indicator,
+                        )
                     };
 
                     let move_indicator = commands
-                        .spawn(SpriteBundle {
-                            texture,
-                            transform,
-                            ..default()
-                        })
+                        .spawn((
+                            SpriteBundle {
+                                sprite: Sprite { color, ..default() },
+                                texture,
+                                transform,
+                                ..default()
+                            },
+                            anchor,
+                            GameEntity,
+                        ))
                         .id();
 
                     commands
@@ -0,0 +1,222 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::config::ChessConfig;
+use crate::game::state::{GameSetup, Opponent};
+use crate::rules::pieces::ChessPieceColour;
+use crate::settings::{PreferredOrientation, Settings};
+use crate::tilemap::animation::PieceAnimation;
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::drag::DraggedPiece;
+use crate::tilemap::pieces::{tile_translation, PieceSprite};
+
+// the side that's drawn at the bottom of the board
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BoardOrientation(pub ChessPieceColour);
+
+impl Default for BoardOrientation {
+    fn default() -> Self {
+        Self(ChessPieceColour::White)
+    }
+}
+
+impl BoardOrientation {
+    pub fn is_flipped(&self) -> bool {
+        self.0 == ChessPieceColour::Black
+    }
+
+    // the board tilemap's transform, turned half way around when black is at the bottom. since
+    // input goes through the inverse of this transform, clicks on the flipped board land on the
+    // right tiles without any extra mapping
+    pub fn tilemap_transform(
+        &self,
+        config: &ChessConfig,
+        map_size: &TilemapSize,
+        grid_size: &TilemapGridSize,
+        map_type: &TilemapType,
+    ) -> Transform {
+        let mut transform = config.tilemap_transform(map_size, grid_size, map_type, 0.0);
+        if self.is_flipped() {
+            // around the middle of the tiles, which is a little off the board position
+            let centre = Vec3::new(
+                (map_size.x - 1) as f32 * grid_size.x / 2.,
+                (map_size.y - 1) as f32 * grid_size.y / 2.,
+                0.0,
+            );
+            transform.rotate_around(transform.transform_point(centre), Quat::from_rotation_z(PI));
+        }
+        transform
+    }
+}
+
+// places an entity next to a tile of the board, following it around when the board is flipped
+// while staying upright
+#[derive(Component, Debug, Copy, Clone)]
+pub struct BoardAnchor {
+    // in the board tilemap's frame
+    translation: Vec3,
+    // mirrored horizontally on a flipped board, so that it stays on the same side of the screen
+    keep_side: bool,
+}
+
+impl BoardAnchor {
+    pub fn new(translation: Vec3) -> Self {
+        Self {
+            translation,
+            keep_side: false,
+        }
+    }
+
+    pub fn keeping_side(translation: Vec3) -> Self {
+        Self {
+            translation,
+            keep_side: true,
+        }
+    }
+
+    pub fn transform(
+        &self,
+        orientation: &BoardOrientation,
+        map_size: &TilemapSize,
+        grid_size: &TilemapGridSize,
+        tilemap_transform: &Transform,
+    ) -> Transform {
+        let mut translation = self.translation;
+        if self.keep_side && orientation.is_flipped() {
+            translation.x = (map_size.x - 1) as f32 * grid_size.x - translation.x;
+        }
+        let mut transform = *tilemap_transform * Transform::from_translation(translation);
+        transform.rotation = Quat::IDENTITY;
+        transform
+    }
+}
+
+// puts the side picked in the settings at the bottom, by default the human's side when playing
+// against the computer or over the network. spectators see it like a game between humans
+pub fn orient_for_player(
+    setup: Res<GameSetup>,
+    settings: Res<Settings>,
+    mut orientation: ResMut<BoardOrientation>,
+) {
+    let colour = match (settings.orientation, setup.opponent) {
+        (PreferredOrientation::Player, Opponent::Computer(colour) | Opponent::Remote(colour)) => {
+            !colour
+        }
+        (PreferredOrientation::Player, Opponent::Human | Opponent::Spectator) => return,
+        (PreferredOrientation::White, _) => ChessPieceColour::White,
+        (PreferredOrientation::Black, _) => ChessPieceColour::Black,
+    };
+    if orientation.0 != colour {
+        orientation.0 = colour;
+    }
+}
+
+pub fn flip_board(mut keys: ResMut<Input<KeyCode>>, mut orientation: ResMut<BoardOrientation>) {
+    if keys.just_pressed(KeyCode::F) {
+        orientation.0 = !orientation.0;
+        keys.reset(KeyCode::F);
+    }
+}
+
+// turns the board around, taking everything that's drawn on it along
+#[allow(clippy::type_complexity)]
+pub fn orient_board(
+    mut commands: Commands,
+    config: Res<ChessConfig>,
+    orientation: Res<BoardOrientation>,
+    mut tilemap_q: Query<
+        (&TilemapSize, &TilemapGridSize, &TilemapType, &mut Transform),
+        With<BoardTilemap>,
+    >,
+    mut anchored_q: Query<(&BoardAnchor, &mut Transform), Without<BoardTilemap>>,
+    piece_q: Query<
+        (Entity, &PieceSprite, &Transform),
+        (
+            Without<BoardTilemap>,
+            Without<BoardAnchor>,
+            Without<DraggedPiece>,
+        ),
+    >,
+) {
+    if !orientation.is_changed() {
+        return;
+    }
+    let (map_size, grid_size, map_type, mut tilemap_transform) = match tilemap_q.get_single_mut() {
+        Ok(tilemap) => tilemap,
+        Err(_) => return,
+    };
+
+    *tilemap_transform = orientation.tilemap_transform(&config, map_size, grid_size, map_type);
+
+    for (anchor, mut transform) in anchored_q.iter_mut() {
+        *transform = anchor.transform(&orientation, map_size, grid_size, &tilemap_transform);
+    }
+
+    // the pieces fly over to their new places
+    for (entity, sprite, transform) in piece_q.iter() {
+        let to = tile_translation(&sprite.pos, grid_size, map_type, &tilemap_transform);
+        commands
+            .entity(entity)
+            .insert(PieceAnimation::new(transform.translation, to));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy_ecs_tilemap::prelude::*;
+
+    use crate::config::ChessConfig;
+    use crate::rules::pieces::ChessPieceColour;
+    use crate::tilemap::orientation::{BoardAnchor, BoardOrientation};
+
+    const MAP_SIZE: TilemapSize = TilemapSize { x: 8, y: 8 };
+    const GRID_SIZE: TilemapGridSize = TilemapGridSize { x: 96., y: 96. };
+
+    fn tile_world_pos(orientation: BoardOrientation, tile_pos: TilePos) -> Vec2 {
+        let config = ChessConfig {
+            board_position: Vec2::new(10., -20.),
+            ..default()
+        };
+        let map_type = TilemapType::default();
+        let transform = orientation.tilemap_transform(&config, &MAP_SIZE, &GRID_SIZE, &map_type);
+        transform
+            .transform_point(tile_pos.center_in_world(&GRID_SIZE, &map_type).extend(0.))
+            .truncate()
+    }
+
+    #[test]
+    fn flipped_board_is_turned_around() {
+        let white = BoardOrientation(ChessPieceColour::White);
+        let black = BoardOrientation(ChessPieceColour::Black);
+        for (tile_pos, opposite) in [
+            (TilePos::new(0, 0), TilePos::new(7, 7)),
+            (TilePos::new(4, 1), TilePos::new(3, 6)),
+        ] {
+            let flipped = tile_world_pos(black, tile_pos);
+            let expected = tile_world_pos(white, opposite);
+            assert!(flipped.abs_diff_eq(expected, 1e-3), "{flipped} {expected}");
+        }
+    }
+
+    #[test]
+    fn anchors_stay_upright() {
+        let black = BoardOrientation(ChessPieceColour::Black);
+        let tilemap_transform =
+            black.tilemap_transform(&default(), &MAP_SIZE, &GRID_SIZE, &default());
+
+        let anchor = BoardAnchor::new(Vec3::new(-100., 0., 2.));
+        let transform = anchor.transform(&black, &MAP_SIZE, &GRID_SIZE, &tilemap_transform);
+        assert_eq!(Quat::IDENTITY, transform.rotation);
+        // left of the board in the tilemap's frame ends up on the right of the screen
+        assert!(transform.translation.x > 0.);
+
+        let anchor = BoardAnchor::keeping_side(Vec3::new(-100., 0., 2.));
+        let transform = anchor.transform(&black, &MAP_SIZE, &GRID_SIZE, &tilemap_transform);
+        assert!(transform.translation.x < 0.);
+        // while still swapping top and bottom
+        assert!(transform.translation.y > 0.);
+    }
+}
@@ -1,108 +1,202 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
-use crate::game::board::Board;
-use crate::game::pieces::{ChessPieceColour, ChessPieceKind};
-
-const WHITE_KING_TEXTURE: u8 = 0;
-const WHITE_QUEEN_TEXTURE: u8 = 1;
-const WHITE_BISHOP_TEXTURE: u8 = 2;
-const WHITE_KNIGHT_TEXTURE: u8 = 3;
-const WHITE_ROOK_TEXTURE: u8 = 4;
-const WHITE_PAWN_TEXTURE: u8 = 5;
-const BLACK_KING_TEXTURE: u8 = 6;
-const BLACK_QUEEN_TEXTURE: u8 = 7;
-const BLACK_BISHOP_TEXTURE: u8 = 8;
-const BLACK_KNIGHT_TEXTURE: u8 = 9;
-const BLACK_ROOK_TEXTURE: u8 = 10;
-const BLACK_PAWN_TEXTURE: u8 = 11;
+use crate::config::{ChessConfig, TEXTURE_TILE_SIZE};
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::history::ViewedPly;
+use crate::rules::board::Board;
+use crate::rules::pieces::{ChessPiece, ChessPieceKind};
+use crate::settings::Settings;
+use crate::theme::{CurrentTheme, PieceTextures, Theme};
+use crate::tilemap::animation::{FadeOut, PieceAnimation};
+use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::GameEntity;
 
+// the piece texture cut up into single pieces
 #[derive(Resource)]
-pub struct ChessPieceHandle(Handle<Image>);
+pub struct ChessPieceAtlas(pub Handle<TextureAtlas>);
 
-impl FromWorld for ChessPieceHandle {
+impl FromWorld for ChessPieceAtlas {
     fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        Self(asset_server.load("chess_pieces_96px.png"))
+        let textures = world.resource::<CurrentTheme>().0.pieces.clone();
+        let atlas = piece_atlas(world.resource::<AssetServer>(), &textures);
+        Self(world.resource_mut::<Assets<TextureAtlas>>().add(atlas))
     }
 }
 
+fn piece_atlas(asset_server: &AssetServer, textures: &PieceTextures) -> TextureAtlas {
+    TextureAtlas::from_grid(
+        asset_server.load(textures.texture.as_str()),
+        Vec2::splat(textures.tile_size),
+        textures.columns,
+        textures.rows,
+        None,
+        None,
+    )
+}
+
+// the scale that makes a piece from the theme's texture as big as a tile of the board
+pub fn piece_scale(theme: &Theme, tilemap_transform: &Transform) -> Vec3 {
+    tilemap_transform.scale * TEXTURE_TILE_SIZE / theme.pieces.tile_size
+}
+
+// cuts up the new theme's piece texture, the pieces are then redrawn with it by sync_pieces
+pub fn update_piece_atlas(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    mut atlas: ResMut<ChessPieceAtlas>,
+    piece_q: Query<Entity, With<PieceSprite>>,
+) {
+    if !theme.is_changed() || theme.is_added() {
+        return;
+    }
+    atlas.0 = atlases.add(piece_atlas(&asset_server, &theme.0.pieces));
+    for entity in piece_q.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// pieces are drawn just above the board
+const PIECE_Z: f32 = 1.0;
+
+// a piece on the board, moved around whenever the board changes
 #[derive(Component)]
-pub struct PieceTilemap;
+pub struct PieceSprite {
+    pub pos: TilePos,
+    texture: u8,
+}
+
+// where a piece on the given tile is drawn, in world coordinates
+pub fn tile_translation(
+    tile_pos: &TilePos,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    tilemap_transform: &Transform,
+) -> Vec3 {
+    let tile_center = tile_pos
+        .center_in_world(grid_size, map_type)
+        .extend(PIECE_Z);
+    tilemap_transform.transform_point(tile_center)
+}
 
-pub fn draw_piece_tilemap(
+// brings the piece sprites in line with the board. instead of redrawing everything, pieces that
+// are still around are moved to their new tiles, so that they can be animated getting there
+#[allow(clippy::too_many_arguments)]
+pub fn sync_pieces(
     mut commands: Commands,
-    chess_piece_handle: Res<ChessPieceHandle>,
-    tilemap_q: Query<Entity, With<PieceTilemap>>,
-    board: Res<Board>,
+    config: Res<ChessConfig>,
+    settings: Res<Settings>,
+    theme: Res<CurrentTheme>,
+    atlas: Res<ChessPieceAtlas>,
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    viewed_ply: Res<ViewedPly>,
+    tilemap_q: Query<(&TilemapGridSize, &TilemapType, &Transform), With<BoardTilemap>>,
+    mut piece_q: Query<(Entity, &mut PieceSprite, &Transform), Without<BoardTilemap>>,
 ) {
-    if board.is_changed() {
-        if let Ok(tilemap_entity) = tilemap_q.get_single() {
-            commands.entity(tilemap_entity).despawn();
+    if !board.is_changed() && !viewed_ply.is_changed() && !piece_q.is_empty() {
+        return;
+    }
+    let (grid_size, map_type, tilemap_transform) = match tilemap_q.get_single() {
+        Ok(tilemap) => tilemap,
+        Err(_) => return,
+    };
+    // while looking through the history, the board from back then is shown instead
+    let board = viewed_ply.board(&board, &history);
+
+    let mut targets: Vec<(TilePos, u8)> = board
+        .iter()
+        .flatten()
+        .filter_map(|piece| {
+            piece_texture(&theme.0, board, piece)
+                .map(|texture| (TilePos::new(piece.x, piece.y), texture))
+        })
+        .collect();
+
+    // pieces that haven't moved stay where they are
+    let mut unmatched = Vec::new();
+    for (entity, sprite, transform) in piece_q.iter_mut() {
+        match targets
+            .iter()
+            .position(|target| target == &(sprite.pos, sprite.texture))
+        {
+            Some(i) => {
+                targets.swap_remove(i);
+            }
+            None => unmatched.push((entity, sprite, transform)),
         }
+    }
 
-        let map_size = TilemapSize { x: 8, y: 8 };
-        let tilemap_entity = commands.spawn(PieceTilemap).id();
-        let mut tile_storage = TileStorage::empty(map_size);
-
-        for x in 0..map_size.x {
-            for y in 0..map_size.y {
-                let board_piece = match board.get(x, y) {
-                    Some(piece) => piece,
-                    None => continue,
-                };
-                let texture = match board_piece.kind {
-                    ChessPieceKind::Pawn => match board_piece.colour {
-                        ChessPieceColour::White => WHITE_PAWN_TEXTURE,
-                        ChessPieceColour::Black => BLACK_PAWN_TEXTURE,
-                    },
-                    ChessPieceKind::Knight => match board_piece.colour {
-                        ChessPieceColour::White => WHITE_KNIGHT_TEXTURE,
-                        ChessPieceColour::Black => BLACK_KNIGHT_TEXTURE,
-                    },
-                    ChessPieceKind::Rook => match board_piece.colour {
-                        ChessPieceColour::White => WHITE_ROOK_TEXTURE,
-                        ChessPieceColour::Black => BLACK_ROOK_TEXTURE,
-                    },
-                    ChessPieceKind::Bishop => match board_piece.colour {
-                        ChessPieceColour::White => WHITE_BISHOP_TEXTURE,
-                        ChessPieceColour::Black => BLACK_BISHOP_TEXTURE,
-                    },
-                    ChessPieceKind::Queen => match board_piece.colour {
-                        ChessPieceColour::White => WHITE_QUEEN_TEXTURE,
-                        ChessPieceColour::Black => BLACK_QUEEN_TEXTURE,
+    for (tile_pos, texture) in targets {
+        let translation = tile_translation(&tile_pos, grid_size, map_type, tilemap_transform);
+
+        // the closest piece of the same kind that's no longer where it was is the one that moved
+        let moved = unmatched
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, sprite, _))| sprite.texture == texture)
+            .min_by_key(|(_, (_, sprite, _))| {
+                sprite.pos.x.abs_diff(tile_pos.x) + sprite.pos.y.abs_diff(tile_pos.y)
+            })
+            .map(|(i, _)| i);
+
+        match moved {
+            Some(i) => {
+                let (entity, mut sprite, transform) = unmatched.swap_remove(i);
+                sprite.pos = tile_pos;
+                commands
+                    .entity(entity)
+                    .insert(PieceAnimation::new(transform.translation, translation));
+            }
+            None => {
+                commands.spawn((
+                    SpriteSheetBundle {
+                        sprite: TextureAtlasSprite::new(texture as usize),
+                        texture_atlas: atlas.0.clone(),
+                        transform: Transform {
+                            translation,
+                            scale: piece_scale(&theme.0, tilemap_transform),
+                            ..default()
+                        },
+                        ..default()
                     },
-                    ChessPieceKind::King => match board_piece.colour {
-                        ChessPieceColour::White => WHITE_KING_TEXTURE,
-                        ChessPieceColour::Black => BLACK_KING_TEXTURE,
+                    PieceSprite {
+                        pos: tile_pos,
+                        texture,
                     },
-                };
-                let tile_pos = TilePos { x, y };
-                let tile_entity = commands
-                    .spawn((TileBundle {
-                        position: tile_pos,
-                        tilemap_id: TilemapId(tilemap_entity),
-                        texture_index: TileTextureIndex(texture as u32),
-                        ..default()
-                    },))
-                    .id();
-                tile_storage.set(&tile_pos, tile_entity);
+                    GameEntity,
+                ));
+            }
+        }
+    }
+
+    // whatever is left over was captured
+    for (entity, _, _) in unmatched {
+        match settings.animation_duration(&config) > 0. {
+            true => {
+                commands
+                    .entity(entity)
+                    .remove::<PieceSprite>()
+                    .remove::<PieceAnimation>()
+                    .insert(FadeOut::default());
             }
+            false => commands.entity(entity).despawn(),
         }
+    }
+}
 
-        let tile_size = TilemapTileSize { x: 96.0, y: 96.0 };
-        let grid_size = tile_size.into();
-        let map_type = TilemapType::default();
-
-        commands.entity(tilemap_entity).insert(TilemapBundle {
-            grid_size,
-            map_type,
-            size: map_size,
-            storage: tile_storage,
-            texture: TilemapTexture::Single(chess_piece_handle.0.clone()),
-            tile_size,
-            transform: get_tilemap_center_transform(&map_size, &grid_size, &map_type, 1.0),
-            ..default()
-        });
+// index of the piece's sprite in the chess piece texture
+pub fn piece_texture(theme: &Theme, board: &Board, piece: &ChessPiece) -> Option<u8> {
+    match piece.kind {
+        ChessPieceKind::Custom(id) => match board.custom_piece(id) {
+            Some(custom) => Some(custom.sprite(piece.colour)),
+            None => {
+                error!("tried to draw undefined custom piece {}", id);
+                None
+            }
+        },
+        kind => theme.pieces.index(piece.colour, kind),
     }
 }
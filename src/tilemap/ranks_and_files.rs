@@ -1,73 +1,98 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
+use crate::rules::notation;
+use crate::theme::CurrentTheme;
+use crate::tilemap::orientation::{BoardAnchor, BoardOrientation};
+use crate::tilemap::GameEntity;
+
+// labels sit just outside the board, centred so that they look the same on a flipped board
+const FILE_LABEL_OFFSET: f32 = 74.0;
+const RANK_LABEL_OFFSET: f32 = 80.0;
+
 #[derive(Component)]
-struct RankFileLabel(Entity);
+pub struct RankFileText;
+
+pub fn restyle_labels(theme: Res<CurrentTheme>, mut text_q: Query<&mut Text, With<RankFileText>>) {
+    if theme.is_changed() {
+        for mut text in text_q.iter_mut() {
+            text.sections[0].style.color = theme.0.colours.labels;
+        }
+    }
+}
 
 pub fn create_labels(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    orientation: Res<BoardOrientation>,
     tilemap_q: Query<
-        (&TilemapGridSize, &TilemapType, &Transform, &TileStorage),
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapType,
+            &Transform,
+            &TileStorage,
+        ),
         With<super::board::BoardTilemap>,
     >,
     tile_q: Query<&TilePos>,
 ) {
-    let font_handle: Handle<Font> = asset_server.load("fonts/UbuntuMono-R.ttf");
-    let (grid_size, map_type, tilemap_transform, tilemap_storage) = tilemap_q.single();
+    let font_handle: Handle<Font> = asset_server.load(theme.0.label_font.as_str());
+    let (map_size, grid_size, map_type, tilemap_transform, tilemap_storage) = tilemap_q.single();
     for tile_entity in tilemap_storage.iter().flatten() {
         let tile_pos = tile_q.get(*tile_entity).unwrap();
-        if (1..7).contains(&tile_pos.x) && (1..7).contains(&tile_pos.y) {
+        if (1..map_size.x - 1).contains(&tile_pos.x) && (1..map_size.y - 1).contains(&tile_pos.y) {
             continue;
         }
         let tile_center = tile_pos.center_in_world(grid_size, map_type);
 
-        let text = get_label_for_pos(
+        let labels = get_label_for_pos(
             tile_pos,
-            *tilemap_transform,
+            map_size,
             tile_center,
             font_handle.clone(),
+            theme.0.colours.labels,
         );
 
-        let label = commands.spawn(text.0).id();
-        commands.entity(*tile_entity).insert(RankFileLabel(label));
-
-        if let Some(text) = text.1 {
-            let label = commands.spawn(text).id();
-            commands.entity(*tile_entity).insert(RankFileLabel(label));
+        for (mut text, anchor) in [Some(labels.0), labels.1].into_iter().flatten() {
+            text.transform = anchor.transform(&orientation, map_size, grid_size, tilemap_transform);
+            commands.spawn((text, anchor, RankFileText, GameEntity));
         }
     }
 }
 
 fn get_label_for_pos(
     tile_pos: &TilePos,
-    tilemap_transform: Transform,
+    map_size: &TilemapSize,
     tile_center: Vec2,
     font_handle: Handle<Font>,
-) -> (Text2dBundle, Option<Text2dBundle>) {
-    if [0, 7].contains(&tile_pos.x) && [0, 7].contains(&tile_pos.y) {
+    colour: Color,
+) -> (
+    (Text2dBundle, BoardAnchor),
+    Option<(Text2dBundle, BoardAnchor)>,
+) {
+    let is_edge_file = [0, map_size.x - 1].contains(&tile_pos.x);
+    let is_edge_rank = [0, map_size.y - 1].contains(&tile_pos.y);
+    if is_edge_file && is_edge_rank {
         (
-            get_file_label(
-                tile_pos,
-                tilemap_transform,
-                tile_center,
-                font_handle.clone(),
-            ),
+            get_file_label(tile_pos, map_size, tile_center, font_handle.clone(), colour),
             Some(get_rank_label(
                 tile_pos,
-                tilemap_transform,
+                map_size,
                 tile_center,
                 font_handle,
+                colour,
             )),
         )
-    } else if [0, 7].contains(&tile_pos.y) {
+    } else if is_edge_rank {
         (
-            get_file_label(tile_pos, tilemap_transform, tile_center, font_handle),
+            get_file_label(tile_pos, map_size, tile_center, font_handle, colour),
             None,
         )
     } else {
         (
-            get_rank_label(tile_pos, tilemap_transform, tile_center, font_handle),
+            get_rank_label(tile_pos, map_size, tile_center, font_handle, colour),
             None,
         )
     }
@@ -75,94 +100,66 @@ fn get_label_for_pos(
 
 fn get_file_label(
     tile_pos: &TilePos,
-    tilemap_transform: Transform,
+    map_size: &TilemapSize,
     tile_center: Vec2,
     font_handle: Handle<Font>,
-) -> Text2dBundle {
-    let text_section = match tile_pos.x {
-        0 => "a",
-        1 => "b",
-        2 => "c",
-        3 => "d",
-        4 => "e",
-        5 => "f",
-        6 => "g",
-        7 => "h",
-        _ => panic!("tried to get file label for x: {}", tile_pos.x),
-    };
-
-    let alignment = match tile_pos.y {
-        0 => TextAlignment::BOTTOM_CENTER,
-        7 => TextAlignment::TOP_CENTER,
-        _ => panic!("tried to get file label alignment for y: {}", tile_pos.y),
-    };
+    colour: Color,
+) -> (Text2dBundle, BoardAnchor) {
+    let text_section = notation::file_name(tile_pos.x).to_string();
 
     let offset = match tile_pos.y {
-        0 => -100.0,
-        7 => 100.0,
+        0 => -FILE_LABEL_OFFSET,
+        y if y == map_size.y - 1 => FILE_LABEL_OFFSET,
         _ => panic!("tried to get file label offset for y: {}", tile_pos.y),
     };
-    let transform = tilemap_transform
-        * Transform::from_translation(Vec3::new(tile_center.x, tile_center.y + offset, 2.0));
+    let anchor = BoardAnchor::new(Vec3::new(tile_center.x, tile_center.y + offset, 2.0));
 
-    Text2dBundle {
-        text: Text::from_section(
-            text_section,
-            TextStyle {
-                font: font_handle,
-                font_size: 48.0,
-                color: Color::WHITE,
-            },
-        )
-        .with_alignment(alignment),
-        transform,
-        ..default()
-    }
+    (
+        Text2dBundle {
+            text: Text::from_section(
+                text_section,
+                TextStyle {
+                    font: font_handle,
+                    font_size: 48.0,
+                    color: colour,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            ..default()
+        },
+        anchor,
+    )
 }
 
 fn get_rank_label(
     tile_pos: &TilePos,
-    tilemap_transform: Transform,
+    map_size: &TilemapSize,
     tile_center: Vec2,
     font_handle: Handle<Font>,
-) -> Text2dBundle {
-    let text_section = match tile_pos.y {
-        0 => "1",
-        1 => "2",
-        2 => "3",
-        3 => "4",
-        4 => "5",
-        5 => "6",
-        6 => "7",
-        7 => "8",
-        _ => panic!("tried to get rank label for y: {}", tile_pos.y),
-    };
-
-    let alignment = match tile_pos.x {
-        0 => TextAlignment::CENTER_LEFT,
-        7 => TextAlignment::CENTER_RIGHT,
-        _ => panic!("tried to get rank label alignment for x: {}", tile_pos.x),
-    };
+    colour: Color,
+) -> (Text2dBundle, BoardAnchor) {
+    let text_section = notation::rank_name(tile_pos.y);
 
     let offset = match tile_pos.x {
-        0 => -100.0,
-        7 => 100.0,
+        0 => -RANK_LABEL_OFFSET,
+        x if x == map_size.x - 1 => RANK_LABEL_OFFSET,
         _ => panic!("tried to get rank label offset for x: {}", tile_pos.x),
     };
-    let transform = tilemap_transform
-        * Transform::from_translation(Vec3::new(tile_center.x + offset, tile_center.y, 2.0));
+    let anchor = BoardAnchor::new(Vec3::new(tile_center.x + offset, tile_center.y, 2.0));
 
-    Text2dBundle {
-        text: Text::from_section(
-            text_section,
-            TextStyle {
-                font: font_handle,
-                font_size: 48.0,
-                color: Color::WHITE,
-            },
-        )
-        .with_alignment(alignment),
-        transform,
-        ..default()
-    }
+    (
+        Text2dBundle {
+            text: Text::from_section(
+                text_section,
+                TextStyle {
+                    font: font_handle,
+                    font_size: 48.0,
+                    color: colour,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            ..default()
+        },
+        anchor,
+    )
 }
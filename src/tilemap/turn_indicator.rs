@@ -1,104 +1,91 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
+use crate::game::board::ChessBoard;
 use crate::game::IsBlackTurn;
+use crate::rules::pieces::ChessPieceColour;
+use crate::theme::{CurrentTheme, Theme};
 use crate::tilemap::board::BoardTilemap;
+use crate::tilemap::orientation::{BoardAnchor, BoardOrientation};
+use crate::tilemap::GameEntity;
 
 #[derive(Component)]
-pub struct TurnIndicator(Entity);
+pub struct TurnIndicator(ChessPieceColour);
 
 pub fn spawn_turn_indicators(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    orientation: Res<BoardOrientation>,
+    board: Res<ChessBoard>,
+    is_black_turn: Res<IsBlackTurn>,
     tilemap_q: Query<
-        (&TilemapGridSize, &TilemapType, &Transform, &TileStorage),
+        (&TilemapSize, &TilemapGridSize, &TilemapType, &Transform),
         With<BoardTilemap>,
     >,
 ) {
-    let font_handle: Handle<Font> = asset_server.load("fonts/FreeSansBold.ttf");
-    let (grid_size, map_type, tilemap_transform, tilemap_storage) = tilemap_q.single();
-
-    let tile_pos = TilePos { x: 0, y: 0 };
-    let tile_entity = tilemap_storage.get(&tile_pos).unwrap();
-    let tile_center = tile_pos.center_in_world(grid_size, map_type);
-
-    let transform = *tilemap_transform
-        * Transform::from_translation(Vec3::new(tile_center.x - 256., tile_center.y, 2.0));
-
-    let turn_indicator_white = commands
-        .spawn(Text2dBundle {
-            text: Text::from_section(
-                "turn",
-                TextStyle {
-                    font: font_handle.clone(),
-                    font_size: 72.,
-                    color: Color::WHITE,
-                },
-            )
-            .with_alignment(TextAlignment::CENTER_LEFT),
-            transform,
-            ..default()
-        })
-        .id();
-
-    commands
-        .entity(tile_entity)
-        .insert(TurnIndicator(turn_indicator_white));
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+    let (map_size, grid_size, map_type, tilemap_transform) = tilemap_q.single();
 
-    let tile_pos = TilePos { x: 0, y: 7 };
-    let tile_entity = tilemap_storage.get(&tile_pos).unwrap();
-    let tile_center = tile_pos.center_in_world(grid_size, map_type);
+    for (colour, tile_pos) in [
+        (ChessPieceColour::White, TilePos { x: 0, y: 0 }),
+        (
+            ChessPieceColour::Black,
+            TilePos {
+                x: 0,
+                y: board.height() - 1,
+            },
+        ),
+    ] {
+        let tile_center = tile_pos.center_in_world(grid_size, map_type);
+        let anchor = BoardAnchor::keeping_side(Vec3::new(tile_center.x - 256., tile_center.y, 2.0));
+        let transform = anchor.transform(&orientation, map_size, grid_size, tilemap_transform);
 
-    let transform = *tilemap_transform
-        * Transform::from_translation(Vec3::new(tile_center.x - 256., tile_center.y, 2.0));
-
-    let turn_indicator_black = commands
-        .spawn(Text2dBundle {
-            text: Text::from_section(
-                "turn",
-                TextStyle {
-                    font: font_handle,
-                    font_size: 72.,
-                    color: *Color::BLACK.as_rgba().set_a(0.3),
-                },
-            )
-            .with_alignment(TextAlignment::CENTER_LEFT),
-            transform,
-            ..default()
-        })
-        .id();
-
-    commands
-        .entity(tile_entity)
-        .insert(TurnIndicator(turn_indicator_black));
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    "turn",
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 72.,
+                        color: turn_indicator_colour(&theme.0, colour, &is_black_turn),
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER_LEFT),
+                transform,
+                ..default()
+            },
+            TurnIndicator(colour),
+            anchor,
+            GameEntity,
+        ));
+    }
 }
 
 pub fn draw_turn_indicators(
     is_black_turn: Res<IsBlackTurn>,
-    turn_indicator_q: Query<&TurnIndicator>,
-    mut colour_q: Query<&mut Text>,
+    theme: Res<CurrentTheme>,
+    mut turn_indicator_q: Query<(&TurnIndicator, &mut Text)>,
 ) {
-    if is_black_turn.is_changed() {
-        for turn_indicator in turn_indicator_q.iter() {
-            if let Ok(mut text) = colour_q.get_mut(turn_indicator.0) {
-                let colour = &mut text.sections[0].style.color;
-                match is_black_turn.0 {
-                    true => {
-                        if colour.r() > 0. {
-                            colour.set_a(0.3);
-                        } else {
-                            colour.set_a(1.);
-                        }
-                    }
-                    false => {
-                        if colour.r() > 0. {
-                            colour.set_a(1.);
-                        } else {
-                            colour.set_a(0.3);
-                        }
-                    }
-                };
-            }
+    if is_black_turn.is_changed() || theme.is_changed() {
+        for (turn_indicator, mut text) in turn_indicator_q.iter_mut() {
+            text.sections[0].style.color =
+                turn_indicator_colour(&theme.0, turn_indicator.0, &is_black_turn);
         }
     }
 }
+
+// the indicator of the side not to move is faded out
+fn turn_indicator_colour(
+    theme: &Theme,
+    colour: ChessPieceColour,
+    is_black_turn: &IsBlackTurn,
+) -> Color {
+    let mut indicator_colour = match colour {
+        ChessPieceColour::White => theme.colours.white_turn_indicator,
+        ChessPieceColour::Black => theme.colours.black_turn_indicator,
+    };
+    let is_turn = (colour == ChessPieceColour::Black) == is_black_turn.0;
+    indicator_colour.set_a(if is_turn { 1. } else { 0.3 });
+    indicator_colour
+}
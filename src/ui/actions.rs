@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::game::actions::{DrawOffer, GameAction, GameActionEvent};
+use crate::game::outcome::GameOutcome;
+use crate::game::state::GameSetup;
+use crate::rules::pieces::ChessPieceColour;
+use crate::theme::CurrentTheme;
+use crate::tilemap::orientation::BoardOrientation;
+use crate::tilemap::GameEntity;
+use crate::ui::BUTTON_COLOUR;
+
+#[derive(Component)]
+pub struct ActionButton {
+    colour: ChessPieceColour,
+    action: GameAction,
+}
+
+// the column of buttons for one side
+#[derive(Component)]
+pub struct ActionButtons(ChessPieceColour);
+
+#[derive(Component)]
+pub struct OutcomeText;
+
+// the computer doesn't get any buttons, it never resigns and answers draw offers on its own
+pub fn spawn_action_buttons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    orientation: Res<BoardOrientation>,
+    setup: Res<GameSetup>,
+) {
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+
+    for colour in [ChessPieceColour::Black, ChessPieceColour::White] {
+        if setup.is_computer(colour) {
+            continue;
+        }
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: action_buttons_position(colour, &orientation),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                },
+                ActionButtons(colour),
+                GameEntity,
+            ))
+            .with_children(|parent| {
+                for (action, label) in [
+                    (GameAction::Resign, "Resign"),
+                    (GameAction::OfferDraw, "Offer draw"),
+                    (GameAction::AcceptDraw, "Accept draw"),
+                    (GameAction::DeclineDraw, "Decline draw"),
+                ] {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(200.), Val::Px(48.)),
+                                    margin: UiRect::all(Val::Px(4.)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    display: button_display(action, colour, None, false),
+                                    ..default()
+                                },
+                                background_color: BUTTON_COLOUR.into(),
+                                ..default()
+                            },
+                            ActionButton { colour, action },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                label,
+                                TextStyle {
+                                    font: font_handle.clone(),
+                                    font_size: 28.,
+                                    color: Color::WHITE,
+                                },
+                            ));
+                        });
+                }
+            });
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: font_handle,
+                font_size: 48.,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(20.),
+                top: Val::Px(20.),
+                ..default()
+            },
+            ..default()
+        }),
+        OutcomeText,
+        GameEntity,
+    ));
+}
+
+// each side's buttons go in the right corner on their side of the board, the bottom one for
+// the side at the bottom
+fn action_buttons_position(colour: ChessPieceColour, orientation: &BoardOrientation) -> UiRect {
+    match colour == orientation.0 {
+        true => UiRect {
+            right: Val::Px(20.),
+            bottom: Val::Px(20.),
+            ..default()
+        },
+        false => UiRect {
+            right: Val::Px(20.),
+            top: Val::Px(20.),
+            ..default()
+        },
+    }
+}
+
+pub fn place_action_buttons(
+    orientation: Res<BoardOrientation>,
+    mut buttons_q: Query<(&ActionButtons, &mut Style)>,
+) {
+    if orientation.is_changed() {
+        for (buttons, mut style) in buttons_q.iter_mut() {
+            style.position = action_buttons_position(buttons.0, &orientation);
+        }
+    }
+}
+
+// which buttons make sense depends on whether there's a draw offer and who made it
+fn button_display(
+    action: GameAction,
+    colour: ChessPieceColour,
+    draw_offer: Option<ChessPieceColour>,
+    is_game_over: bool,
+) -> Display {
+    let shown = !is_game_over
+        && match action {
+            GameAction::Resign => true,
+            GameAction::OfferDraw => draw_offer.is_none(),
+            GameAction::AcceptDraw | GameAction::DeclineDraw => draw_offer == Some(!colour),
+        };
+
+    match shown {
+        true => Display::Flex,
+        false => Display::None,
+    }
+}
+
+// the buttons of a side played over the network are only there to show what the other player
+// can do, and nothing is done for the computer
+pub fn handle_action_buttons(
+    interaction_q: Query<(&Interaction, &ActionButton), Changed<Interaction>>,
+    setup: Res<GameSetup>,
+    mut action_ev: EventWriter<GameActionEvent>,
+) {
+    for (interaction, button) in interaction_q.iter() {
+        if let Interaction::Clicked = interaction {
+            if setup.is_remote(button.colour) || setup.is_computer(button.colour) {
+                continue;
+            }
+            action_ev.send(GameActionEvent {
+                colour: button.colour,
+                action: button.action,
+            });
+        }
+    }
+}
+
+pub fn update_action_buttons(
+    draw_offer: Res<DrawOffer>,
+    outcome: Res<GameOutcome>,
+    mut button_q: Query<(&ActionButton, &mut Style)>,
+    mut outcome_text_q: Query<&mut Text, With<OutcomeText>>,
+) {
+    if draw_offer.is_changed() || outcome.is_changed() {
+        for (button, mut style) in button_q.iter_mut() {
+            style.display = button_display(
+                button.action,
+                button.colour,
+                draw_offer.0,
+                outcome.0.is_some(),
+            );
+        }
+
+        for mut text in outcome_text_q.iter_mut() {
+            text.sections[0].value = match (outcome.0, draw_offer.0) {
+                (Some(outcome), _) => outcome.to_string(),
+                (None, Some(ChessPieceColour::White)) => String::from("White offers a draw"),
+                (None, Some(ChessPieceColour::Black)) => String::from("Black offers a draw"),
+                (None, None) => String::new(),
+            };
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::game::board::ChessBoard;
+use crate::game::book::Book;
+use crate::theme::CurrentTheme;
+use crate::tilemap::GameEntity;
+
+// the most moves listed at once
+const MAX_BOOK_MOVES: usize = 8;
+
+#[derive(Component)]
+pub struct BookPanelText;
+
+pub fn spawn_book_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+) {
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: font_handle,
+                font_size: 28.,
+                color: theme.0.colours.text,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(20.),
+                top: Val::Px(100.),
+                ..default()
+            },
+            ..default()
+        }),
+        BookPanelText,
+        GameEntity,
+    ));
+}
+
+// the book's moves for the current position with how often they're played, nothing once the
+// game has left the book
+pub fn update_book_panel(
+    book: Res<Book>,
+    board: Res<ChessBoard>,
+    theme: Res<CurrentTheme>,
+    added_q: Query<(), Added<BookPanelText>>,
+    mut text_q: Query<&mut Text, With<BookPanelText>>,
+) {
+    if !book.is_changed() && !board.is_changed() && !theme.is_changed() && added_q.is_empty() {
+        return;
+    }
+    let moves = match &book.book {
+        Some(book) => book.moves(&board),
+        None => Vec::new(),
+    };
+    let total: u32 = moves.iter().map(|_move| u32::from(_move.weight)).sum();
+    let lines: Vec<String> = moves
+        .iter()
+        .take(MAX_BOOK_MOVES)
+        .map(|book_move| {
+            let share = match total {
+                0 => 0,
+                total => u32::from(book_move.weight) * 100 / total,
+            };
+            format!("{:<8}{:>3}%", board.san(&book_move._move), share)
+        })
+        .collect();
+
+    for mut text in text_q.iter_mut() {
+        text.sections[0].style.color = theme.0.colours.text;
+        text.sections[0].value = match lines.is_empty() {
+            true => String::new(),
+            false => format!("Book\n{}", lines.join("\n")),
+        };
+    }
+}
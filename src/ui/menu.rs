@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+
+use crate::game::correspondence::{new_correspondence_game, Correspondence, CorrespondenceDir};
+use crate::game::network::{host_game, join_game, watch_game, NetworkGame};
+use crate::game::save::{resume_game, PendingGame, SaveDir};
+use crate::game::state::{AppState, GameSetup, Opponent};
+use crate::rules::board::Variant;
+use crate::rules::clock::TimeControl;
+use crate::rules::pieces::ChessPieceColour;
+use crate::settings::Settings;
+use crate::theme::CurrentTheme;
+use crate::ui::saves::SavesScreenMode;
+use crate::ui::{screen_node, spawn_button};
+
+// the time controls that can be picked in the menu, None is a game without a clock
+const TIME_CONTROLS: [Option<&str>; 7] = [
+    None,
+    Some("1+0"),
+    Some("3+2"),
+    Some("5+3"),
+    Some("10+5"),
+    Some("25b10"),
+    Some("90d30"),
+];
+
+#[derive(Component)]
+pub struct MainMenuScreen;
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MenuButton {
+    // only there if a game was left unfinished
+    Continue,
+    Variant,
+    Opponent,
+    TimeControl,
+    Settings,
+    Start,
+    Load,
+    // network games, on the address in the settings
+    Host,
+    Join,
+    Watch,
+    // a game played by sending a file back and forth, received ones are dropped onto the window
+    Correspondence,
+}
+
+impl MenuButton {
+    fn label(&self, setup: &GameSetup) -> String {
+        match self {
+            MenuButton::Continue => String::from("Continue"),
+            MenuButton::Variant => format!(
+                "Variant: {}",
+                match setup.variant {
+                    Variant::Standard => "standard",
+                    Variant::Capablanca => "Capablanca",
+                    Variant::LosAlamos => "Los Alamos",
+                }
+            ),
+            MenuButton::Opponent => format!(
+                "Opponent: {}",
+                match setup.opponent {
+                    Opponent::Human => "human",
+                    Opponent::Computer(ChessPieceColour::White) => "computer as white",
+                    Opponent::Computer(ChessPieceColour::Black) => "computer as black",
+                    Opponent::Remote(_) | Opponent::Spectator => "network",
+                }
+            ),
+            MenuButton::TimeControl => match setup.time_control {
+                Some(time_control) => format!("Clock: {}", time_control),
+                None => String::from("Clock: none"),
+            },
+            MenuButton::Settings => String::from("Settings"),
+            MenuButton::Start => String::from("Start"),
+            MenuButton::Load => String::from("Load game"),
+            MenuButton::Host => String::from("Host game"),
+            MenuButton::Join => String::from("Join game"),
+            MenuButton::Watch => String::from("Watch game"),
+            MenuButton::Correspondence => String::from("New correspondence game"),
+        }
+    }
+}
+
+pub fn spawn_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    setup: Res<GameSetup>,
+    save_dir: Res<SaveDir>,
+) {
+    let has_autosave = save_dir.autosave().is_some_and(|path| path.exists());
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+
+    commands
+        .spawn((screen_node(Color::NONE), MainMenuScreen))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Chess",
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 96.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(24.)),
+                    ..default()
+                }),
+            );
+            if has_autosave {
+                spawn_button(
+                    parent,
+                    font_handle.clone(),
+                    &MenuButton::Continue.label(&setup),
+                    MenuButton::Continue,
+                );
+            }
+            for button in [
+                MenuButton::Variant,
+                MenuButton::Opponent,
+                MenuButton::TimeControl,
+                MenuButton::Settings,
+                MenuButton::Start,
+                MenuButton::Load,
+                MenuButton::Host,
+                MenuButton::Join,
+                MenuButton::Watch,
+                MenuButton::Correspondence,
+            ] {
+                spawn_button(parent, font_handle.clone(), &button.label(&setup), button);
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_menu_buttons(
+    interaction_q: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    save_dir: Res<SaveDir>,
+    settings: Res<Settings>,
+    mut setup: ResMut<GameSetup>,
+    mut pending: ResMut<PendingGame>,
+    mut network: ResMut<NetworkGame>,
+    correspondence_dir: Res<CorrespondenceDir>,
+    mut correspondence: ResMut<Correspondence>,
+    mut saves_mode: ResMut<SavesScreenMode>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for (interaction, button) in interaction_q.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            MenuButton::Continue => {
+                let path = match save_dir.autosave() {
+                    Some(path) => path,
+                    None => continue,
+                };
+                match resume_game(&path, &mut setup, &mut pending) {
+                    Ok(()) => {
+                        let _ = state.set(AppState::Setup);
+                    }
+                    Err(err) => warn!("{}", err),
+                }
+            }
+            MenuButton::Variant => {
+                setup.variant = match setup.variant {
+                    Variant::Standard => Variant::Capablanca,
+                    Variant::Capablanca => Variant::LosAlamos,
+                    Variant::LosAlamos => Variant::Standard,
+                }
+            }
+            MenuButton::Opponent => {
+                setup.opponent = match setup.opponent {
+                    Opponent::Human => Opponent::Computer(ChessPieceColour::Black),
+                    Opponent::Computer(ChessPieceColour::Black) => {
+                        Opponent::Computer(ChessPieceColour::White)
+                    }
+                    Opponent::Computer(ChessPieceColour::White)
+                    | Opponent::Remote(_)
+                    | Opponent::Spectator => Opponent::Human,
+                }
+            }
+            MenuButton::TimeControl => {
+                let time_controls: Vec<Option<TimeControl>> = TIME_CONTROLS
+                    .iter()
+                    .map(|time_control| time_control.map(|tc| tc.parse().unwrap()))
+                    .collect();
+                // a time control given on the command line might not be one of the presets,
+                // in which case cycling starts over from the first one
+                let next = time_controls
+                    .iter()
+                    .position(|time_control| time_control == &setup.time_control)
+                    .map_or(0, |i| (i + 1) % time_controls.len());
+                setup.time_control = time_controls[next];
+            }
+            MenuButton::Settings => {
+                let _ = state.push(AppState::Settings);
+            }
+            MenuButton::Start => {
+                let _ = state.set(AppState::Setup);
+            }
+            MenuButton::Load => {
+                *saves_mode = SavesScreenMode::Load;
+                let _ = state.push(AppState::Saves);
+            }
+            MenuButton::Host => {
+                match host_game(&settings.server_address, &mut setup, &mut network) {
+                    Ok(()) => {
+                        let _ = state.set(AppState::Setup);
+                    }
+                    Err(err) => warn!("{}", err),
+                }
+            }
+            // the game starts once the host has sent it, see `wait_for_game`
+            MenuButton::Join => {
+                if let Err(err) = join_game(&settings.server_address, &mut network) {
+                    warn!("{}", err);
+                }
+            }
+            MenuButton::Watch => {
+                if let Err(err) = watch_game(
+                    &settings.server_address,
+                    settings.watched_game,
+                    &mut network,
+                ) {
+                    warn!("{}", err);
+                }
+            }
+            MenuButton::Correspondence => {
+                match new_correspondence_game(&correspondence_dir, &mut setup, &mut correspondence)
+                {
+                    Ok(()) => {
+                        let _ = state.set(AppState::Setup);
+                    }
+                    Err(err) => warn!("{}", err),
+                }
+            }
+        }
+    }
+}
+
+pub fn update_menu_labels(
+    setup: Res<GameSetup>,
+    button_q: Query<(&MenuButton, &Children)>,
+    mut text_q: Query<&mut Text>,
+) {
+    if setup.is_changed() {
+        for (button, children) in button_q.iter() {
+            for child in children.iter() {
+                if let Ok(mut text) = text_q.get_mut(*child) {
+                    text.sections[0].value = button.label(&setup);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+pub mod actions;
+pub mod book;
+pub mod menu;
+pub mod move_input;
+pub mod opening;
+pub mod overlays;
+pub mod saves;
+pub mod settings;
+
+pub const BUTTON_COLOUR: Color = Color::rgb(0.25, 0.25, 0.25);
+pub const HOVERED_BUTTON_COLOUR: Color = Color::rgb(0.35, 0.35, 0.35);
+
+pub fn highlight_buttons(
+    mut interaction_q: Query<(&Interaction, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, mut background_colour) in interaction_q.iter_mut() {
+        *background_colour = match interaction {
+            Interaction::Hovered | Interaction::Clicked => HOVERED_BUTTON_COLOUR.into(),
+            Interaction::None => BUTTON_COLOUR.into(),
+        };
+    }
+}
+
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    font_handle: Handle<Font>,
+    label: &str,
+    marker: impl Component,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(320.), Val::Px(56.)),
+                    margin: UiRect::all(Val::Px(6.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOUR.into(),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font_handle,
+                    font_size: 32.,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+// a full screen column that centers whatever is put in it
+pub fn screen_node(background_colour: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: background_colour.into(),
+        ..default()
+    }
+}
+
+pub fn despawn_with<T: Component>(mut commands: Commands, entity_q: Query<Entity, With<T>>) {
+    for entity in entity_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::game::keyboard::MoveTextInput;
+use crate::theme::{CurrentTheme, ThemeColours};
+use crate::tilemap::GameEntity;
+
+#[derive(Component)]
+pub struct MoveInputText;
+
+pub fn spawn_move_input(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    input: Res<MoveTextInput>,
+) {
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+
+    commands.spawn((
+        TextBundle::from_sections(
+            move_input_sections(&input, &theme.0.colours)
+                .into_iter()
+                .map(|(value, color)| {
+                    TextSection::new(
+                        value,
+                        TextStyle {
+                            font: font_handle.clone(),
+                            font_size: 32.,
+                            color,
+                        },
+                    )
+                }),
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(20.),
+                bottom: Val::Px(20.),
+                ..default()
+            },
+            ..default()
+        }),
+        MoveInputText,
+        GameEntity,
+    ));
+}
+
+// the text being typed, followed by why the last move didn't work
+fn move_input_sections(input: &MoveTextInput, colours: &ThemeColours) -> [(String, Color); 2] {
+    let text = match input.active {
+        true => (format!("Move: {}_", input.text), colours.text),
+        false => (String::from("Tab: type a move"), colours.hint_text),
+    };
+    let error = match &input.error {
+        Some(err) => (format!("  {}", err), colours.error_text),
+        None => (String::new(), colours.error_text),
+    };
+    [text, error]
+}
+
+pub fn update_move_input(
+    input: Res<MoveTextInput>,
+    theme: Res<CurrentTheme>,
+    mut text_q: Query<&mut Text, With<MoveInputText>>,
+) {
+    if input.is_changed() || theme.is_changed() {
+        for mut text in text_q.iter_mut() {
+            for (section, (value, color)) in text
+                .sections
+                .iter_mut()
+                .zip(move_input_sections(&input, &theme.0.colours))
+            {
+                section.value = value;
+                section.style.color = color;
+            }
+        }
+    }
+}
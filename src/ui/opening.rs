@@ -0,0 +1,72 @@
+use std::iter;
+
+use bevy::prelude::*;
+
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::rules::eco::OpeningTable;
+use crate::theme::CurrentTheme;
+use crate::tilemap::GameEntity;
+
+#[derive(Component)]
+pub struct OpeningText;
+
+// a row across the top of the window, so that the name is centered above the board
+pub fn spawn_opening_name(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+) {
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Auto),
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(20.),
+                        ..default()
+                    },
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font_handle,
+                        font_size: 32.,
+                        color: theme.0.colours.text,
+                    },
+                ),
+                OpeningText,
+            ));
+        });
+}
+
+// the opening the game went through, e.g. `C50 Italian Game`
+pub fn update_opening_name(
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    theme: Res<CurrentTheme>,
+    added_q: Query<(), Added<OpeningText>>,
+    mut text_q: Query<&mut Text, With<OpeningText>>,
+) {
+    if !board.is_changed() && !theme.is_changed() && added_q.is_empty() {
+        return;
+    }
+    let positions = history.0.iter().chain(iter::once(&board.0));
+    let name = OpeningTable::bundled()
+        .recognise(positions)
+        .map_or(String::new(), |opening| opening.to_string());
+    for mut text in text_q.iter_mut() {
+        text.sections[0].style.color = theme.0.colours.text;
+        text.sections[0].value = name.clone();
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::game::outcome::GameOutcome;
+use crate::game::state::AppState;
+use crate::theme::CurrentTheme;
+use crate::ui::saves::SavesScreenMode;
+use crate::ui::{screen_node, spawn_button};
+
+const OVERLAY_COLOUR: Color = Color::rgba(0., 0., 0., 0.6);
+
+// the pause and game over screens, spawned on top of the board
+#[derive(Component)]
+pub struct Overlay;
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverlayButton {
+    Resume,
+    Save,
+    Settings,
+    Review,
+    Rematch,
+    BackToResult,
+    MainMenu,
+}
+
+fn spawn_overlay(
+    commands: &mut Commands,
+    font_handle: Handle<Font>,
+    title: &str,
+    buttons: &[(OverlayButton, &str)],
+) {
+    commands
+        .spawn((screen_node(OVERLAY_COLOUR), Overlay))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    title,
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 64.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(24.)),
+                    ..default()
+                }),
+            );
+            for (button, label) in buttons {
+                spawn_button(parent, font_handle.clone(), label, *button);
+            }
+        });
+}
+
+pub fn spawn_pause_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+) {
+    spawn_overlay(
+        &mut commands,
+        asset_server.load(theme.0.font.as_str()),
+        "Paused",
+        &[
+            (OverlayButton::Resume, "Resume"),
+            (OverlayButton::Save, "Save game"),
+            (OverlayButton::Settings, "Settings"),
+            (OverlayButton::MainMenu, "Main menu"),
+        ],
+    );
+}
+
+pub fn spawn_game_over_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    outcome: Res<GameOutcome>,
+) {
+    let title = outcome
+        .0
+        .map_or(String::from("Game over"), |outcome| outcome.to_string());
+    spawn_overlay(
+        &mut commands,
+        asset_server.load(theme.0.font.as_str()),
+        &title,
+        &[
+            (OverlayButton::Review, "Review game"),
+            (OverlayButton::Rematch, "Rematch"),
+            (OverlayButton::MainMenu, "Main menu"),
+        ],
+    );
+}
+
+// unlike the other overlays this one leaves the board visible, it only sits in a corner
+pub fn spawn_review_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+) {
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(20.),
+                        bottom: Val::Px(20.),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            Overlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Left/right arrow keys to step through the moves",
+                TextStyle {
+                    font: font_handle.clone(),
+                    font_size: 24.,
+                    color: Color::WHITE,
+                },
+            ));
+            spawn_button(parent, font_handle, "Back", OverlayButton::BackToResult);
+        });
+}
+
+pub fn handle_overlay_buttons(
+    interaction_q: Query<(&Interaction, &OverlayButton), Changed<Interaction>>,
+    mut saves_mode: ResMut<SavesScreenMode>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for (interaction, button) in interaction_q.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let _ = match button {
+            OverlayButton::Resume => state.pop(),
+            OverlayButton::Save => {
+                *saves_mode = SavesScreenMode::Save;
+                state.push(AppState::Saves)
+            }
+            OverlayButton::Settings => state.push(AppState::Settings),
+            OverlayButton::Review => state.set(AppState::Review),
+            OverlayButton::Rematch => state.set(AppState::Setup),
+            OverlayButton::BackToResult => state.set(AppState::GameOver),
+            // replace rather than set, so that a paused game doesn't stay on the stack
+            OverlayButton::MainMenu => state.replace(AppState::MainMenu),
+        };
+    }
+}
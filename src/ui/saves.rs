@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+
+use crate::game::annotations::BoardAnnotations;
+use crate::game::board::{BoardHistory, ChessBoard};
+use crate::game::clock::GameClock;
+use crate::game::save::{resume_game, PendingGame, SaveDir, SavedGame, SAVE_SLOTS};
+use crate::game::state::{AppState, GameSetup};
+use crate::theme::CurrentTheme;
+use crate::ui::{screen_node, spawn_button};
+
+const SAVES_BACKGROUND_COLOUR: Color = Color::rgba(0., 0., 0., 0.85);
+
+// the save slots are opened from the main menu to load a game, or from the pause screen to save
+// the one being played
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SavesScreenMode {
+    #[default]
+    Load,
+    Save,
+}
+
+#[derive(Component)]
+pub struct SavesScreen;
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SavesButton {
+    // numbered from 1
+    Slot(u32),
+    Back,
+}
+
+impl SavesButton {
+    fn label(&self, save_dir: &SaveDir) -> String {
+        match self {
+            SavesButton::Slot(slot) => {
+                let game = save_dir
+                    .slot(*slot)
+                    .filter(|path| path.exists())
+                    .and_then(|path| SavedGame::load(path).ok());
+                match game {
+                    Some(game) => format!(
+                        "Slot {}: {}, move {}",
+                        slot,
+                        game.variant,
+                        game.ply_count() / 2 + 1
+                    ),
+                    None => format!("Slot {}: empty", slot),
+                }
+            }
+            SavesButton::Back => String::from("Back"),
+        }
+    }
+}
+
+pub fn spawn_saves_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    mode: Res<SavesScreenMode>,
+    save_dir: Res<SaveDir>,
+) {
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+    let title = match *mode {
+        SavesScreenMode::Load => "Load game",
+        SavesScreenMode::Save => "Save game",
+    };
+
+    commands
+        .spawn((screen_node(SAVES_BACKGROUND_COLOUR), SavesScreen))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    title,
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 64.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(24.)),
+                    ..default()
+                }),
+            );
+            for button in (1..=SAVE_SLOTS)
+                .map(SavesButton::Slot)
+                .chain([SavesButton::Back])
+            {
+                spawn_button(
+                    parent,
+                    font_handle.clone(),
+                    &button.label(&save_dir),
+                    button,
+                );
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_saves_buttons(
+    interaction_q: Query<(&Interaction, &SavesButton, &Children), Changed<Interaction>>,
+    mode: Res<SavesScreenMode>,
+    save_dir: Res<SaveDir>,
+    board: Res<ChessBoard>,
+    history: Res<BoardHistory>,
+    clock: Res<GameClock>,
+    annotations: Res<BoardAnnotations>,
+    mut setup: ResMut<GameSetup>,
+    mut pending: ResMut<PendingGame>,
+    mut state: ResMut<State<AppState>>,
+    mut text_q: Query<&mut Text>,
+) {
+    for (interaction, button, children) in interaction_q.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let slot = match button {
+            SavesButton::Slot(slot) => *slot,
+            // back to whichever screen the save slots were opened from
+            SavesButton::Back => {
+                let _ = state.pop();
+                continue;
+            }
+        };
+        let path = match save_dir.slot(slot) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        match *mode {
+            SavesScreenMode::Save => {
+                let game = SavedGame::new(&setup, &board, &history, &clock, &annotations);
+                if let Err(err) = game.save(&path) {
+                    warn!("{}", err);
+                }
+                for child in children.iter() {
+                    if let Ok(mut text) = text_q.get_mut(*child) {
+                        text.sections[0].value = button.label(&save_dir);
+                    }
+                }
+            }
+            SavesScreenMode::Load if path.exists() => {
+                match resume_game(&path, &mut setup, &mut pending) {
+                    // replace rather than set, so that the main menu doesn't stay on the stack
+                    Ok(()) => {
+                        let _ = state.replace(AppState::Setup);
+                    }
+                    Err(err) => warn!("{}", err),
+                }
+            }
+            SavesScreenMode::Load => (),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+
+use crate::game::state::AppState;
+use crate::settings::{PreferredOrientation, Settings};
+use crate::theme::{CurrentTheme, ThemeHandles};
+use crate::ui::{screen_node, spawn_button};
+
+const SETTINGS_BACKGROUND_COLOUR: Color = Color::rgba(0., 0., 0., 0.85);
+
+#[derive(Component)]
+pub struct SettingsScreen;
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SettingsButton {
+    HighlightLastMove,
+    LastMoveArrow,
+    Animations,
+    Orientation,
+    Theme,
+    Back,
+}
+
+impl SettingsButton {
+    fn label(&self, settings: &Settings, theme: &CurrentTheme) -> String {
+        let on_off = |on: bool| match on {
+            true => "on",
+            false => "off",
+        };
+        match self {
+            SettingsButton::HighlightLastMove => format!(
+                "Highlight last move: {}",
+                on_off(settings.highlight_last_move)
+            ),
+            SettingsButton::LastMoveArrow => {
+                format!("Last move arrow: {}", on_off(settings.last_move_arrow))
+            }
+            SettingsButton::Animations => format!("Animations: {}", on_off(settings.animations)),
+            SettingsButton::Orientation => format!(
+                "Board orientation: {}",
+                match settings.orientation {
+                    PreferredOrientation::Player => "player",
+                    PreferredOrientation::White => "white",
+                    PreferredOrientation::Black => "black",
+                }
+            ),
+            SettingsButton::Theme => format!("Theme: {}", theme.0.name),
+            SettingsButton::Back => String::from("Back"),
+        }
+    }
+}
+
+pub fn spawn_settings_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<CurrentTheme>,
+    settings: Res<Settings>,
+) {
+    let font_handle: Handle<Font> = asset_server.load(theme.0.font.as_str());
+
+    commands
+        .spawn((screen_node(SETTINGS_BACKGROUND_COLOUR), SettingsScreen))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Settings",
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 64.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(24.)),
+                    ..default()
+                }),
+            );
+            for button in [
+                SettingsButton::HighlightLastMove,
+                SettingsButton::LastMoveArrow,
+                SettingsButton::Animations,
+                SettingsButton::Orientation,
+                SettingsButton::Theme,
+                SettingsButton::Back,
+            ] {
+                spawn_button(
+                    parent,
+                    font_handle.clone(),
+                    &button.label(&settings, &theme),
+                    button,
+                );
+            }
+        });
+}
+
+pub fn handle_settings_buttons(
+    interaction_q: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    themes: Res<ThemeHandles>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for (interaction, button) in interaction_q.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            SettingsButton::HighlightLastMove => {
+                settings.highlight_last_move = !settings.highlight_last_move
+            }
+            SettingsButton::LastMoveArrow => settings.last_move_arrow = !settings.last_move_arrow,
+            SettingsButton::Animations => settings.animations = !settings.animations,
+            // applies from the next game on
+            SettingsButton::Orientation => {
+                settings.orientation = match settings.orientation {
+                    PreferredOrientation::Player => PreferredOrientation::White,
+                    PreferredOrientation::White => PreferredOrientation::Black,
+                    PreferredOrientation::Black => PreferredOrientation::Player,
+                }
+            }
+            // cycles through the themes from the config
+            SettingsButton::Theme => {
+                let next = themes
+                    .0
+                    .iter()
+                    .position(|(path, _)| *path == settings.theme)
+                    .map_or(0, |index| index + 1);
+                if let Some((path, _)) = themes.0.get(next).or_else(|| themes.0.first()) {
+                    settings.theme = path.clone();
+                }
+            }
+            // back to whichever screen the settings were opened from
+            SettingsButton::Back => {
+                let _ = state.pop();
+            }
+        }
+    }
+}
+
+pub fn update_settings_labels(
+    settings: Res<Settings>,
+    theme: Res<CurrentTheme>,
+    button_q: Query<(&SettingsButton, &Children)>,
+    mut text_q: Query<&mut Text>,
+) {
+    if settings.is_changed() || theme.is_changed() {
+        for (button, children) in button_q.iter() {
+            for child in children.iter() {
+                if let Ok(mut text) = text_q.get_mut(*child) {
+                    text.sections[0].value = button.label(&settings, &theme);
+                }
+            }
+        }
+    }
+}
@@ -3,13 +3,17 @@ use bevy::window::WindowResized;
 
 pub mod cursor;
 
+// the size of the screen everything is laid out for, smaller windows are zoomed out to fit
+const LAYOUT_WIDTH: f32 = 1600.;
+const LAYOUT_HEIGHT: f32 = 900.;
+
 pub fn on_window_resize(
     mut camera_q: Query<&mut OrthographicProjection>,
     mut window_resized_ev: EventReader<WindowResized>,
 ) {
     for ev in window_resized_ev.iter() {
-        let width_scale = (1600. / ev.width).max(1.);
-        let height_scale = (900. / ev.height).max(1.);
+        let width_scale = (LAYOUT_WIDTH / ev.width).max(1.);
+        let height_scale = (LAYOUT_HEIGHT / ev.height).max(1.);
         camera_q.single_mut().scale = width_scale.max(height_scale);
     }
 }